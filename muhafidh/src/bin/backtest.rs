@@ -0,0 +1,87 @@
+// ─────────────────────────────────────────────────────────────────────────────
+//  Funding Signal Backtest
+//  Part of the Al-Hafiz Project, the Guardian Layer of BismillahDAO.
+//
+//  Groups stored launches into cohorts by funding source, risk band, dev
+//  label or platform and reports how each cohort turned out.
+//
+//  In the name of Allah, the Most Gracious, the Most Merciful.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use clap::Parser;
+use muhafidh::config::load_config;
+use muhafidh::error::Result;
+use muhafidh::error::anyhow;
+use muhafidh::model::backtest::CohortDimension;
+use muhafidh::model::backtest::reports_to_csv;
+use muhafidh::model::backtest::run_backtest;
+use muhafidh::storage::postgres::make_postgres_client;
+use tracing::info;
+
+#[derive(Debug, Parser)]
+#[command(name = "backtest", about = "Backtest funding signals against token outcomes")]
+struct Args {
+    /// Cohort dimension(s): funding_source, risk_band, dev_label or platform
+    #[arg(short, long, num_args = 1.., default_values = ["funding_source", "risk_band", "dev_label", "platform"])]
+    cohort: Vec<String>,
+
+    /// Only launches created at or after this unix timestamp, defaults to 30 days ago
+    #[arg(long)]
+    from: Option<u64>,
+
+    /// Only launches created before this unix timestamp, defaults to now
+    #[arg(long)]
+    to: Option<u64>,
+
+    /// Only launches on this platform, e.g. pumpfun
+    #[arg(short, long)]
+    platform: Option<String>,
+
+    /// Report format: csv or json
+    #[arg(short, long, default_value = "csv")]
+    format: String,
+
+    /// Output file, defaults to backtest.<format>
+    #[arg(short, long)]
+    output: Option<String>,
+
+    #[arg(long, default_value = "Config.toml")]
+    config: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = load_config(&args.config).await?;
+
+    let dimensions = args
+        .cohort
+        .iter()
+        .map(|cohort| cohort.parse::<CohortDimension>())
+        .collect::<Result<Vec<CohortDimension>>>()?;
+    if !matches!(args.format.as_str(), "csv" | "json") {
+        return Err(anyhow!("unsupported_report_format::{}", args.format));
+    }
+
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    let to = args.to.unwrap_or(now);
+    let from = args.from.unwrap_or_else(|| to.saturating_sub(30 * 86_400));
+
+    let postgres = make_postgres_client("backtest", &config.storage_postgres).await?;
+    let samples = postgres
+        .cex_analytics
+        .get_backtest_samples(from, to, args.platform.as_deref())
+        .await?;
+    let reports = run_backtest(&samples, &dimensions, now);
+
+    let report = match args.format.as_str() {
+        "json" => serde_json::to_string_pretty(&reports)?,
+        _ => reports_to_csv(&reports),
+    };
+    let output = args.output.unwrap_or_else(|| format!("backtest.{}", args.format));
+    std::fs::write(&output, report)?;
+
+    info!("backtest_written::launches::{}::cohorts::{}::output::{}", samples.len(), reports.len(), output);
+    println!("Backtested {} launches into {} cohorts, written to {}", samples.len(), reports.len(), output);
+    Ok(())
+}
@@ -0,0 +1,120 @@
+// ─────────────────────────────────────────────────────────────────────────────
+//  Dataset Exporter
+//  Part of the Al-Hafiz Project, the Guardian Layer of BismillahDAO.
+//
+//  Streams tokens, CEX relations, funding graph nodes and edges and price
+//  history into day-partitioned Parquet or NDJSON files for offline analysis.
+//
+//  In the name of Allah, the Most Gracious, the Most Merciful.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Utc;
+use clap::Parser;
+use muhafidh::config::load_config;
+use muhafidh::error::Result;
+use muhafidh::model::export::DayPartitionedWriter;
+use muhafidh::model::export::ExportDataset;
+use muhafidh::model::export::ExportFormat;
+use muhafidh::storage::postgres::make_postgres_client;
+use tracing::info;
+
+// Last successful incremental export of each dataset, kept next to the files it describes
+const WATERMARKS_FILE: &str = "_watermarks.json";
+
+#[derive(Debug, Parser)]
+#[command(name = "export", about = "Export the muhafidh dataset to Parquet or NDJSON")]
+struct Args {
+    /// Dataset(s): tokens, cex_relations, wallet_nodes, wallet_edges or price_history
+    #[arg(short, long, num_args = 1.., default_values = ["tokens", "cex_relations", "wallet_nodes", "wallet_edges", "price_history"])]
+    dataset: Vec<String>,
+
+    /// Only rows whose time is at or after this unix timestamp, defaults to the beginning
+    #[arg(long)]
+    from: Option<i64>,
+
+    /// Only rows whose time is before this unix timestamp, defaults to no limit
+    #[arg(long)]
+    to: Option<i64>,
+
+    /// Output format: parquet or ndjson
+    #[arg(short, long, default_value = "parquet")]
+    format: String,
+
+    /// Output directory, each dataset gets a directory of `day=YYYY-MM-DD` partitions in it
+    #[arg(short, long, default_value = "export")]
+    output: String,
+
+    /// Only export rows written or updated since the previous incremental run
+    #[arg(long)]
+    incremental: bool,
+
+    #[arg(long, default_value = "Config.toml")]
+    config: String,
+}
+
+fn load_watermarks(path: &Path) -> Result<HashMap<String, DateTime<Utc>>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = load_config(&args.config).await?;
+
+    let datasets = args
+        .dataset
+        .iter()
+        .map(|dataset| dataset.parse::<ExportDataset>())
+        .collect::<Result<Vec<ExportDataset>>>()?;
+    let format = args.format.parse::<ExportFormat>()?;
+    let from = args.from.unwrap_or(0);
+    let to = args.to.unwrap_or(i64::MAX);
+
+    let root = Path::new(&args.output);
+    std::fs::create_dir_all(root)?;
+    let watermarks_path = root.join(WATERMARKS_FILE);
+    let mut watermarks = load_watermarks(&watermarks_path)?;
+
+    let postgres = make_postgres_client("export", &config.storage_postgres).await?;
+    let run_id = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    for dataset in datasets {
+        // Taken before reading, so rows changing during the export are picked up again by the next run
+        let started_at = Utc::now();
+        let changed_since = if args.incremental {
+            watermarks.get(dataset.as_str()).copied()
+        } else {
+            None
+        };
+
+        let mut writer = DayPartitionedWriter::new(root, dataset, format, &run_id);
+        let rows = postgres
+            .export
+            .export_dataset(dataset, from, to, changed_since, |day, row| writer.write_row(day, row))
+            .await?;
+        let files = writer.finish()?;
+
+        if args.incremental {
+            watermarks.insert(dataset.as_str().to_string(), started_at);
+            std::fs::write(&watermarks_path, serde_json::to_string_pretty(&watermarks)?)?;
+        }
+
+        info!(
+            "dataset_exported::{}::rows::{}::files::{}::changed_since::{:?}",
+            dataset.as_str(),
+            rows,
+            files.len(),
+            changed_since
+        );
+        println!("Exported {} {} rows into {} files", rows, dataset.as_str(), files.len());
+    }
+
+    Ok(())
+}
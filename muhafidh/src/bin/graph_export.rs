@@ -0,0 +1,92 @@
+// ─────────────────────────────────────────────────────────────────────────────
+//  Funding Graph Exporter
+//  Part of the Al-Hafiz Project, the Guardian Layer of BismillahDAO.
+//
+//  Exports creator funding graphs as GraphML, GEXF or DOT so they can be
+//  explored in Gephi, yEd or Graphviz.
+//
+//  In the name of Allah, the Most Gracious, the Most Merciful.
+// ─────────────────────────────────────────────────────────────────────────────
+
+use std::str::FromStr;
+
+use clap::Parser;
+use muhafidh::config::load_config;
+use muhafidh::error::Result;
+use muhafidh::error::anyhow;
+use muhafidh::model::creator::export::GraphExportFormat;
+use muhafidh::model::creator::graph::CreatorConnectionGraph;
+use muhafidh::storage::postgres::make_postgres_client;
+use muhafidh::storage::redis::make_redis_client;
+use solana_pubkey::Pubkey;
+use tracing::info;
+use tracing::warn;
+
+#[derive(Debug, Parser)]
+#[command(name = "graph_export", about = "Export creator funding graphs")]
+struct Args {
+    /// Token mint(s) whose funding graph to export; several mints are merged into one subgraph
+    #[arg(short, long, required = true, num_args = 1..)]
+    mint: Vec<String>,
+
+    /// Output format: graphml, gexf or dot
+    #[arg(short, long, default_value = "graphml")]
+    format: String,
+
+    /// Where to read the graph from: redis or postgres
+    #[arg(short, long, default_value = "postgres")]
+    source: String,
+
+    /// Output file, defaults to funding_graph.<ext>
+    #[arg(short, long)]
+    output: Option<String>,
+
+    #[arg(long, default_value = "Config.toml")]
+    config: String,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = load_config(&args.config).await?;
+
+    let format = args.format.parse::<GraphExportFormat>()?;
+    let mints = args
+        .mint
+        .iter()
+        .map(|mint| Pubkey::from_str(mint).map_err(|e| anyhow!("invalid_mint::{}::{}", mint, e)))
+        .collect::<Result<Vec<Pubkey>>>()?;
+
+    let graph = match args.source.as_str() {
+        "postgres" => {
+            let postgres = make_postgres_client("graph-export", &config.storage_postgres).await?;
+            postgres.graph.load_connection_graph(&mints).await?
+        },
+        "redis" => {
+            let redis = make_redis_client("graph-export", &config.storage_redis).await?;
+            let mut graph = CreatorConnectionGraph::new();
+            for mint in &mints {
+                let key = format!("developer_connection_graph:{}", mint);
+                match redis.kv.get_graph(&key).await? {
+                    Some(mint_graph) => graph.merge(&mint_graph),
+                    None => warn!("graph_not_found_in_redis::mint::{}", mint),
+                }
+            }
+            graph
+        },
+        other => return Err(anyhow!("unsupported_graph_source::{}", other)),
+    };
+
+    let output = args.output.unwrap_or_else(|| format!("funding_graph.{}", format.extension()));
+    std::fs::write(&output, graph.export(format))?;
+
+    info!(
+        "graph_exported::mints::{}::nodes::{}::edges::{}::output::{}",
+        mints.len(),
+        graph.get_node_count(),
+        graph.get_edge_count(),
+        output
+    );
+    println!("Exported {} nodes and {} edges to {}", graph.get_node_count(), graph.get_edge_count(), output);
+    Ok(())
+}
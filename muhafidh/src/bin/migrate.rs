@@ -2,24 +2,57 @@
 //  Database Migration Tool
 //  Part of the Al-Hafiz Project, the Guardian Layer of BismillahDAO.
 //
-//  Applies all database migrations before services are started.
+//  Applies, inspects and reverts database migrations before services are
+//  started.
 //
 //  In the name of Allah, the Most Gracious, the Most Merciful.
 // ─────────────────────────────────────────────────────────────────────────────
 
+use clap::Parser;
+use clap::Subcommand;
 use muhafidh::config::load_config;
 use muhafidh::error::Result;
+use muhafidh::error::anyhow;
 use muhafidh::handler::shutdown::ShutdownSignal;
+use muhafidh::storage::migration::Migrator;
+use muhafidh::storage::postgres::make_postgres_client;
 use muhafidh::storage::run_database_migrations;
 use muhafidh::tracing::setup_tracing;
 use tracing::error;
 use tracing::info;
 
+#[derive(Debug, Parser)]
+#[command(name = "migrate", about = "Apply, inspect and revert database migrations")]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[arg(long, default_value = "Config.toml")]
+    config: String,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Apply all pending migrations (default)
+    Up,
+    /// List applied and pending migrations with their timestamps
+    Status,
+    /// Revert applied migrations newer than a version, using their down scripts
+    Down {
+        /// Version to roll back to, it stays applied
+        #[arg(long)]
+        to: i64,
+    },
+    /// Fail if an applied migration's SQL changed since it ran
+    Verify,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args = Args::parse();
     let shutdown_signal = ShutdownSignal::new();
     // Load configuration
-    let config = load_config("Config.toml").await?;
+    let config = load_config(&args.config).await?;
 
     // Initialize logging
     info!("Initializing logging...");
@@ -27,9 +60,52 @@ async fn main() -> Result<()> {
         error!("failed_to_setup_tracing: {}", e);
     }
 
-    // Run migrations
-    run_database_migrations("migration-tool", &config).await?;
+    match args.command.unwrap_or(Command::Up) {
+        Command::Up => {
+            // Run migrations
+            run_database_migrations("migration-tool", &config).await?;
+            info!("Database Migration Tool completed successfully");
+        },
+        Command::Status => {
+            let postgres = make_postgres_client("migration-tool", &config.storage_postgres).await?;
+            let statuses = Migrator::new(postgres.pool.clone()).status().await?;
+
+            println!("{:>7}  {:<42} {:<9} {:<26} {}", "version", "name", "state", "applied_at", "reversible");
+            for status in &statuses {
+                let applied_at = status.applied_at.map(|at| at.to_rfc3339()).unwrap_or_else(|| String::from("-"));
+                println!(
+                    "{:>7}  {:<42} {:<9} {:<26} {}",
+                    status.version,
+                    status.name,
+                    status.state.as_str(),
+                    applied_at,
+                    if status.reversible {
+                        "yes"
+                    } else {
+                        "no"
+                    }
+                );
+            }
+        },
+        Command::Down {
+            to,
+        } => {
+            let postgres = make_postgres_client("migration-tool", &config.storage_postgres).await?;
+            let reverted = Migrator::new(postgres.pool.clone()).rollback_to(to).await?;
+
+            info!("migrations_reverted::to::{}::versions::{:?}", to, reverted);
+            println!("Reverted {} migrations down to version {}", reverted.len(), to);
+        },
+        Command::Verify => {
+            let postgres = make_postgres_client("migration-tool", &config.storage_postgres).await?;
+            let modified = Migrator::new(postgres.pool.clone()).verify_checksums().await?;
+            if !modified.is_empty() {
+                return Err(anyhow!("migration_checksum_mismatch::{:?}", modified));
+            }
+
+            println!("All applied migrations match their checksums");
+        },
+    }
 
-    info!("Database Migration Tool completed successfully");
     Ok(())
 }
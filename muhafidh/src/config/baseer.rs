@@ -0,0 +1,20 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BaseerConfig {
+    // How often launch outcomes of CEX-funded tokens are attributed to their exchanges (default: 60s)
+    pub cex_outcome_refresh_secs: u64,
+    // Mints whose outcome is computed per refresh (default: 500)
+    pub cex_outcome_batch_size: usize,
+}
+
+impl Default for BaseerConfig {
+    fn default() -> Self {
+        Self {
+            cex_outcome_refresh_secs: 60,
+            cex_outcome_batch_size: 500,
+        }
+    }
+}
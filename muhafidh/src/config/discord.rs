@@ -6,6 +6,7 @@ pub enum DiscordChannel {
     Debug,
     Error,
     Info,
+    Alert,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,26 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchConfig {
+    // Buys up to this many slots after `Create` are checked for bundling (default: 3)
+    pub bundle_window_slots: u64,
+    // Creator sells are watched for this long after launch (default: 30m)
+    pub dev_dump_window_secs: u64,
+    // Share of its holdings the creator cluster must sell to count as a dump (default: 50%)
+    pub dev_dump_threshold_pct: f64,
+    // How often clustered wallets are reloaded from the funding graph and bundlers (default: 60s)
+    pub dev_dump_cluster_refresh_secs: u64,
+}
+
+impl Default for LaunchConfig {
+    fn default() -> Self {
+        Self {
+            bundle_window_slots: 3,
+            dev_dump_window_secs: 1_800,
+            dev_dump_threshold_pct: 50.0,
+            dev_dump_cluster_refresh_secs: 60,
+        }
+    }
+}
@@ -1,23 +1,36 @@
+pub mod baseer;
 pub mod creator;
 pub mod discord;
+pub mod launch;
 pub mod log;
+pub mod price;
 pub mod rpc;
 pub mod storage;
 
 use std::path::Path;
 
+pub use baseer::BaseerConfig;
 pub use creator::CreatorAnalyzerConfig;
 pub use discord::DiscordChannel;
 pub use discord::DiscordChannelConfig;
 pub use discord::DiscordConfig;
+pub use launch::LaunchConfig;
 pub use log::LoggingConfig;
+pub use price::PriceConfig;
 pub use rpc::RpcConfig;
 pub use rpc::RpcProviderConfig;
 pub use rpc::RpcProviderRole;
 use serde::Deserialize;
 use serde::Serialize;
+pub use storage::PartitionConfig;
+pub use storage::PartitionRetentionConfig;
+pub use storage::RedisCoordinationConfig;
+pub use storage::RedisLeaseConfig;
+pub use storage::RedisStreamConfig;
 pub use storage::StoragePostgresConfig;
 pub use storage::StorageRedisConfig;
+pub use storage::TlsConfig;
+pub use storage::TlsMode;
 use toml;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +41,12 @@ pub struct Config {
     pub creator_analyzer: CreatorAnalyzerConfig,
     pub logging: LoggingConfig,
     pub discord: DiscordConfig,
+    #[serde(default)]
+    pub price: PriceConfig,
+    #[serde(default)]
+    pub launch: LaunchConfig,
+    #[serde(default)]
+    pub baseer: BaseerConfig,
 }
 
 pub async fn load_config(path: impl AsRef<Path>) -> crate::Result<Config> {
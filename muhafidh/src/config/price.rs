@@ -0,0 +1,56 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceConfig {
+    // How long after creation a bonding curve, or after graduation a PumpSwap pool, keeps being tracked (default: 24h)
+    pub tracking_window_secs: u64,
+    // How often curves and pools past the tracking window are dropped, curves leave on bonding anyway (default: 60s)
+    pub prune_interval_secs: u64,
+    // Pyth SOL/USD price update account (default: sponsored SOL/USD feed, shard 0)
+    pub pyth_sol_usd_account: String,
+    // Optional JSON endpoint used when Pyth is unavailable or stale
+    pub sol_usd_fallback_url: Option<String>,
+    // JSON pointer to the price inside the fallback response, e.g. "/data/price"
+    pub sol_usd_fallback_pointer: String,
+    // How often the SOL/USD price is refreshed (default: 10s)
+    pub sol_usd_refresh_secs: u64,
+    // Older prices are rejected and USD values are omitted (default: 120s)
+    pub sol_usd_max_age_secs: u64,
+    // How often changed all-time highs are written to Postgres (default: 30s)
+    pub ath_flush_interval_secs: u64,
+    // Rise over the last announced ATH, in percent, that triggers `token_new_ath` (default: 10%)
+    pub ath_event_threshold_pct: f64,
+    // How often OHLCV candles touched by new trades are rebuilt from the trade history (default: 10s)
+    pub candle_flush_interval_secs: u64,
+    // How often holder concentration is snapshotted for every tracked mint (default: 300s)
+    pub holder_snapshot_interval_secs: u64,
+    // Tracked mints snapshotted per round, the rest wait for the next rounds in turn (default: 100)
+    pub holder_snapshot_batch_size: usize,
+    // Snapshots taken at the same time, each one scans the mint's token accounts (default: 4)
+    pub holder_snapshot_concurrency: usize,
+    // How often price alert rules and CEX-funded mints are reloaded from Postgres (default: 30s)
+    pub alert_refresh_secs: u64,
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        Self {
+            tracking_window_secs: 86_400,
+            prune_interval_secs: 60,
+            pyth_sol_usd_account: String::from("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"),
+            sol_usd_fallback_url: None,
+            sol_usd_fallback_pointer: String::from("/price"),
+            sol_usd_refresh_secs: 10,
+            sol_usd_max_age_secs: 120,
+            ath_flush_interval_secs: 30,
+            ath_event_threshold_pct: 10.0,
+            candle_flush_interval_secs: 10,
+            holder_snapshot_interval_secs: 300,
+            holder_snapshot_batch_size: 100,
+            holder_snapshot_concurrency: 4,
+            alert_refresh_secs: 30,
+        }
+    }
+}
@@ -74,6 +74,8 @@ pub const USDC_MINT_KEY: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZ
 
 pub const USDT_MINT_KEY: Pubkey = pubkey!("Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB");
 
+pub const SYSTEM_PROGRAM_ID: Pubkey = pubkey!("11111111111111111111111111111111");
+
 pub const TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
 pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
@@ -82,6 +84,9 @@ pub const TOKEN_2022_PROGRAM_ID: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1
 /// PumpFun program ID - The main program ID for PumpFun platform
 pub const PUMP_FUN_PROGRAM_ID: Pubkey = pubkey!("6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P");
 
+/// PumpSwap program ID - AMM that pump.fun tokens migrate to once their bonding curve completes
+pub const PUMP_SWAP_PROGRAM_ID: Pubkey = pubkey!("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA");
+
 /// PumpFun API base URL - This is a public API endpoint, not a secret
 pub const PUMP_FUN_API_URL: &str = "https://frontend-api.pump.fun";
 
@@ -97,6 +102,15 @@ pub const PUMP_FUN_MINT_AUTHORITY: Pubkey = pubkey!("TSLvdd1pWpHVjahSpsvCXUbgwsL
 /// PumpFun event authority - Authority for PumpFun events
 pub const EVENT_AUTHORITY: Pubkey = pubkey!("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1");
 
+/// PumpFun bonding curve account discriminator - First 8 bytes of every bonding curve account
+pub const PUMP_BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+
+/// PumpFun token decimals - Every token minted on the bonding curve uses 6 decimals
+pub const PUMP_TOKEN_DECIMALS: u32 = 6;
+
+/// PumpFun token supply - Every token is minted with 1B whole tokens, in base units
+pub const PUMP_TOKEN_TOTAL_SUPPLY: u64 = 1_000_000_000_000_000;
+
 /// Solana
 /// Associated Token Program ID - The SPL Associated Token Account
 pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
@@ -54,6 +54,7 @@ impl Baseer {
             operator_receiver,
             operator_sender.clone(),
             rpc_config.clone(),
+            config.price.sol_usd_max_age_secs as i64,
         ));
 
         let baseer = Baseer {
@@ -73,13 +74,32 @@ impl Baseer {
 
         let account_recovery_handle = baseer.spawn_account_recovery(cancellation_token.clone());
 
+        let account_lease_keeper_handle = baseer.spawn_account_lease_keeper();
+
+        let instance_coordinator_handle = baseer.spawn_instance_coordinator();
+
         let account_queue_reporting_handle = baseer.spawn_account_queue_reporting();
 
+        let cex_outcome_refresh_handle = baseer.spawn_cex_outcome_refresh();
+
+        let cex_activity_rollup_handle = baseer.spawn_cex_activity_rollup();
+
+        let history_partition_maintenance_handle = baseer
+            .db
+            .postgres()?
+            .partition
+            .spawn_maintenance(baseer.config.storage_postgres.partitions.clone(), shutdown_signal.clone());
+
         tokio::select! {
             _ = token_creator_analyzer_handle => {},
             _ = token_subscriber_handle => {},
             _ = account_recovery_handle => {},
+            _ = account_lease_keeper_handle => {},
+            _ = instance_coordinator_handle => {},
             _ = account_queue_reporting_handle => {},
+            _ = cex_outcome_refresh_handle => {},
+            _ = cex_activity_rollup_handle => {},
+            _ = history_partition_maintenance_handle => {},
             _ = tokio::signal::ctrl_c() => {
                 let _ = shutdown_tx.send(()).await;
             },
@@ -1,8 +1,8 @@
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
 
-use futures_util::StreamExt;
 use tokio::sync::RwLock;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
@@ -19,71 +19,77 @@ use crate::err_with_loc;
 use crate::handler::shutdown::ShutdownSignal;
 use crate::handler::token::CreatorHandler;
 use crate::model::cex::Cex;
+use crate::model::creator::crawl::CrawlGuard;
 use crate::model::creator::metadata::CreatorMetadata;
 use crate::model::dev::Dev;
 use crate::pipeline::crawler::creator::make_creator_crawler_pipeline;
 use crate::pipeline::processor::creator::CreatorInstructionProcessor;
+use crate::storage::backend::EventStreamExt;
+use crate::storage::backend::KvCacheExt;
+use crate::storage::postgres::model::ActivityBucket;
 use crate::storage::redis::model::NewTokenCache;
+use crate::storage::redis::stream::StreamEvent;
 
 impl Baseer {
+    // Consume `new_token_created` through Baseer's consumer group; entries are handed to the analyzer
+    // unacknowledged, so tokens announced while Baseer was down or busy are picked up later
     pub fn spawn_new_token_subscriber(
         &self,
         shutdown_signal: ShutdownSignal,
-        sender: mpsc::Sender<NewTokenCache>,
+        sender: mpsc::Sender<StreamEvent<NewTokenCache>>,
     ) -> JoinHandle<()> {
-        let db = self.db.clone();
-        let max_depth = self.config.creator_analyzer.max_depth;
+        let stream = self.db.backend.streams.clone();
+        let coordinator = self.db.backend.coordinator.clone();
         tokio::spawn(async move {
-            // Clone the db here to avoid borrowing conflicts
-            let db_for_subscriber = db.clone();
-            let mut subscriber = db_for_subscriber.redis.queue.pubsub.as_ref().write().await;
-
-            if let Err(e) = subscriber.subscribe("new_token_created").await {
-                error!("failed_to_subscribe_to_new_token_created::error::{}", e);
+            let channel = "new_token_created";
+            if let Err(e) = stream.ensure_group(channel).await {
+                error!("failed_to_create_new_token_created_group::error::{}", e);
+                return;
             }
 
-            // Create a channel for buffering messages - with good capacity for performance
-            let (buffer_tx, mut buffer_rx) = mpsc::channel::<NewTokenCache>(10000);
-            // Process messages
-            let mut msg_stream = subscriber.on_message();
-
-            // Clone db for the buffer task
-            let db_for_buffer = db.clone();
-            let shutdown_fut = shutdown_signal.clone();
+            let mut last_claim = None;
             loop {
-                tokio::select! {
-                  Some(token) = buffer_rx.recv() => {
-                    // Store the mint before sending the token
-                    let mint = token.mint;
-                    if buffer_rx.capacity() < 9999 {
-                        error!("low_capacity_on_buffer::mint::{}", mint);
+                let events = tokio::select! {
+                    events = stream.next_batch::<NewTokenCache>(channel, &mut last_claim) => events,
+                    _ = shutdown_signal.wait_for_shutdown() => break,
+                };
+                let events = match events {
+                    Ok(events) => events,
+                    Err(e) => {
+                        error!("failed_to_read_new_token_created::error::{}", e);
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    },
+                };
+
+                for event in events {
+                    let mint = event.payload.mint;
+                    // Another instance is already analyzing this mint (e.g. a reclaimed entry). The entry stays
+                    // pending: that instance acknowledges it once done, or it is reclaimed again if it crashes
+                    match coordinator.try_lock_mint(&mint).await {
+                        Ok(true) => {},
+                        Ok(false) => {
+                            debug!("mint_locked_by_other_instance::mint::{}", mint);
+                            continue;
+                        },
+                        Err(e) => error!("failed_to_lock_mint::mint::{}::error::{}", mint, e),
                     }
-
-                    if let Err(e) = sender.try_send(token.clone()) {
+                    // Wait for room instead of dropping, the stream holds the backlog meanwhile
+                    if let Err(e) = sender.send(event).await {
                         error!("failed_to_send_token_to_processor::mint::{}::error::{}", mint, e);
+                        return;
                     }
-                  },
-                  Some(message) = msg_stream.next() => {
-                    if let Ok(msg) = message.get_payload::<String>() {
-                        if let Ok(token) = serde_json::from_str::<NewTokenCache>(&msg) {
-                            if let Err(e) = buffer_tx.try_send(token.clone()) {
-                                error!("failed_to_send_token_to_buffer::mint::{}::error::{}", token.mint, e);
-                            }
-                        }
-                    }
-                  },
-                  _ = shutdown_fut.wait_for_shutdown() => {
-                    break;
-                  }
                 }
             }
-            debug!("token_subscriber::buffer_task_ending");
+            debug!("token_subscriber::ended");
         })
     }
 
+    // Crawl each token's creator. Its stream entry is acknowledged once the whole crawl is over, whether it
+    // found a CEX, ran out of accounts or handed failed accounts to the failed queue
     pub fn spawn_new_token_creator_analyzer(
         &self,
-        mut receiver: mpsc::Receiver<NewTokenCache>,
+        mut receiver: mpsc::Receiver<StreamEvent<NewTokenCache>>,
         sender: mpsc::Sender<CreatorHandler>,
         cancellation_token: CancellationToken,
     ) -> JoinHandle<Result<()>> {
@@ -95,12 +101,42 @@ impl Baseer {
 
         tokio::spawn(async move {
             let max_depth = baseer.config.creator_analyzer.max_depth;
+            // Stream entries being crawled here, a reclaim of our own pending entry must not start a second crawl
+            let in_flight: Arc<RwLock<HashSet<String>>> = Arc::new(RwLock::new(HashSet::new()));
             // Process tokens using select for better control
             loop {
                 let creator_analyzer_config = creator_analyzer_config.clone();
                 let creator_handler = baseer.creator_handler.clone();
                 tokio::select! {
-                    Some(token) = receiver.recv() => {
+                    Some(event) = receiver.recv() => {
+                        if !in_flight.write().await.insert(event.id.clone()) {
+                            debug!("new_token_already_in_flight::mint::{}", event.payload.mint);
+                            continue;
+                        }
+                        let token = event.payload;
+                        let (crawl, crawl_finished) = CrawlGuard::new();
+                        {
+                            let stream = baseer.db.backend.streams.clone();
+                            let coordinator = baseer.db.backend.coordinator.clone();
+                            let in_flight = in_flight.clone();
+                            let cancellation_token = cancellation_token.clone();
+                            let mint = token.mint;
+                            let id = event.id;
+                            tokio::spawn(async move {
+                                crawl_finished.wait().await;
+                                // Every path out of the crawl ends here, including failures to start it
+                                if let Err(e) = coordinator.release_mint_lock(&mint).await {
+                                    error!("failed_to_release_mint_lock::mint::{}::error::{}", mint, e);
+                                }
+                                // A crawl cut short by shutdown stays pending and is reclaimed after restart
+                                if !cancellation_token.is_cancelled() {
+                                    if let Err(e) = stream.ack("new_token_created", &id).await {
+                                        error!("failed_to_ack_new_token_created::mint::{}::error::{}", mint, e);
+                                    }
+                                }
+                                in_flight.write().await.remove(&id);
+                            });
+                        }
                         let child_token = cancellation_token.child_token();
                         let rpc_config_clone = rpc_config.clone();
                         let creator_metadata = CreatorMetadata::initialize(token.clone(), max_depth).await;
@@ -108,7 +144,7 @@ impl Baseer {
 
                         tokio::spawn(async move {
                             let creator_metadata = Arc::new(creator_metadata);
-                            let processor = CreatorInstructionProcessor::new(creator_handler.clone(), creator_metadata.clone(), child_token.clone(), creator_analyzer_config.clone(), rpc_config_clone, Arc::new(RwLock::new(0)));
+                            let processor = CreatorInstructionProcessor::new(creator_handler.clone(), creator_metadata.clone(), child_token.clone(), creator_analyzer_config.clone(), rpc_config_clone, Arc::new(RwLock::new(0)), crawl.clone());
                             // First check if this is a known developer address with associated CEX
                             if let Some(dev) = Dev::get_dev_info(creator_metadata.original_creator.clone()) {
                                 let cex_name = dev.cex_name;
@@ -124,6 +160,7 @@ impl Baseer {
                                     dev: creator_metadata.original_creator,
                                     created_at: creator_metadata.created_at,
                                     bonding_curve: creator_metadata.bonding_curve.unwrap_or_default(),
+                                    crawl,
                                 }) {
                                     error!("failed_to_send_cex_connection_request::sender::{}::receiver::{}::error::{}", creator_metadata.mint, creator_metadata.original_creator, e);
                                     return Err(err_with_loc!(HandlerError::SendCreatorHandlerError(format!(
@@ -209,8 +246,8 @@ impl Baseer {
                         let mut found_work = false;
 
                         // First try to process failed accounts (higher priority)
-                        match db.redis.queue.get_next_failed_account().await {
-                            Ok(Some(account)) => {
+                        match db.backend.work_queue.lease_next_failed_account().await {
+                            Ok(Some((lease, account))) => {
                                 found_work = true;
                                 debug!("processing_failed_account::account::{}::mint::{}::retry_count::{}",
                                     account.get_analyzed_account().await, account.mint, account.retry_count);
@@ -220,6 +257,9 @@ impl Baseer {
                                     // warn!("max_retries_exceeded::account::{}::mint::{}::moving_to_dead_letter",
                                     //     account.address, account.mint);
                                     // <TODO> implement dead letter queue here if needed
+                                    if let Err(e) = db.backend.work_queue.release_account_lease(&lease).await {
+                                        error!("failed_to_release_account_lease::lease::{}::error::{}", lease.id, e);
+                                    }
                                     continue;
                                 }
 
@@ -232,6 +272,7 @@ impl Baseer {
                                     child_token,
                                     creator_analyzer_config: creator_analyzer_config.clone(),
                                     depth: 0,
+                                    lease: lease.clone(),
                                 }) {
                                     error!("failed_to_send_recovery_request::mint::{}::error::{}",
                                         creator_metadata.mint, e);
@@ -239,16 +280,19 @@ impl Baseer {
                                     // Re-add to failed queue
                                     let mut failed_account = (*creator_metadata).clone();
                                     failed_account.mark_as_failed().await;
-                                    if let Err(e) = db.redis.queue.add_failed_account(&failed_account).await {
+                                    if let Err(e) = db.backend.work_queue.add_failed_account(&failed_account).await {
                                         error!("failed_to_requeue_failed_account::account::{}::error::{}",
                                             failed_account.get_analyzed_account().await, e);
                                     }
+                                    if let Err(e) = db.backend.work_queue.release_account_lease(&lease).await {
+                                        error!("failed_to_release_account_lease::lease::{}::error::{}", lease.id, e);
+                                    }
                                 }
                             },
                             Ok(None) => {
                                 // No failed accounts, try unprocessed
-                                match db.redis.queue.get_next_unprocessed_account().await {
-                                    Ok(Some(account)) => {
+                                match db.backend.work_queue.lease_next_unprocessed_account().await {
+                                    Ok(Some((lease, account))) => {
                                         found_work = true;
                                         debug!("processing_unprocessed_account::account::{}::mint::{}",
                                             account.get_analyzed_account().await, account.mint);
@@ -262,6 +306,7 @@ impl Baseer {
                                             child_token,
                                             creator_analyzer_config: creator_analyzer_config.clone(),
                                             depth: 0,
+                                            lease: lease.clone(),
                                         }) {
                                             error!("failed_to_send_unprocessed_request::mint::{}::error::{}",
                                                 creator_metadata.mint, e);
@@ -269,10 +314,13 @@ impl Baseer {
                                             // Mark as failed and add to failed queue
                                             let mut failed_account = (*creator_metadata).clone();
                                             failed_account.mark_as_failed().await;
-                                            if let Err(e) = db.redis.queue.add_failed_account(&failed_account).await {
+                                            if let Err(e) = db.backend.work_queue.add_failed_account(&failed_account).await {
                                                 error!("failed_to_add_to_failed_queue::account::{}::error::{}",
                                                     failed_account.get_analyzed_account().await, e);
                                             }
+                                            if let Err(e) = db.backend.work_queue.release_account_lease(&lease).await {
+                                                error!("failed_to_release_account_lease::lease::{}::error::{}", lease.id, e);
+                                            }
                                         }
                                     },
                                     Ok(None) => {
@@ -321,6 +369,124 @@ impl Baseer {
         })
     }
 
+    // Keep this worker's account leases alive and return leases of crashed workers to their queues
+    pub fn spawn_account_lease_keeper(&self) -> JoinHandle<Result<()>> {
+        let db = self.db.clone();
+        let shutdown_signal = self.creator_handler.shutdown.clone();
+        let lease_config = self.config.storage_redis.leases.clone();
+
+        tokio::spawn(async move {
+            debug!("account_lease_keeper_task::started");
+
+            let mut heartbeat_timer =
+                tokio::time::interval(Duration::from_secs(lease_config.heartbeat_interval_secs.max(1)));
+            let mut reap_timer = tokio::time::interval(Duration::from_secs(lease_config.reap_interval_secs.max(1)));
+
+            loop {
+                tokio::select! {
+                    _ = heartbeat_timer.tick() => {
+                        match db.backend.work_queue.heartbeat_account_leases().await {
+                            Ok(held) => debug!("account_leases_heartbeat::held::{}", held),
+                            Err(e) => error!("failed_to_heartbeat_account_leases::error::{}", e),
+                        }
+                    },
+                    _ = reap_timer.tick() => {
+                        match db.backend.work_queue.reap_expired_account_leases().await {
+                            Ok(0) => {},
+                            Ok(reaped) => info!("account_leases_reaped::count::{}", reaped),
+                            Err(e) => error!("failed_to_reap_account_leases::error::{}", e),
+                        }
+                    },
+                    _ = shutdown_signal.wait_for_shutdown() => {
+                        warn!("account_lease_keeper_task::shutdown_signal_received");
+                        break;
+                    }
+                }
+            }
+
+            debug!("account_lease_keeper_task::ended");
+            Ok(())
+        })
+    }
+
+    // Keep this instance registered, renew the mint locks it holds, and rebalance after peers that
+    // stopped heartbeating: their mint locks are freed, and their stream consumers are dropped once the
+    // entries they left pending have been reclaimed by the group
+    pub fn spawn_instance_coordinator(&self) -> JoinHandle<Result<()>> {
+        let db = self.db.clone();
+        let shutdown_signal = self.creator_handler.shutdown.clone();
+        let coordination_config = self.config.storage_redis.coordination.clone();
+
+        tokio::spawn(async move {
+            debug!("instance_coordinator_task::started::{}", db.backend.coordinator.worker());
+
+            let channel = "new_token_created";
+            let mut heartbeat_timer =
+                tokio::time::interval(Duration::from_secs(coordination_config.instance_heartbeat_secs.max(1)));
+            let mut renew_timer =
+                tokio::time::interval(Duration::from_secs(coordination_config.mint_lock_renew_secs.max(1)));
+            // Departed instances whose consumer still has pending entries, retried until removed
+            let mut departed: HashSet<String> = HashSet::new();
+
+            loop {
+                tokio::select! {
+                    _ = heartbeat_timer.tick() => {
+                        if let Err(e) = db.backend.coordinator.heartbeat_instance().await {
+                            error!("failed_to_heartbeat_instance::error::{}", e);
+                            continue;
+                        }
+
+                        match db.backend.coordinator.take_dead_instances().await {
+                            Ok(dead) => {
+                                for worker in dead {
+                                    match db.backend.coordinator.release_instance_mint_locks(&worker).await {
+                                        Ok(released) => warn!("instance_departed::{}::released_mint_locks::{}", worker, released),
+                                        Err(e) => error!("failed_to_release_instance_mint_locks::{}::error::{}", worker, e),
+                                    }
+                                    departed.insert(worker);
+                                }
+                            },
+                            Err(e) => error!("failed_to_get_dead_instances::error::{}", e),
+                        }
+
+                        let mut removed = Vec::new();
+                        for worker in &departed {
+                            match db.backend.streams.remove_idle_consumer(channel, worker).await {
+                                Ok(true) => removed.push(worker.clone()),
+                                Ok(false) => debug!("departed_consumer_has_pending_entries::{}", worker),
+                                Err(e) => error!("failed_to_remove_departed_consumer::{}::error::{}", worker, e),
+                            }
+                        }
+                        for worker in removed {
+                            departed.remove(&worker);
+                        }
+
+                        match db.backend.coordinator.get_live_instances().await {
+                            Ok(instances) => debug!("live_instances::count::{}", instances.len()),
+                            Err(e) => error!("failed_to_get_live_instances::error::{}", e),
+                        }
+                    },
+                    _ = renew_timer.tick() => {
+                        match db.backend.coordinator.renew_mint_locks().await {
+                            Ok(held) => debug!("mint_locks_renewed::held::{}", held),
+                            Err(e) => error!("failed_to_renew_mint_locks::error::{}", e),
+                        }
+                    },
+                    _ = shutdown_signal.wait_for_shutdown() => {
+                        warn!("instance_coordinator_task::shutdown_signal_received");
+                        if let Err(e) = db.backend.coordinator.deregister_instance().await {
+                            error!("failed_to_deregister_instance::error::{}", e);
+                        }
+                        break;
+                    }
+                }
+            }
+
+            debug!("instance_coordinator_task::ended");
+            Ok(())
+        })
+    }
+
     // New method to spawn a task for queue reporting
     pub fn spawn_account_queue_reporting(&self) -> JoinHandle<Result<()>> {
         let creator_handler = self.creator_handler.clone();
@@ -346,10 +512,18 @@ impl Baseer {
                         match creator_handler.get_pending_account_counts().await {
                             Ok((failed_count, unprocessed_count)) => {
                                 let total = failed_count + unprocessed_count;
+                                // Accounts leased by any Baseer instance and not yet released
+                                let in_flight = match creator_handler.get_in_flight_account_count().await {
+                                    Ok(in_flight) => in_flight,
+                                    Err(e) => {
+                                        error!("failed_to_get_in_flight_account_count::error::{}", e);
+                                        0
+                                    }
+                                };
 
-                                if total > 0 {
-                                    info!("queue_status::failed::{}::unprocessed::{}::total::{}",
-                                        failed_count, unprocessed_count, total);
+                                if total > 0 || in_flight > 0 {
+                                    info!("queue_status::failed::{}::unprocessed::{}::in_flight::{}::total::{}",
+                                        failed_count, unprocessed_count, in_flight, total);
 
                                     // Log warning if queues are getting too large
                                     if total > 1000 {
@@ -377,4 +551,128 @@ impl Baseer {
             Ok(())
         })
     }
+
+    // Attribute ATH-vs-launch outcomes of CEX-funded tokens to their exchanges once price history exists
+    pub fn spawn_cex_outcome_refresh(&self) -> JoinHandle<Result<()>> {
+        let db = self.db.clone();
+        let shutdown_signal = self.creator_handler.shutdown.clone();
+        let refresh_interval = Duration::from_secs(self.config.baseer.cex_outcome_refresh_secs.max(1));
+        let batch_size = self.config.baseer.cex_outcome_batch_size.max(1) as i64;
+
+        tokio::spawn(async move {
+            let postgres = db.postgres()?.clone();
+            debug!("cex_outcome_refresh_task::started");
+
+            let mut refresh_timer = tokio::time::interval(refresh_interval);
+            refresh_timer.tick().await;
+
+            loop {
+                tokio::select! {
+                    _ = refresh_timer.tick() => {
+                        let mints = match postgres.cex_analytics.get_pending_outcome_mints(batch_size).await {
+                            Ok(mints) => mints,
+                            Err(e) => {
+                                error!("failed_to_get_pending_outcome_mints::error::{}", e);
+                                continue;
+                            }
+                        };
+
+                        let mut updated = 0;
+                        for mint in mints {
+                            let outcome = match postgres.cex_analytics.compute_launch_outcome(&mint).await {
+                                Ok(Some(outcome)) => outcome,
+                                Ok(None) => continue,
+                                Err(e) => {
+                                    error!("failed_to_compute_launch_outcome::mint::{}::error::{}", mint, e);
+                                    continue;
+                                }
+                            };
+
+                            let cexes = match postgres.cex_analytics.get_funding_cexes(&mint).await {
+                                Ok(cexes) => cexes,
+                                Err(e) => {
+                                    error!("failed_to_get_funding_cexes::mint::{}::error::{}", mint, e);
+                                    continue;
+                                }
+                            };
+
+                            for cex_address in cexes {
+                                if let Err(e) = postgres.db.update_cex_token_ath(&cex_address, &mint, &outcome).await {
+                                    error!("failed_to_update_cex_token_ath::cex::{}::mint::{}::error::{}", cex_address, mint, e);
+                                } else {
+                                    updated += 1;
+                                }
+                            }
+                        }
+
+                        if updated == 0 {
+                            continue;
+                        }
+
+                        // Cache the per-exchange stats so consumers don't have to aggregate
+                        match postgres.cex_analytics.get_cex_launch_stats().await {
+                            Ok(stats) => {
+                                if let Err(e) = db.backend.kv.set("cex_launch_stats", &stats).await {
+                                    error!("failed_to_cache_cex_launch_stats::error::{}", e);
+                                }
+                                info!("cex_outcome_refresh::updated::{}::exchanges::{}", updated, stats.len());
+                            },
+                            Err(e) => {
+                                error!("failed_to_get_cex_launch_stats::error::{}", e);
+                            }
+                        }
+                    },
+                    _ = shutdown_signal.wait_for_shutdown() => {
+                        warn!("cex_outcome_refresh_task::shutdown_signal_received");
+                        break;
+                    }
+                }
+            }
+
+            debug!("cex_outcome_refresh_task::ended");
+            Ok(())
+        })
+    }
+
+    // Keep the daily and weekly CEX activity rollups in sync with the hourly buckets
+    pub fn spawn_cex_activity_rollup(&self) -> JoinHandle<Result<()>> {
+        let db = self.db.clone();
+        let shutdown_signal = self.creator_handler.shutdown.clone();
+
+        tokio::spawn(async move {
+            let postgres = db.postgres()?.clone();
+            debug!("cex_activity_rollup_task::started");
+
+            let rollup_interval = Duration::from_secs(300);
+            let mut rollup_timer = tokio::time::interval(rollup_interval);
+
+            loop {
+                tokio::select! {
+                    _ = rollup_timer.tick() => {
+                        let now = chrono::Utc::now().timestamp();
+
+                        // Only the current and previous bucket can still change
+                        for bucket in [ActivityBucket::Day, ActivityBucket::Week] {
+                            let since = now - 2 * bucket.seconds();
+                            match postgres.time_series.rollup_cex_activity(bucket, since).await {
+                                Ok(rows) => {
+                                    debug!("cex_activity_rollup::{}::rows::{}", bucket.as_str(), rows);
+                                },
+                                Err(e) => {
+                                    error!("failed_to_rollup_cex_activity::{}::error::{}", bucket.as_str(), e);
+                                }
+                            }
+                        }
+                    },
+                    _ = shutdown_signal.wait_for_shutdown() => {
+                        warn!("cex_activity_rollup_task::shutdown_signal_received");
+                        break;
+                    }
+                }
+            }
+
+            debug!("cex_activity_rollup_task::ended");
+            Ok(())
+        })
+    }
 }
@@ -9,6 +9,7 @@ use crate::config::load_config;
 use crate::err_with_loc;
 use crate::error::EngineError;
 use crate::handler::shutdown::ShutdownSignal;
+use crate::handler::token::dump::DevDumpWatcherOperator;
 use crate::handler::token::metadata::TokenHandlerMetadataOperator;
 use crate::pipeline::subscriber::pumpfun::make_pumpfun_subscriber_pipeline;
 use crate::storage::StorageEngine;
@@ -21,6 +22,7 @@ pub struct Raqib {
     pub config: Config,
     pub db: Arc<StorageEngine>,
     pub token_handler: Arc<TokenHandlerMetadataOperator>,
+    pub dev_dump_watcher: Arc<DevDumpWatcherOperator>,
 }
 
 impl Raqib {
@@ -37,17 +39,27 @@ impl Raqib {
         let db_engine = Arc::new(make_storage_engine("raqib", &config).await?);
         info!("db_engine::created");
 
-        db_engine.postgres.db.health_check().await?;
+        db_engine.postgres()?.db.health_check().await?;
         info!("postgres::health_check::ok");
-        db_engine.postgres.db.initialize().await?;
+        db_engine.postgres()?.db.initialize().await?;
         info!("postgres::initialize::ok");
 
-        let token_handler = Arc::new(TokenHandlerMetadataOperator::new(db_engine.clone(), shutdown_signal.clone()));
+        // Launch bundle analysis looks up early buyers' funding over RPC
+        let mut rpc_config = config.rpc.clone();
+        rpc_config.init_runtime_state().await;
+        let rpc_config = Arc::new(rpc_config);
+
+        let token_handler =
+            Arc::new(TokenHandlerMetadataOperator::new(db_engine.clone(), shutdown_signal.clone(), rpc_config));
+
+        let dev_dump_watcher =
+            Arc::new(DevDumpWatcherOperator::new(db_engine.clone(), shutdown_signal.clone(), &config.launch));
 
         let raqib = Raqib {
             config,
             db: db_engine,
             token_handler: token_handler.clone(),
+            dev_dump_watcher: dev_dump_watcher.clone(),
         };
 
         let mut pipeline = make_pumpfun_subscriber_pipeline(raqib)?;
@@ -57,6 +69,7 @@ impl Raqib {
         tokio::select! {
             result = pipeline.run() => {
                 token_handler.shutdown();
+                dev_dump_watcher.shutdown();
                 let _ = shutdown_tx.send(()).await;
                 result.map_err(|e| {
                     error!("pipeline_error: {}", e);
@@ -67,12 +80,14 @@ impl Raqib {
                 info!("termination_signal::graceful_shutdown");
 
                 token_handler.shutdown();
+                dev_dump_watcher.shutdown();
                 let _ = shutdown_tx.send(()).await;
             },
             _ = shutdown_rx.recv() => {
                 info!("shutdown_signal::other_component");
 
                 token_handler.shutdown();
+                dev_dump_watcher.shutdown();
             }
         }
 
@@ -1,69 +1,366 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::StreamExt;
+use solana_pubkey::Pubkey;
+use tokio::sync::RwLock;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
 use crate::Result;
 use crate::config::Config;
+use crate::config::RpcConfig;
+use crate::config::load_config;
+use crate::err_with_loc;
+use crate::error::EngineError;
+use crate::handler::discord::webhook::DiscordWebhookHandlerOperator;
+use crate::handler::price::solana::SolPriceOracle;
+use crate::handler::price::token::TokenPriceHandlerOperator;
+use crate::handler::shutdown::ShutdownSignal;
+use crate::handler::token::holders::HolderSnapshotter;
+use crate::model::platform::Platform;
+use crate::model::price::SlotClock;
+use crate::model::price::TrackedCurve;
+use crate::model::price::TrackedCurves;
+use crate::model::price::TrackedPool;
+use crate::model::price::TrackedPools;
+use crate::model::price::derive_bonding_curve;
+use crate::model::price::derive_pump_swap_pool;
+use crate::pipeline::subscriber::bonding_curve::make_bonding_curve_subscriber_pipeline;
+use crate::pipeline::subscriber::curve_trade::make_curve_trade_subscriber_pipeline;
+use crate::pipeline::subscriber::pump_swap::make_pump_swap_subscriber_pipeline;
+use crate::storage::StorageEngine;
+use crate::storage::backend::EventStreamExt;
+use crate::storage::make_storage_engine;
+use crate::storage::postgres::PostgresStorage;
+use crate::storage::redis::model::NewTokenCache;
+use crate::tracing::setup_tracing;
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Siraaj {
     pub config: Config,
-    //   pub db:     Arc<StorageEngine>,
-    //   pub token_handler: Arc<TokenHandlerMetadataOperator>,
+    pub db: Arc<StorageEngine>,
+    pub price_handler: Arc<TokenPriceHandlerOperator>,
+    pub sol_price: Arc<SolPriceOracle>,
+    pub tracked: TrackedCurves,
+    pub tracked_pools: TrackedPools,
+    // Block times of recent slots, shared by the trade and curve account pipelines
+    pub slot_clock: SlotClock,
 }
 
 impl Siraaj {
     pub async fn run() -> Result<()> {
-        // info!("Starting Baseer (بصير): The Analyzer");
+        info!("Starting Siraaj (سراج): The Radiant Lamp");
+
+        let shutdown_signal = ShutdownSignal::new();
+
+        let config = load_config("Config.toml").await?;
+        if let Err(e) = setup_tracing(config.clone(), "siraaj", shutdown_signal.clone()).await {
+            error!("failed_to_setup_tracing: {}", e);
+        }
+
+        let db_engine = Arc::new(make_storage_engine("siraaj", &config).await?);
+        info!("db_engine::created");
+
+        db_engine.postgres()?.db.health_check().await?;
+        info!("postgres::health_check::ok");
 
-        // setup_tracing("baseer");
-        // info!("setup_tracing");
+        let mut rpc_config = config.rpc.clone();
+        rpc_config.init_runtime_state().await;
+        let rpc_config = Arc::new(rpc_config);
+        let sol_price =
+            Arc::new(SolPriceOracle::from_config(&config.price, rpc_config.clone(), db_engine.backend.clone())?);
+        if let Err(e) = sol_price.refresh().await {
+            error!("initial_sol_usd_price_refresh_failed: {}", e);
+        }
+        let sol_price_refresh_handle =
+            sol_price.spawn_refresh(config.price.sol_usd_refresh_secs, shutdown_signal.clone());
 
-        // let config = load_config("Config.toml")?;
-        // info!("config loaded");
+        // Alert hits go through their own webhook handler, separate from the one behind tracing
+        let (discord_sender, discord_receiver) = mpsc::channel(1000);
+        let discord = DiscordWebhookHandlerOperator::new(
+            shutdown_signal.clone(),
+            discord_receiver,
+            discord_sender,
+            Arc::new(config.discord.clone()),
+        );
 
-        // let db_engine = Arc::new(make_storage_engine("baseer", &config).await?);
-        // info!("db_engine::created");
+        let price_handler = Arc::new(TokenPriceHandlerOperator::new(
+            db_engine.clone(),
+            shutdown_signal.clone(),
+            sol_price.clone(),
+            discord,
+            &config.price,
+        ));
 
-        // let shutdown_signal = ShutdownSignal::new();
+        let siraaj = Siraaj {
+            config,
+            db: db_engine,
+            price_handler: price_handler.clone(),
+            sol_price,
+            tracked: Arc::new(RwLock::new(HashMap::new())),
+            tracked_pools: Arc::new(RwLock::new(HashMap::new())),
+            slot_clock: SlotClock::default(),
+        };
 
-        // db_engine.postgres.db.health_check().await?;
-        // db_engine.postgres.db.initialize().await?;
+        siraaj.load_tracked_curves().await?;
+        siraaj.load_tracked_pools().await?;
 
-        // let token_handler = Arc::new(TokenHandlerMetadataOperator::new(
-        //     db_engine.clone(), shutdown_signal.clone()));
+        let new_token_subscriber_handle = siraaj.spawn_new_token_subscriber(shutdown_signal.clone());
+        let tracked_curve_pruner_handle = siraaj.spawn_tracked_curve_pruner();
+        let holder_snapshot_handle = siraaj.spawn_holder_snapshotter(rpc_config);
+        let history_partition_maintenance_handle = siraaj
+            .db
+            .postgres()?
+            .partition
+            .spawn_maintenance(siraaj.config.storage_postgres.partitions.clone(), shutdown_signal.clone());
 
-        // let raqib = Raqib { config, db: db_engine, token_handler: token_handler };
+        let mut pipeline = make_bonding_curve_subscriber_pipeline(&siraaj)?;
+        let mut curve_trade_pipeline = make_curve_trade_subscriber_pipeline(&siraaj)?;
+        let mut pump_swap_pipeline = make_pump_swap_subscriber_pipeline(&siraaj)?;
 
-        // let shutdown_signal = ShutdownSignal::new();
+        tokio::select! {
+            result = pipeline.run() => {
+                price_handler.shutdown();
+                result.map_err(|e| {
+                    error!("pipeline_error: {}", e);
+                    err_with_loc!(EngineError::EngineError(e))
+                  })?
+            },
+            result = curve_trade_pipeline.run() => {
+                price_handler.shutdown();
+                result.map_err(|e| {
+                    error!("curve_trade_pipeline_error: {}", e);
+                    err_with_loc!(EngineError::EngineError(e))
+                  })?
+            },
+            result = pump_swap_pipeline.run() => {
+                price_handler.shutdown();
+                result.map_err(|e| {
+                    error!("pump_swap_pipeline_error: {}", e);
+                    err_with_loc!(EngineError::EngineError(e))
+                  })?
+            },
+            _ = new_token_subscriber_handle => {
+                info!("new_token_subscriber::stopped");
+                price_handler.shutdown();
+            },
+            _ = sol_price_refresh_handle => {
+                info!("sol_price_refresh::stopped");
+                price_handler.shutdown();
+            },
+            _ = tracked_curve_pruner_handle => {
+                info!("tracked_curve_pruner::stopped");
+                price_handler.shutdown();
+            },
+            _ = holder_snapshot_handle => {
+                info!("holder_snapshotter::stopped");
+                price_handler.shutdown();
+            },
+            _ = history_partition_maintenance_handle => {
+                info!("history_partition_maintenance::stopped");
+                price_handler.shutdown();
+            },
+            _ = tokio::signal::ctrl_c() => {
+                info!("termination_signal::graceful_shutdown");
+                price_handler.shutdown();
+            },
+        }
 
-        // let mut pipeline = make_pumpfun_subscriber_pipeline(raqib)?;
+        info!("all_component_shutdown");
+        tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
-        // let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel(1);
+        info!("siraaj::shutdown");
 
-        // tokio::select! {
-        //     result = pipeline.run() => {
-        //         shutdown_signal.shutdown();
-        //         let _ = shutdown_tx.send(()).await;
-        //         result.map_err(|e| {
-        //             error!("pipeline_error: {}", e);
-        //             err_with_loc!(EngineError::EngineError(e))
-        //           })?
-        //     },
-        //     _ = tokio::signal::ctrl_c() => {
-        //         info!("termination_signal::graceful_shutdown");
+        Ok(())
+    }
 
-        //         shutdown_signal.shutdown();
-        //         let _ = shutdown_tx.send(()).await;
-        //     },
-        //     _ = shutdown_rx.recv() => {
-        //         info!("shutdown_signal::other_component");
+    fn tracking_cutoff(&self) -> u64 {
+        (chrono::Utc::now().timestamp().max(0) as u64).saturating_sub(self.config.price.tracking_window_secs)
+    }
 
-        //         shutdown_signal.shutdown();
-        //     }
-        // }
+    // Seed the tracked set with unbonded mints still inside the tracking window
+    async fn load_tracked_curves(&self) -> Result<()> {
+        let tokens = self
+            .db
+            .backend
+            .tokens
+            .get_unbonded_tokens_since(&Platform::PumpFun.to_string(), self.tracking_cutoff())
+            .await?;
 
-        // info!("all_component_shutdown");
-        // tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
+        let mut tracked = self.tracked.write().await;
+        for (mint, created_at) in tokens {
+            tracked.insert(derive_bonding_curve(&mint), TrackedCurve {
+                mint,
+                created_at,
+            });
+        }
+        info!("tracked_curves_loaded::count::{}", tracked.len());
+        Ok(())
+    }
 
-        // info!("raqib::shutdown");
+    // Seed the tracked pools with mints that graduated inside the tracking window
+    async fn load_tracked_pools(&self) -> Result<()> {
+        let tokens = self
+            .db
+            .backend
+            .tokens
+            .get_bonded_tokens_since(&Platform::PumpFun.to_string(), self.tracking_cutoff())
+            .await?;
 
+        let mut tracked_pools = self.tracked_pools.write().await;
+        for (mint, bonded_at) in tokens {
+            tracked_pools.insert(derive_pump_swap_pool(&mint), TrackedPool {
+                mint,
+                bonded_at,
+            });
+        }
+        info!("tracked_pools_loaded::count::{}", tracked_pools.len());
         Ok(())
     }
+
+    // Start tracking every new pump.fun token announced by Raqib, read through Siraaj's consumer group
+    fn spawn_new_token_subscriber(
+        &self,
+        shutdown_signal: ShutdownSignal,
+    ) -> JoinHandle<()> {
+        let stream = self.db.backend.streams.clone();
+        let tracked = self.tracked.clone();
+        tokio::spawn(async move {
+            let channel = "new_token_created";
+            if let Err(e) = stream.ensure_group(channel).await {
+                error!("failed_to_create_new_token_created_group::error::{}", e);
+                return;
+            }
+
+            let mut last_claim = None;
+            loop {
+                let events = tokio::select! {
+                    events = stream.next_batch::<NewTokenCache>(channel, &mut last_claim) => events,
+                    _ = shutdown_signal.wait_for_shutdown() => break,
+                };
+                let events = match events {
+                    Ok(events) => events,
+                    Err(e) => {
+                        error!("failed_to_read_new_token_created::error::{}", e);
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        continue;
+                    },
+                };
+
+                for event in events {
+                    let token = event.payload;
+                    let bonding_curve = token.bonding_curve.unwrap_or_else(|| derive_bonding_curve(&token.mint));
+                    tracked.write().await.insert(bonding_curve, TrackedCurve {
+                        mint: token.mint,
+                        created_at: token.created_at,
+                    });
+                    debug!("tracking_bonding_curve::mint::{}::bonding_curve::{}", token.mint, bonding_curve);
+                    if let Err(e) = stream.ack(channel, &event.id).await {
+                        error!("failed_to_ack_new_token_created::mint::{}::error::{}", token.mint, e);
+                    }
+                }
+            }
+        })
+    }
+
+    // Drop curves and pools that aged out of the tracking window
+    fn spawn_tracked_curve_pruner(&self) -> JoinHandle<()> {
+        let siraaj = self.clone();
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(siraaj.config.price.prune_interval_secs.max(1)));
+            loop {
+                interval.tick().await;
+                let cutoff = siraaj.tracking_cutoff();
+                let mut expired = Vec::new();
+                let mut tracked = siraaj.tracked.write().await;
+                let before = tracked.len();
+                tracked.retain(|_, curve| {
+                    if curve.created_at < cutoff {
+                        expired.push(curve.mint);
+                    }
+                    curve.created_at >= cutoff
+                });
+                if tracked.len() != before {
+                    debug!("tracked_curves_pruned::removed::{}::remaining::{}", before - tracked.len(), tracked.len());
+                }
+                drop(tracked);
+
+                let mut tracked_pools = siraaj.tracked_pools.write().await;
+                let before = tracked_pools.len();
+                tracked_pools.retain(|_, pool| {
+                    if pool.bonded_at < cutoff {
+                        expired.push(pool.mint);
+                    }
+                    pool.bonded_at >= cutoff
+                });
+                if tracked_pools.len() != before {
+                    debug!(
+                        "tracked_pools_pruned::removed::{}::remaining::{}",
+                        before - tracked_pools.len(),
+                        tracked_pools.len()
+                    );
+                }
+                drop(tracked_pools);
+
+                if !expired.is_empty() {
+                    if let Err(e) = siraaj.price_handler.stop_tracking(expired).await {
+                        error!("stop_tracking_expired_mints_failed::error::{}", e);
+                    }
+                }
+            }
+        })
+    }
+
+    // Periodically snapshot holder concentration of every tracked mint, curve or pool
+    fn spawn_holder_snapshotter(
+        &self,
+        rpc_config: Arc<RpcConfig>,
+    ) -> JoinHandle<()> {
+        let siraaj = self.clone();
+        let snapshotter = HolderSnapshotter::new(self.db.backend.clone(), rpc_config);
+        tokio::spawn(async move {
+            let mut interval =
+                tokio::time::interval(Duration::from_secs(siraaj.config.price.holder_snapshot_interval_secs.max(1)));
+            let batch_size = siraaj.config.price.holder_snapshot_batch_size.max(1);
+            let concurrency = siraaj.config.price.holder_snapshot_concurrency.max(1);
+            // Where the next round picks up, so every mint gets its turn when more are tracked than fit a batch
+            let mut cursor = 0;
+            loop {
+                interval.tick().await;
+                let mut mints: Vec<Pubkey> = siraaj.tracked.read().await.values().map(|curve| curve.mint).collect();
+                mints.extend(siraaj.tracked_pools.read().await.values().map(|pool| pool.mint));
+                if mints.is_empty() {
+                    continue;
+                }
+                mints.sort();
+
+                cursor %= mints.len();
+                let batch: Vec<Pubkey> = mints
+                    .iter()
+                    .cycle()
+                    .skip(cursor)
+                    .take(batch_size.min(mints.len()))
+                    .copied()
+                    .collect();
+                cursor += batch.len();
+
+                futures::stream::iter(batch)
+                    .for_each_concurrent(concurrency, |mint| {
+                        let snapshotter = &snapshotter;
+                        async move {
+                            if let Err(e) = snapshotter.snapshot_and_store(&mint).await {
+                                error!("holder_snapshot_failed::mint::{}::error::{}", mint, e);
+                            }
+                        }
+                    })
+                    .await;
+            }
+        })
+    }
 }
@@ -6,6 +6,8 @@ pub enum HandlerError {
     SendTokenHandlerError(String),
     #[error("Failed to send creator to metadata handler: {0}")]
     SendCreatorHandlerError(String),
+    #[error("Failed to send price update to price handler: {0}")]
+    SendPriceHandlerError(String),
     #[error("Failed to send log to Discord: {0}")]
     SendDiscordError(String),
     #[error("Failed to analyze CEX: {0}")]
@@ -10,4 +10,7 @@ pub enum DiscordHandlerLevel {
     Debug {
         message: String,
     },
+    Alert {
+        message: String,
+    },
 }
@@ -114,6 +114,18 @@ async fn run_discord_webhook_handler(mut discord_webhook_handler: DiscordWebhook
                             }
                         }
                     },
+                    DiscordHandlerLevel::Alert { message } => {
+                        // Alerts land in the info channel when no dedicated alert channel is configured
+                        let channel = discord_webhook_handler
+                            .discord_config
+                            .get_channel_by_name(&DiscordChannel::Alert)
+                            .or_else(|| discord_webhook_handler.discord_config.get_channel_by_name(&DiscordChannel::Info));
+                        if let Some(channel) = channel {
+                            if let Err(e) = discord_webhook_handler.send_to_discord(channel, &message).await {
+                                eprintln!("Error sending alert to Discord channel {:?}: {}", channel.channel_name, e);
+                            }
+                        }
+                    },
                 }
             },
             else => {
@@ -174,4 +186,18 @@ impl DiscordWebhookHandlerOperator {
             _ => Ok(()),
         }
     }
+
+    pub fn send_alert(
+        &self,
+        message: String,
+    ) -> Result<()> {
+        self.sender
+            .try_send(DiscordHandlerLevel::Alert {
+                message,
+            })
+            .map_err(|e| {
+                error!("send_discord_alert_failed: {}", e);
+                err_with_loc!(HandlerError::SendDiscordError(format!("send_discord_alert_failed:{}", e)))
+            })
+    }
 }
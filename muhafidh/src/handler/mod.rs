@@ -1,3 +1,4 @@
 pub mod discord;
+pub mod price;
 pub mod shutdown;
 pub mod token;
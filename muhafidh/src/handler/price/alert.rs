@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use solana_pubkey::Pubkey;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+use crate::Result;
+use crate::handler::discord::webhook::DiscordWebhookHandlerOperator;
+use crate::model::alert::AlertHit;
+use crate::model::alert::AlertRule;
+use crate::model::alert::PriceWindow;
+use crate::model::price::TokenAth;
+use crate::model::price::TokenPrice;
+use crate::storage::StorageEngine;
+use crate::storage::backend::EventBusExt;
+use crate::storage::redis::model::PriceAlertTriggeredCache;
+
+/// Evaluates the alert rules stored in Postgres against every price update, keeping rules,
+/// CEX-funded mints and recent prices in memory so evaluation never touches the database
+pub struct AlertEngine {
+    db: Arc<StorageEngine>,
+    discord: DiscordWebhookHandlerOperator,
+    // Only mints created inside this window are considered for CEX-funded rules
+    tracking_window_secs: u64,
+    rules: Vec<AlertRule>,
+    cex_funded: HashSet<Pubkey>,
+    last_market_cap: HashMap<Pubkey, f64>,
+    windows: HashMap<Pubkey, PriceWindow>,
+    // Last hit per (rule, mint), for cooldowns
+    last_fired: HashMap<(i32, Pubkey), i64>,
+}
+
+impl AlertEngine {
+    pub fn new(
+        db: Arc<StorageEngine>,
+        discord: DiscordWebhookHandlerOperator,
+        tracking_window_secs: u64,
+    ) -> Self {
+        Self {
+            db,
+            discord,
+            tracking_window_secs,
+            rules: Vec::new(),
+            cex_funded: HashSet::new(),
+            last_market_cap: HashMap::new(),
+            windows: HashMap::new(),
+            last_fired: HashMap::new(),
+        }
+    }
+
+    // Longest window any rule looks back over
+    fn max_window_secs(&self) -> i64 {
+        self.rules
+            .iter()
+            .filter_map(|rule| rule.condition.window_secs())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Reload rules and CEX-funded mints, and drop state of mints that went quiet
+    pub async fn refresh(&mut self) {
+        match self.db.backend.alerts.get_enabled_alert_rules().await {
+            Ok(rules) => {
+                if rules.len() != self.rules.len() {
+                    info!("price_alert_rules_loaded::count::{}", rules.len());
+                }
+                self.rules = rules;
+            },
+            Err(e) => error!("load_price_alert_rules_failed::error::{}", e),
+        }
+
+        if self.rules.iter().any(|rule| rule.cex_funded_only) {
+            let cutoff = (chrono::Utc::now().timestamp().max(0) as u64).saturating_sub(self.tracking_window_secs);
+            match self.db.backend.tokens.get_cex_funded_tokens_since(cutoff).await {
+                Ok(mints) => self.cex_funded = mints.into_iter().collect(),
+                Err(e) => error!("load_cex_funded_tokens_failed::error::{}", e),
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let max_window_secs = self.max_window_secs();
+        self.windows
+            .retain(|_, window| window.last_at().is_some_and(|at| now - at <= max_window_secs));
+        let rules: HashMap<i32, i64> = self.rules.iter().map(|rule| (rule.id, rule.cooldown_secs)).collect();
+        self.last_fired
+            .retain(|(rule_id, _), fired_at| rules.get(rule_id).is_some_and(|cooldown| now - *fired_at < *cooldown));
+    }
+
+    /// Drop the per-mint state of a mint that is no longer tracked
+    pub fn forget(
+        &mut self,
+        mint: &Pubkey,
+    ) {
+        self.last_market_cap.remove(mint);
+        self.windows.remove(mint);
+        self.last_fired.retain(|(_, fired_mint), _| fired_mint != mint);
+    }
+
+    /// Check every applicable rule against a new price and deliver the hits
+    pub async fn evaluate(
+        &mut self,
+        price: &TokenPrice,
+        ath: Option<&TokenAth>,
+    ) {
+        if self.rules.is_empty() {
+            return;
+        }
+
+        let max_window_secs = self.max_window_secs();
+        if max_window_secs > 0 {
+            self.windows
+                .entry(price.mint)
+                .or_default()
+                .push(price.timestamp, price.price, max_window_secs);
+        }
+        let previous_market_cap = self.last_market_cap.insert(price.mint, price.market_cap_sol);
+        let cex_funded = self.cex_funded.contains(&price.mint);
+
+        let mut hits = Vec::new();
+        for rule in &self.rules {
+            if !rule.applies_to(&price.mint, cex_funded) {
+                continue;
+            }
+            if let Some(fired_at) = self.last_fired.get(&(rule.id, price.mint)) {
+                if price.timestamp - fired_at < rule.cooldown_secs {
+                    continue;
+                }
+            }
+            let Some(observed) =
+                rule.condition
+                    .evaluate(price, previous_market_cap, self.windows.get(&price.mint), ath)
+            else {
+                continue;
+            };
+            hits.push(AlertHit {
+                rule_id: rule.id,
+                rule_name: rule.name.clone(),
+                mint: price.mint,
+                condition: rule.condition.clone(),
+                observed,
+                price_sol: price.price_sol,
+                market_cap_sol: price.market_cap_sol,
+                market_cap_usd: price.market_cap_usd,
+                triggered_at: price.timestamp,
+            });
+        }
+
+        for hit in hits {
+            self.last_fired.insert((hit.rule_id, hit.mint), hit.triggered_at);
+            if let Err(e) = self.deliver(&hit).await {
+                error!("deliver_price_alert_failed::rule::{}::mint::{}::error::{}", hit.rule_id, hit.mint, e);
+            }
+        }
+    }
+
+    async fn deliver(
+        &self,
+        hit: &AlertHit,
+    ) -> Result<()> {
+        debug!("price_alert_triggered::rule::{}::mint::{}::observed::{}", hit.rule_id, hit.mint, hit.observed);
+
+        let event = PriceAlertTriggeredCache {
+            rule_id: hit.rule_id,
+            rule_name: hit.rule_name.clone(),
+            mint: hit.mint.to_string(),
+            kind: hit.condition.as_str().to_string(),
+            threshold: hit.condition.threshold(),
+            observed: hit.observed,
+            price_sol: hit.price_sol,
+            market_cap_sol: hit.market_cap_sol,
+            market_cap_usd: hit.market_cap_usd,
+            triggered_at: hit.triggered_at,
+        };
+        if let Err(e) = self.db.backend.events.broadcast("price_alert_triggered", &event).await {
+            error!("publish_price_alert_triggered_failed::mint::{}::error::{}", hit.mint, e);
+        }
+        if let Err(e) = self.discord.send_alert(hit.message()) {
+            error!("send_price_alert_to_discord_failed::mint::{}::error::{}", hit.mint, e);
+        }
+
+        self.db.backend.alerts.record_alert_hit(hit).await
+    }
+}
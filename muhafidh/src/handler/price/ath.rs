@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use solana_pubkey::Pubkey;
+use tracing::debug;
+use tracing::error;
+
+use crate::Result;
+use crate::model::price::TokenAth;
+use crate::model::price::TokenPrice;
+use crate::model::token::TokenMetadata;
+use crate::storage::StorageBackend;
+use crate::storage::backend::EventBusExt;
+use crate::storage::backend::KvCacheExt;
+use crate::storage::redis::model::TokenNewAthCache;
+
+/// Keeps every token's all-time high in memory and Redis as prices arrive, and flushes
+/// the changed ones to Postgres in batches so hot tokens don't hammer the `tokens` table
+pub struct AthTracker {
+    db: StorageBackend,
+    // Minimum rise over the last announced ATH before `token_new_ath` fires again
+    threshold_pct: f64,
+    aths: HashMap<Pubkey, TokenAth>,
+    dirty: HashSet<Pubkey>,
+}
+
+impl AthTracker {
+    pub fn new(
+        db: StorageBackend,
+        threshold_pct: f64,
+    ) -> Self {
+        Self {
+            db,
+            threshold_pct,
+            aths: HashMap::new(),
+            dirty: HashSet::new(),
+        }
+    }
+
+    async fn load(
+        &mut self,
+        mint: &Pubkey,
+    ) -> Result<Option<TokenAth>> {
+        if let Some(ath) = self.aths.get(mint) {
+            return Ok(Some(ath.clone()));
+        }
+        let mut ath = self.db.kv.get::<TokenAth>(&TokenAth::redis_key(mint)).await?;
+        // Not cached yet or evicted from Redis, continue from the high stored with the token
+        if ath.is_none() {
+            ath = self
+                .db
+                .tokens
+                .get_token_by_mint(mint)
+                .await?
+                .filter(|token| token.all_time_high_price > 0)
+                .map(|token| {
+                    TokenAth::from_stored(*mint, token.all_time_high_price, token.all_time_high_price_at as i64)
+                });
+        }
+        if let Some(ath) = &ath {
+            self.aths.insert(*mint, ath.clone());
+        }
+        Ok(ath)
+    }
+
+    /// Record a new price, returns the updated ATH when the price set a new high
+    pub async fn observe(
+        &mut self,
+        price: &TokenPrice,
+    ) -> Result<Option<TokenAth>> {
+        let mut ath = match self.load(&price.mint).await? {
+            Some(ath) if price.price <= ath.price => return Ok(None),
+            Some(mut ath) => {
+                ath.price = price.price;
+                ath.price_sol = price.price_sol;
+                ath.market_cap_sol = price.market_cap_sol;
+                ath.market_cap_usd = price.market_cap_usd;
+                ath.at = price.timestamp;
+                ath
+            },
+            // The first price seen is both the first high and the baseline for announcements
+            None => TokenAth::from_price(price),
+        };
+
+        let rise_pct = ath.rise_over_announced_pct();
+        if rise_pct >= self.threshold_pct {
+            let event = TokenNewAthCache {
+                mint: price.mint.to_string(),
+                previous_ath: ath.announced_price,
+                ath: ath.price,
+                ath_sol: ath.price_sol,
+                rise_pct,
+                market_cap_sol: ath.market_cap_sol,
+                market_cap_usd: ath.market_cap_usd,
+                at: ath.at,
+            };
+            if let Err(e) = self.db.events.broadcast("token_new_ath", &event).await {
+                error!("publish_token_new_ath_failed::mint::{}::error::{}", price.mint, e);
+            }
+            ath.announced_price = ath.price;
+        }
+
+        self.db.kv.set(&TokenAth::redis_key(&price.mint), &ath).await?;
+        self.aths.insert(price.mint, ath.clone());
+        self.dirty.insert(price.mint);
+        Ok(Some(ath))
+    }
+
+    /// ATH of a mint already seen by `observe`
+    pub fn current(
+        &self,
+        mint: &Pubkey,
+    ) -> Option<&TokenAth> {
+        self.aths.get(mint)
+    }
+
+    /// Drop a mint that is no longer priced, after writing out its pending ATH. The mint is loaded again
+    /// from Redis or Postgres if it comes back, e.g. once its curve graduates to a pool
+    pub async fn forget(
+        &mut self,
+        mint: &Pubkey,
+    ) {
+        if self.dirty.contains(mint) && !self.flush_mint(mint).await {
+            return;
+        }
+        self.dirty.remove(mint);
+        self.aths.remove(mint);
+    }
+
+    /// Write every ATH changed since the last flush to Postgres and the token cache
+    pub async fn flush(&mut self) {
+        if self.dirty.is_empty() {
+            return;
+        }
+
+        let dirty: Vec<Pubkey> = self.dirty.drain().collect();
+        let mut flushed = 0;
+        for mint in dirty {
+            if self.flush_mint(&mint).await {
+                flushed += 1;
+            } else {
+                // Retry on the next flush
+                self.dirty.insert(mint);
+            }
+        }
+
+        debug!("token_ath_flushed::count::{}::pending::{}", flushed, self.dirty.len());
+    }
+
+    // Returns false when the ATH could not be written and has to be retried
+    async fn flush_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> bool {
+        let Some(ath) = self.aths.get(mint).cloned() else {
+            return true;
+        };
+
+        if let Err(e) = self.db.tokens.update_token_ath(mint, ath.price, ath.at as u64).await {
+            error!("flush_token_ath_failed::mint::{}::error::{}", mint, e);
+            return false;
+        }
+
+        let token_key = mint.to_string();
+        if let Ok(Some(mut token_metadata)) = self.db.kv.get::<TokenMetadata>(&token_key).await {
+            if token_metadata.all_time_high_price < ath.price {
+                token_metadata.all_time_high_price = ath.price;
+                token_metadata.all_time_high_price_at = ath.at as u64;
+                if let Err(e) = self.db.kv.set(&token_key, &token_metadata).await {
+                    error!("update_token_ath_redis_failed::mint::{}::error::{}", mint, e);
+                }
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use solana_pubkey::Pubkey;
+use tracing::debug;
+use tracing::error;
+
+use crate::storage::StorageBackend;
+use crate::storage::postgres::model::CandleInterval;
+
+// Intervals derived from the 1m candles instead of raw history
+const ROLLUP_INTERVALS: [CandleInterval; 3] = [CandleInterval::M5, CandleInterval::H1, CandleInterval::D1];
+
+/// Keeps 1m/5m/1h/1d OHLCV candles per mint up to date with the recorded trades. Trades are
+/// stored one row each with their block time, the builder only remembers which time range of a
+/// mint saw trades. On flush the touched 1m buckets are rebuilt from storage and the coarser
+/// intervals rolled up from them. A trade landing in a minute an earlier flush already wrote has
+/// every interval rebuilt from raw history instead. Both are idempotent, so a retried flush or a
+/// late trade can't double count
+pub struct CandleBuilder {
+    db: StorageBackend,
+    // Earliest and latest trade time recorded per mint since the last flush
+    touched: HashMap<Pubkey, (i64, i64)>,
+    // Same, for trades older than the last minute flushed for their mint
+    late: HashMap<Pubkey, (i64, i64)>,
+    // Latest trade time a flush wrote, per mint
+    flushed: HashMap<Pubkey, i64>,
+}
+
+fn widen(
+    ranges: &mut HashMap<Pubkey, (i64, i64)>,
+    mint: &Pubkey,
+    from: i64,
+    to: i64,
+) {
+    ranges
+        .entry(*mint)
+        .and_modify(|(start, end)| {
+            *start = (*start).min(from);
+            *end = (*end).max(to);
+        })
+        .or_insert((from, to));
+}
+
+impl CandleBuilder {
+    pub fn new(db: StorageBackend) -> Self {
+        Self {
+            db,
+            touched: HashMap::new(),
+            late: HashMap::new(),
+            flushed: HashMap::new(),
+        }
+    }
+
+    /// Note a trade of `mint` at `timestamp` that was written to the price and volume history
+    pub fn observe(
+        &mut self,
+        mint: &Pubkey,
+        timestamp: i64,
+    ) {
+        let late = self
+            .flushed
+            .get(mint)
+            .is_some_and(|flushed| timestamp < CandleInterval::M1.bucket_start(*flushed));
+        if late {
+            widen(&mut self.late, mint, timestamp, timestamp);
+        } else {
+            widen(&mut self.touched, mint, timestamp, timestamp);
+        }
+    }
+
+    /// Drop what is kept for mints that are no longer tracked
+    pub fn forget(
+        &mut self,
+        mint: &Pubkey,
+    ) {
+        self.flushed.remove(mint);
+    }
+
+    /// Rebuild every bucket touched since the last flush
+    pub async fn flush(&mut self) {
+        if self.touched.is_empty() && self.late.is_empty() {
+            return;
+        }
+
+        let mut rebuilt = 0;
+        let late: Vec<(Pubkey, (i64, i64))> = self.late.drain().collect();
+        for (mint, (from, to)) in late {
+            let mut failed = false;
+            for interval in CandleInterval::ALL {
+                match self.db.time_series.rebuild_candles(&mint.to_string(), interval, from, to).await {
+                    Ok(count) => rebuilt += count,
+                    Err(e) => {
+                        error!(
+                            "rebuild_token_candles_failed::mint::{}::interval::{}::error::{}",
+                            mint,
+                            interval.as_str(),
+                            e
+                        );
+                        failed = true;
+                    },
+                }
+            }
+            // Retry the whole range on the next flush, together with whatever arrived meanwhile
+            if failed {
+                widen(&mut self.late, &mint, from, to);
+            }
+        }
+
+        let touched: Vec<(Pubkey, (i64, i64))> = self.touched.drain().collect();
+        for (mint, (from, to)) in touched {
+            let mint_key = mint.to_string();
+            let mut failed = false;
+            match self
+                .db
+                .time_series
+                .rebuild_candles(&mint_key, CandleInterval::M1, from, to)
+                .await
+            {
+                Ok(count) => rebuilt += count,
+                Err(e) => {
+                    error!(
+                        "rebuild_token_candles_failed::mint::{}::interval::{}::error::{}",
+                        mint,
+                        CandleInterval::M1.as_str(),
+                        e
+                    );
+                    failed = true;
+                },
+            }
+            // Coarser candles are only as good as the minutes under them
+            for interval in ROLLUP_INTERVALS {
+                if failed {
+                    break;
+                }
+                match self.db.time_series.rollup_candles(&mint_key, interval, from, to).await {
+                    Ok(count) => rebuilt += count,
+                    Err(e) => {
+                        error!(
+                            "rollup_token_candles_failed::mint::{}::interval::{}::error::{}",
+                            mint,
+                            interval.as_str(),
+                            e
+                        );
+                        failed = true;
+                    },
+                }
+            }
+
+            if failed {
+                widen(&mut self.touched, &mint, from, to);
+            } else {
+                self.flushed
+                    .entry(mint)
+                    .and_modify(|flushed| *flushed = (*flushed).max(to))
+                    .or_insert(to);
+            }
+        }
+
+        debug!(
+            "token_candles_flushed::count::{}::pending::{}::late_pending::{}",
+            rebuilt,
+            self.touched.len(),
+            self.late.len()
+        );
+    }
+}
@@ -0,0 +1,27 @@
+pub mod alert;
+pub mod ath;
+pub mod candle;
+pub mod solana;
+pub mod token;
+
+use solana_pubkey::Pubkey;
+
+use crate::model::price::BondingCurveState;
+use crate::model::price::CurveTradeState;
+use crate::model::price::PoolSwapState;
+
+pub enum PriceHandler {
+    BondingCurveUpdate {
+        state: BondingCurveState,
+    },
+    CurveTrade {
+        state: CurveTradeState,
+    },
+    PoolSwap {
+        state: PoolSwapState,
+    },
+    // Mints that left the tracking window, their per-mint state is dropped
+    StopTracking {
+        mints: Vec<Pubkey>,
+    },
+}
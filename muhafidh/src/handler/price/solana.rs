@@ -0,0 +1,272 @@
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Client;
+use solana_commitment_config::CommitmentConfig;
+use solana_pubkey::Pubkey;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::error;
+use tracing::warn;
+
+use crate::Result;
+use crate::config::PriceConfig;
+use crate::config::RpcConfig;
+use crate::config::RpcProviderRole;
+use crate::err_with_loc;
+use crate::error::HandlerError;
+use crate::handler::shutdown::ShutdownSignal;
+use crate::model::price::SOL_USD_PRICE_KEY;
+use crate::model::price::SolUsdPrice;
+use crate::storage::StorageBackend;
+use crate::storage::backend::KvCacheExt;
+
+/// A place the SOL/USD price can be read from. Sources are tried in order until one answers.
+#[async_trait::async_trait]
+pub trait SolPriceSource: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn fetch(&self) -> Result<SolUsdPrice>;
+}
+
+/// Reads a Pyth `PriceUpdateV2` account (pull oracle) through the configured RPC providers
+pub struct PythSolPriceSource {
+    rpc_config: Arc<RpcConfig>,
+    price_account: Pubkey,
+}
+
+impl PythSolPriceSource {
+    // discriminator (8) + write_authority (32)
+    const VERIFICATION_LEVEL_OFFSET: usize = 40;
+
+    pub fn new(
+        rpc_config: Arc<RpcConfig>,
+        price_account: Pubkey,
+    ) -> Self {
+        Self {
+            rpc_config,
+            price_account,
+        }
+    }
+
+    fn read_bytes<const N: usize>(
+        data: &[u8],
+        offset: usize,
+    ) -> Result<[u8; N]> {
+        data.get(offset..offset + N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| err_with_loc!(HandlerError::RpcError(format!("pyth_account_too_short::{}", data.len()))))
+    }
+
+    fn decode(data: &[u8]) -> Result<SolUsdPrice> {
+        // VerificationLevel is borsh encoded: Partial { num_signatures: u8 } takes two bytes, Full one
+        let level = Self::read_bytes::<1>(data, Self::VERIFICATION_LEVEL_OFFSET)?[0];
+        let message = match level {
+            0 => Self::VERIFICATION_LEVEL_OFFSET + 2,
+            1 => Self::VERIFICATION_LEVEL_OFFSET + 1,
+            other => {
+                return Err(err_with_loc!(HandlerError::RpcError(format!(
+                    "pyth_unknown_verification_level::{}",
+                    other
+                ))));
+            },
+        };
+
+        // PriceFeedMessage: feed_id (32), price (i64), conf (u64), exponent (i32), publish_time (i64), ...
+        let price = i64::from_le_bytes(Self::read_bytes(data, message + 32)?);
+        let conf = u64::from_le_bytes(Self::read_bytes(data, message + 40)?);
+        let exponent = i32::from_le_bytes(Self::read_bytes(data, message + 48)?);
+        let publish_time = i64::from_le_bytes(Self::read_bytes(data, message + 52)?);
+
+        let scale = 10f64.powi(exponent);
+        Ok(SolUsdPrice {
+            price: price as f64 * scale,
+            confidence: Some(conf as f64 * scale),
+            source: String::from("pyth"),
+            publish_time,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl SolPriceSource for PythSolPriceSource {
+    fn name(&self) -> &str {
+        "pyth"
+    }
+
+    async fn fetch(&self) -> Result<SolUsdPrice> {
+        let Some((client, provider)) = self
+            .rpc_config
+            .get_next_client_for_role(&RpcProviderRole::TransactionFetcher, CommitmentConfig::confirmed())
+            .await
+        else {
+            return Err(err_with_loc!(HandlerError::RpcError(String::from("no_rpc_provider_for_pyth"))));
+        };
+
+        let data = client.get_account_data(&self.price_account).await.map_err(|e| {
+            error!("failed_to_get_pyth_account::{}::provider::{}::error::{}", self.price_account, provider, e);
+            err_with_loc!(HandlerError::RpcError(format!("failed_to_get_pyth_account: {}", e)))
+        })?;
+
+        Self::decode(&data)
+    }
+}
+
+/// Reads the price from any JSON HTTP endpoint, `pointer` being a JSON pointer to the numeric price
+pub struct HttpSolPriceSource {
+    http_client: Client,
+    url: String,
+    pointer: String,
+}
+
+impl HttpSolPriceSource {
+    pub fn new(
+        url: String,
+        pointer: String,
+    ) -> Self {
+        Self {
+            http_client: Client::new(),
+            url,
+            pointer,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SolPriceSource for HttpSolPriceSource {
+    fn name(&self) -> &str {
+        "http"
+    }
+
+    async fn fetch(&self) -> Result<SolUsdPrice> {
+        let body: serde_json::Value = self
+            .http_client
+            .get(&self.url)
+            .timeout(Duration::from_secs(5))
+            .send()
+            .await
+            .map_err(|e| err_with_loc!(HandlerError::RpcError(format!("failed_to_fetch_sol_price: {}", e))))?
+            .json()
+            .await
+            .map_err(|e| err_with_loc!(HandlerError::RpcError(format!("failed_to_parse_sol_price: {}", e))))?;
+
+        // Some APIs return prices as strings
+        let price = body
+            .pointer(&self.pointer)
+            .and_then(|value| value.as_f64().or_else(|| value.as_str().and_then(|s| s.parse::<f64>().ok())));
+
+        match price {
+            Some(price) => Ok(SolUsdPrice {
+                price,
+                confidence: None,
+                source: self.url.clone(),
+                publish_time: chrono::Utc::now().timestamp(),
+            }),
+            None => Err(err_with_loc!(HandlerError::RpcError(format!("sol_price_not_found_at::{}", self.pointer)))),
+        }
+    }
+}
+
+/// Keeps a fresh SOL/USD price in memory and in Redis under `sol_usd_price`
+pub struct SolPriceOracle {
+    sources: Vec<Box<dyn SolPriceSource>>,
+    latest: RwLock<Option<SolUsdPrice>>,
+    max_age_secs: i64,
+    db: StorageBackend,
+}
+
+impl SolPriceOracle {
+    pub fn new(
+        sources: Vec<Box<dyn SolPriceSource>>,
+        max_age_secs: i64,
+        db: StorageBackend,
+    ) -> Self {
+        Self {
+            sources,
+            latest: RwLock::new(None),
+            max_age_secs,
+            db,
+        }
+    }
+
+    /// Pyth first, then the HTTP fallback when one is configured
+    pub fn from_config(
+        price_config: &PriceConfig,
+        rpc_config: Arc<RpcConfig>,
+        db: StorageBackend,
+    ) -> Result<Self> {
+        let pyth_account = Pubkey::from_str(&price_config.pyth_sol_usd_account).map_err(|e| {
+            error!("invalid_pyth_sol_usd_account::{}::{}", price_config.pyth_sol_usd_account, e);
+            err_with_loc!(HandlerError::RpcError(format!("invalid_pyth_sol_usd_account: {}", e)))
+        })?;
+
+        let mut sources: Vec<Box<dyn SolPriceSource>> =
+            vec![Box::new(PythSolPriceSource::new(rpc_config, pyth_account))];
+        if let Some(url) = &price_config.sol_usd_fallback_url {
+            sources.push(Box::new(HttpSolPriceSource::new(url.clone(), price_config.sol_usd_fallback_pointer.clone())));
+        }
+
+        Ok(Self::new(sources, price_config.sol_usd_max_age_secs as i64, db))
+    }
+
+    pub async fn refresh(&self) -> Result<SolUsdPrice> {
+        for source in &self.sources {
+            match source.fetch().await {
+                Ok(price) if price.price > 0.0 && price.is_fresh(self.max_age_secs) => {
+                    *self.latest.write().await = Some(price.clone());
+                    if let Err(e) = self.db.kv.set(SOL_USD_PRICE_KEY, &price).await {
+                        error!("store_sol_usd_price_failed::error::{}", e);
+                    }
+                    debug!("sol_usd_price_refreshed::source::{}::price::{}", source.name(), price.price);
+                    return Ok(price);
+                },
+                Ok(price) => {
+                    warn!(
+                        "sol_usd_price_rejected::source::{}::price::{}::publish_time::{}",
+                        source.name(),
+                        price.price,
+                        price.publish_time
+                    );
+                },
+                Err(e) => warn!("sol_usd_price_source_failed::source::{}::error::{}", source.name(), e),
+            }
+        }
+
+        Err(err_with_loc!(HandlerError::RpcError(String::from("all_sol_usd_price_sources_failed"))))
+    }
+
+    /// Latest price, `None` until the first refresh succeeds or once it went stale
+    pub async fn latest(&self) -> Option<SolUsdPrice> {
+        self.latest
+            .read()
+            .await
+            .clone()
+            .filter(|price| price.is_fresh(self.max_age_secs))
+    }
+
+    pub fn spawn_refresh(
+        self: &Arc<Self>,
+        interval_secs: u64,
+        shutdown: ShutdownSignal,
+    ) -> JoinHandle<()> {
+        let oracle = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_secs.max(1)));
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        if let Err(e) = oracle.refresh().await {
+                            error!("sol_usd_price_refresh_failed::error::{}", e);
+                        }
+                    },
+                    _ = shutdown.wait_for_shutdown() => {
+                        debug!("sol_usd_price_refresh::received_shutdown_signal");
+                        break;
+                    },
+                }
+            }
+        })
+    }
+}
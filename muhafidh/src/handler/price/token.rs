@@ -0,0 +1,309 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use solana_pubkey::Pubkey;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+use super::PriceHandler;
+use super::alert::AlertEngine;
+use super::ath::AthTracker;
+use super::candle::CandleBuilder;
+use super::solana::SolPriceOracle;
+use crate::Result;
+use crate::config::PriceConfig;
+use crate::err_with_loc;
+use crate::error::HandlerError;
+use crate::handler::discord::webhook::DiscordWebhookHandlerOperator;
+use crate::handler::shutdown::ShutdownSignal;
+use crate::model::price::BondingCurveState;
+use crate::model::price::CurveTradeState;
+use crate::model::price::PoolSwapState;
+use crate::model::price::TokenPrice;
+use crate::storage::StorageEngine;
+use crate::storage::backend::KvCacheExt;
+
+struct TokenPriceHandler {
+    receiver: mpsc::Receiver<PriceHandler>,
+    db: Arc<StorageEngine>,
+    shutdown: ShutdownSignal,
+    sol_price: Arc<SolPriceOracle>,
+    ath_tracker: AthTracker,
+    ath_flush_interval: Duration,
+    candle_builder: CandleBuilder,
+    candle_flush_interval: Duration,
+    alert_engine: AlertEngine,
+    alert_refresh_interval: Duration,
+}
+
+impl TokenPriceHandler {
+    fn new(
+        receiver: mpsc::Receiver<PriceHandler>,
+        db: Arc<StorageEngine>,
+        shutdown: ShutdownSignal,
+        sol_price: Arc<SolPriceOracle>,
+        discord: DiscordWebhookHandlerOperator,
+        price_config: &PriceConfig,
+    ) -> Self {
+        Self {
+            receiver,
+            ath_tracker: AthTracker::new(db.backend.clone(), price_config.ath_event_threshold_pct),
+            ath_flush_interval: Duration::from_secs(price_config.ath_flush_interval_secs.max(1)),
+            candle_builder: CandleBuilder::new(db.backend.clone()),
+            candle_flush_interval: Duration::from_secs(price_config.candle_flush_interval_secs.max(1)),
+            alert_engine: AlertEngine::new(db.clone(), discord, price_config.tracking_window_secs),
+            alert_refresh_interval: Duration::from_secs(price_config.alert_refresh_secs.max(1)),
+            db,
+            shutdown,
+            sol_price,
+        }
+    }
+
+    // Curve prices come from the reserves of each account update, dated by the block time of its slot.
+    // Volume comes from the trade events
+    async fn process_bonding_curve_update(
+        &mut self,
+        state: BondingCurveState,
+    ) -> Result<()> {
+        if !state.complete {
+            let sol_usd = self.sol_price.latest().await;
+            let price = TokenPrice::from_bonding_curve(&state).with_usd(sol_usd.as_ref());
+            // A curve account changes at most once per slot
+            return self.record_price(&price, 0, &state.slot.to_string()).await;
+        }
+
+        // Nothing moves on the curve once it completes, pricing continues from the PumpSwap pool
+        self.ath_tracker.forget(&state.mint).await;
+        self.db
+            .backend
+            .tokens
+            .mark_token_bonded(&state.mint, state.timestamp.max(0) as u64)
+            .await?;
+        info!("token_bonded::mint::{}", state.mint);
+        Ok(())
+    }
+
+    async fn process_curve_trade(
+        &mut self,
+        state: CurveTradeState,
+    ) -> Result<()> {
+        if state.sol_volume > 0 {
+            self.db
+                .backend
+                .time_series
+                .add_token_volume(&state.mint.to_string(), state.sol_volume, state.timestamp, &state.trade_id)
+                .await?;
+        }
+        self.candle_builder.observe(&state.mint, state.timestamp);
+        Ok(())
+    }
+
+    async fn stop_tracking(
+        &mut self,
+        mints: Vec<Pubkey>,
+    ) {
+        for mint in &mints {
+            self.ath_tracker.forget(mint).await;
+            self.alert_engine.forget(mint);
+            self.candle_builder.forget(mint);
+        }
+        debug!("token_price_tracking_stopped::count::{}", mints.len());
+    }
+
+    async fn process_pool_swap(
+        &mut self,
+        state: PoolSwapState,
+    ) -> Result<()> {
+        let sol_usd = self.sol_price.latest().await;
+        let price = TokenPrice::from_pool_swap(&state).with_usd(sol_usd.as_ref());
+        self.record_price(&price, state.quote_volume, &state.trade_id).await
+    }
+
+    // Bonding curve and pool prices share one history per mint, so charts and ATHs run across migration
+    async fn record_price(
+        &mut self,
+        price: &TokenPrice,
+        volume: u64,
+        trade_id: &str,
+    ) -> Result<()> {
+        let mint = price.mint.to_string();
+        self.db
+            .backend
+            .time_series
+            .add_token_price(&mint, price.price, price.timestamp, trade_id)
+            .await?;
+        if volume > 0 {
+            self.db
+                .backend
+                .time_series
+                .add_token_volume(&mint, volume, price.timestamp, trade_id)
+                .await?;
+        }
+        self.db.backend.kv.set(&TokenPrice::redis_key(&price.mint), price).await?;
+
+        if let Some(ath) = self.ath_tracker.observe(price).await? {
+            debug!("token_ath_updated::mint::{}::price_sol::{}", price.mint, ath.price_sol);
+        }
+        self.candle_builder.observe(&price.mint, price.timestamp);
+        self.alert_engine.evaluate(price, self.ath_tracker.current(&price.mint)).await;
+
+        debug!(
+            "token_price_updated::mint::{}::source::{:?}::price_sol::{}::market_cap_sol::{}::liquidity_sol::{:?\
+             }::market_cap_usd::{:?}",
+            price.mint, price.source, price.price_sol, price.market_cap_sol, price.liquidity_sol, price.market_cap_usd
+        );
+        Ok(())
+    }
+}
+
+async fn run_token_price_handler(mut token_price_handler: TokenPriceHandler) {
+    let mut ath_flush = tokio::time::interval(token_price_handler.ath_flush_interval);
+    let mut candle_flush = tokio::time::interval(token_price_handler.candle_flush_interval);
+    let mut alert_refresh = tokio::time::interval(token_price_handler.alert_refresh_interval);
+    loop {
+        tokio::select! {
+            Some(msg) = token_price_handler.receiver.recv() => {
+                match msg {
+                    PriceHandler::BondingCurveUpdate { state } => {
+                        let mint = state.mint;
+                        if let Err(e) = token_price_handler.process_bonding_curve_update(state).await {
+                            error!("process_bonding_curve_update_failed::mint::{}::error::{}", mint, e);
+                        }
+                    },
+                    PriceHandler::CurveTrade { state } => {
+                        let mint = state.mint;
+                        if let Err(e) = token_price_handler.process_curve_trade(state).await {
+                            error!("process_curve_trade_failed::mint::{}::error::{}", mint, e);
+                        }
+                    },
+                    PriceHandler::PoolSwap { state } => {
+                        let mint = state.mint;
+                        if let Err(e) = token_price_handler.process_pool_swap(state).await {
+                            error!("process_pool_swap_failed::mint::{}::error::{}", mint, e);
+                        }
+                    },
+                    PriceHandler::StopTracking { mints } => {
+                        token_price_handler.stop_tracking(mints).await;
+                    },
+                }
+            },
+            _ = ath_flush.tick() => {
+                token_price_handler.ath_tracker.flush().await;
+            },
+            _ = candle_flush.tick() => {
+                token_price_handler.candle_builder.flush().await;
+            },
+            _ = alert_refresh.tick() => {
+                token_price_handler.alert_engine.refresh().await;
+            },
+            _ = token_price_handler.shutdown.wait_for_shutdown() => {
+                debug!("token_price_handler::received_shutdown_signal");
+                break;
+            },
+            else => {
+                debug!("token_price_handler::all_senders_dropped");
+                break;
+            }
+        }
+    }
+
+    // Don't lose highs and trades seen since the last flush
+    token_price_handler.ath_tracker.flush().await;
+    token_price_handler.candle_builder.flush().await;
+    info!("token_price_handler::shutdown");
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenPriceHandlerOperator {
+    sender: mpsc::Sender<PriceHandler>,
+    shutdown: ShutdownSignal,
+}
+
+impl TokenPriceHandlerOperator {
+    pub fn new(
+        db: Arc<StorageEngine>,
+        shutdown: ShutdownSignal,
+        sol_price: Arc<SolPriceOracle>,
+        discord: DiscordWebhookHandlerOperator,
+        price_config: &PriceConfig,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(10000);
+
+        let receiver = TokenPriceHandler::new(receiver, db, shutdown.clone(), sol_price, discord, price_config);
+
+        // Spawn the actor
+        tokio::spawn(run_token_price_handler(receiver));
+
+        Self {
+            sender,
+            shutdown,
+        }
+    }
+
+    pub async fn update_bonding_curve(
+        &self,
+        state: BondingCurveState,
+    ) -> Result<()> {
+        // Use try_send for backpressure handling
+        match self.sender.try_send(PriceHandler::BondingCurveUpdate {
+            state,
+        }) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("send_price_handler_failed: {}", e);
+                Err(err_with_loc!(HandlerError::SendPriceHandlerError(format!("send_price_handler_failed:{}", e))))
+            },
+        }
+    }
+
+    pub async fn update_curve_trade(
+        &self,
+        state: CurveTradeState,
+    ) -> Result<()> {
+        match self.sender.try_send(PriceHandler::CurveTrade {
+            state,
+        }) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("send_price_handler_failed: {}", e);
+                Err(err_with_loc!(HandlerError::SendPriceHandlerError(format!("send_price_handler_failed:{}", e))))
+            },
+        }
+    }
+
+    pub async fn update_pool_swap(
+        &self,
+        state: PoolSwapState,
+    ) -> Result<()> {
+        match self.sender.try_send(PriceHandler::PoolSwap {
+            state,
+        }) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("send_price_handler_failed: {}", e);
+                Err(err_with_loc!(HandlerError::SendPriceHandlerError(format!("send_price_handler_failed:{}", e))))
+            },
+        }
+    }
+
+    pub async fn stop_tracking(
+        &self,
+        mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        match self.sender.try_send(PriceHandler::StopTracking {
+            mints,
+        }) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("send_price_handler_failed: {}", e);
+                Err(err_with_loc!(HandlerError::SendPriceHandlerError(format!("send_price_handler_failed:{}", e))))
+            },
+        }
+    }
+
+    pub fn shutdown(&self) {
+        self.shutdown.shutdown();
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use solana_client::rpc_client::GetConfirmedSignaturesForAddress2Config;
+use solana_client::rpc_config::RpcTransactionConfig;
+use solana_commitment_config::CommitmentConfig;
+use solana_pubkey::Pubkey;
+use solana_signature::Signature;
+use solana_transaction_status::UiTransactionEncoding;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+use crate::config::RpcConfig;
+use crate::config::RpcProviderRole;
+use crate::constants::SYSTEM_PROGRAM_ID;
+use crate::model::bundle::BundleAnalysis;
+use crate::model::bundle::LaunchWindow;
+use crate::model::creator::graph::CreatorConnectionGraph;
+use crate::storage::StorageEngine;
+
+// Transactions before a buyer's first buy that are searched for a transfer from the creator
+const CREATOR_FUNDING_LOOKBACK: usize = 5;
+
+// Buyers whose funding is looked up at the same time
+const CREATOR_FUNDING_CONCURRENCY: usize = 4;
+
+// Discriminant of the system program `Transfer` instruction
+const SYSTEM_TRANSFER_DISCRIMINANT: u32 = 2;
+
+/// Checks a launch's early buys once both its window (Raqib) and the creator's funding graph
+/// (Baseer) are stored. Whichever side stores last runs the analysis, storing it is idempotent.
+pub struct BundleAnalyzer {
+    db: Arc<StorageEngine>,
+    rpc_config: Arc<RpcConfig>,
+}
+
+impl BundleAnalyzer {
+    pub fn new(
+        db: Arc<StorageEngine>,
+        rpc_config: Arc<RpcConfig>,
+    ) -> Self {
+        Self {
+            db,
+            rpc_config,
+        }
+    }
+
+    /// Called by Baseer once the funding graph is stored, `None` while Raqib has no window for the mint
+    pub async fn analyze_with_graph(
+        &self,
+        mint: &Pubkey,
+        connection_graph: &CreatorConnectionGraph,
+        analyzed_at: u64,
+    ) -> Option<BundleAnalysis> {
+        let window = match self.db.backend.launches.get_launch_window(mint).await {
+            Ok(Some(window)) => window,
+            Ok(None) => {
+                debug!("launch_window_not_stored_yet::mint::{}", mint);
+                return None;
+            },
+            Err(e) => {
+                error!("get_launch_window_failed::mint::{}::error::{}", mint, e);
+                return None;
+            },
+        };
+        Some(self.analyze(&window, connection_graph, analyzed_at).await)
+    }
+
+    /// Called by Raqib once the window is stored, the funding graph is empty until Baseer stores it
+    pub async fn analyze_with_window(
+        &self,
+        window: &LaunchWindow,
+        analyzed_at: u64,
+    ) -> BundleAnalysis {
+        let connection_graph = match self.db.backend.graphs.load_connection_graph(&[window.mint]).await {
+            Ok(graph) => graph,
+            Err(e) => {
+                error!("load_connection_graph_failed::mint::{}::error::{}", window.mint, e);
+                CreatorConnectionGraph::new()
+            },
+        };
+        self.analyze(window, &connection_graph, analyzed_at).await
+    }
+
+    async fn analyze(
+        &self,
+        window: &LaunchWindow,
+        connection_graph: &CreatorConnectionGraph,
+        analyzed_at: u64,
+    ) -> BundleAnalysis {
+        let creator_funded = self.creator_funded_buyers(window).await;
+        let analysis = BundleAnalysis::analyze(window, connection_graph, &creator_funded);
+        if let Err(e) = self.db.backend.launches.store_bundle_analysis(&analysis, analyzed_at).await {
+            error!("store_bundle_analysis_failed::mint::{}::error::{}", window.mint, e);
+        }
+        if analysis.is_bundled() {
+            info!(
+                "launch_bundle_suspected::mint::{}::bundlers::{}::supply_share::{:.4}",
+                window.mint,
+                analysis.bundlers.len(),
+                analysis.bundled_supply_share
+            );
+        }
+        analysis
+    }
+
+    // Buyers that received SOL from the creator in their buy or in the few transactions before it
+    async fn creator_funded_buyers(
+        &self,
+        window: &LaunchWindow,
+    ) -> HashSet<Pubkey> {
+        let mut seen = HashSet::new();
+        let first_buys: Vec<(Pubkey, String)> = window
+            .buys
+            .iter()
+            .filter(|buy| buy.buyer != window.creator && seen.insert(buy.buyer))
+            .map(|buy| (buy.buyer, buy.signature.clone()))
+            .collect();
+
+        futures::stream::iter(first_buys)
+            .map(|(buyer, signature)| async move {
+                self.is_funded_by(window.creator, buyer, &signature).await.then_some(buyer)
+            })
+            .buffer_unordered(CREATOR_FUNDING_CONCURRENCY)
+            .filter_map(|buyer| async move { buyer })
+            .collect()
+            .await
+    }
+
+    async fn is_funded_by(
+        &self,
+        creator: Pubkey,
+        buyer: Pubkey,
+        buy_signature: &str,
+    ) -> bool {
+        let Ok(buy_signature) = Signature::from_str(buy_signature) else {
+            error!("invalid_buy_signature::buyer::{}::signature::{}", buyer, buy_signature);
+            return false;
+        };
+        let commitment = CommitmentConfig::confirmed();
+
+        let Some((client, _)) = self
+            .rpc_config
+            .get_next_client_for_role(&RpcProviderRole::SignatureFetcher, commitment)
+            .await
+        else {
+            return false;
+        };
+        let mut signatures = vec![buy_signature];
+        match client
+            .get_signatures_for_address_with_config(&buyer, GetConfirmedSignaturesForAddress2Config {
+                before: Some(buy_signature),
+                until: None,
+                limit: Some(CREATOR_FUNDING_LOOKBACK),
+                commitment: Some(commitment),
+            })
+            .await
+        {
+            Ok(prior) => signatures.extend(prior.iter().filter_map(|s| Signature::from_str(&s.signature).ok())),
+            Err(e) => error!("get_buyer_signatures_failed::buyer::{}::error::{}", buyer, e),
+        }
+
+        for signature in signatures {
+            let Some((client, _)) = self
+                .rpc_config
+                .get_next_client_for_role(&RpcProviderRole::TransactionFetcher, commitment)
+                .await
+            else {
+                return false;
+            };
+            let transaction = match client
+                .get_transaction_with_config(&signature, RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(commitment),
+                    max_supported_transaction_version: Some(0),
+                })
+                .await
+            {
+                Ok(transaction) => transaction,
+                Err(e) => {
+                    error!("get_buyer_transaction_failed::signature::{}::error::{}", signature, e);
+                    continue;
+                },
+            };
+            let Some(decoded) = transaction.transaction.transaction.decode() else {
+                continue;
+            };
+            let keys = decoded.message.static_account_keys();
+            let funded = decoded.message.instructions().iter().any(|ix| {
+                keys.get(ix.program_id_index as usize) == Some(&SYSTEM_PROGRAM_ID)
+                    && ix.data.get(..4) == Some(&SYSTEM_TRANSFER_DISCRIMINANT.to_le_bytes()[..])
+                    && ix.accounts.first().and_then(|i| keys.get(*i as usize)) == Some(&creator)
+                    && ix.accounts.get(1).and_then(|i| keys.get(*i as usize)) == Some(&buyer)
+            });
+            if funded {
+                debug!("buyer_funded_by_creator::buyer::{}::signature::{}", buyer, signature);
+                return true;
+            }
+        }
+        false
+    }
+}
@@ -15,21 +15,31 @@ use crate::config::RpcConfig;
 use crate::err_with_loc;
 use crate::error::HandlerError;
 use crate::handler::shutdown::ShutdownSignal;
+use crate::handler::token::bundle::BundleAnalyzer;
 use crate::model::cex::Cex;
+use crate::model::creator::crawl::CrawlGuard;
 use crate::model::creator::graph::SharedCreatorConnectionGraph;
 use crate::model::creator::metadata::CreatorMetadata;
 use crate::model::dev::Dev;
+use crate::model::price::SOL_USD_PRICE_KEY;
+use crate::model::price::SolUsdPrice;
 use crate::pipeline::crawler::creator::make_creator_crawler_pipeline;
 use crate::pipeline::processor::creator::CreatorInstructionProcessor;
 use crate::storage::StorageEngine;
+use crate::storage::backend::EventBusExt;
+use crate::storage::backend::KvCacheExt;
 use crate::storage::redis::model::MaxDepthReachedCache;
 use crate::storage::redis::model::TokenAnalyzedCache;
+use crate::storage::redis::queue::AccountLease;
 
 pub struct CreatorHandlerMetadata {
     receiver: mpsc::Receiver<CreatorHandler>,
     db: Arc<StorageEngine>,
     shutdown: ShutdownSignal,
     rpc_config: Arc<RpcConfig>,
+    bundle_analyzer: BundleAnalyzer,
+    // Cached SOL/USD prices older than this are left out of the reported funding
+    sol_usd_max_age_secs: i64,
 }
 
 impl CreatorHandlerMetadata {
@@ -38,12 +48,16 @@ impl CreatorHandlerMetadata {
         db: Arc<StorageEngine>,
         shutdown: ShutdownSignal,
         rpc_config: Arc<RpcConfig>,
+        sol_usd_max_age_secs: i64,
     ) -> Self {
+        let bundle_analyzer = BundleAnalyzer::new(db.clone(), rpc_config.clone());
         Self {
             receiver,
             db,
             shutdown,
             rpc_config,
+            bundle_analyzer,
+            sol_usd_max_age_secs,
         }
     }
 
@@ -72,24 +86,41 @@ impl CreatorHandlerMetadata {
         // }
         // Update in PostgreSQL
         self.db
-            .postgres
-            .db
+            .backend
+            .tokens
             .update_token_cex_sources(&mint, &cex_sources, updated_at)
             .await?;
 
-        // Record CEX activity for analytics
-        if let Err(e) = self
-            .db
-            .postgres
+        // Record CEX activity for analytics. A retried mint is already recorded and must not be counted twice
+        let newly_recorded = match self
             .db
+            .backend
+            .tokens
             .record_cex_activity(&cex.name.to_string(), &cex.address, &mint)
             .await
         {
-            error!("record_cex_activity_postgres_failed::{}::mint::{}::error::{}", cex.name, mint, e);
+            Ok(newly_recorded) => newly_recorded,
+            Err(e) => {
+                error!("record_cex_activity_postgres_failed::{}::mint::{}::error::{}", cex.name, mint, e);
+                false
+            },
+        };
+
+        // Record the launch into the hourly CEX activity time series
+        if newly_recorded {
+            if let Err(e) = self
+                .db
+                .backend
+                .time_series
+                .record_cex_launch(&cex.address.to_string(), created_at as i64)
+                .await
+            {
+                error!("record_cex_launch_time_series_failed::{}::mint::{}::error::{}", cex.name, mint, e);
+            }
         }
 
         // Store the connection graph in pgrouting
-        if let Err(e) = self.db.postgres.graph.store_connection_graph(&mint, &connection_graph).await {
+        if let Err(e) = self.db.backend.graphs.store_connection_graph(&mint, &connection_graph).await {
             error!("store_connection_graph_pgrouting_failed::{}::mint::{}::error::{}", cex.name, mint, e);
             // Continue despite the error
         }
@@ -97,19 +128,19 @@ impl CreatorHandlerMetadata {
         // Update Redis cache
         let token_key = mint.to_string();
         if let Ok(Some(mut token_metadata)) =
-            self.db.redis.kv.get::<crate::model::token::TokenMetadata>(&token_key).await
+            self.db.backend.kv.get::<crate::model::token::TokenMetadata>(&token_key).await
         {
             token_metadata.cex_sources = Some(cex_sources.clone());
             token_metadata.cex_updated_at = Some(updated_at);
 
-            if let Err(e) = self.db.redis.kv.set(&token_key, &token_metadata).await {
+            if let Err(e) = self.db.backend.kv.set(&token_key, &token_metadata).await {
                 error!("update_token_redis_failed::{}::mint::{}::error::{}", cex.name, mint, e);
             }
         }
 
         // Store connection graph in Redis
         let graph_key = format!("developer_connection_graph:{}", mint);
-        if let Err(e) = self.db.redis.kv.set_graph(&graph_key, &connection_graph).await {
+        if let Err(e) = self.db.backend.kv.set(&graph_key, &connection_graph).await {
             error!("store_connection_graph_redis_failed::{}::mint::{}::error::{}", cex.name, mint, e);
         }
 
@@ -122,10 +153,30 @@ impl CreatorHandlerMetadata {
           "updated_at": updated_at
         });
 
-        if let Err(e) = self.db.redis.kv.set(&cex_key, &cex_data).await {
+        if let Err(e) = self.db.backend.kv.set(&cex_key, &cex_data).await {
             error!("store_cex_data_redis_failed::{}::mint::{}::error::{}", cex.name, mint, e);
         }
 
+        // Report the funding in USD alongside SOL when Siraaj has a fresh SOL/USD price
+        let funding_amount_sol: f64 = connection_graph
+            .get_edges()
+            .iter()
+            .filter(|edge| edge.to == dev)
+            .map(|edge| edge.amount)
+            .sum();
+        let sol_usd = match self.db.backend.kv.get::<SolUsdPrice>(SOL_USD_PRICE_KEY).await {
+            Ok(price) => price.filter(|price| price.is_fresh(self.sol_usd_max_age_secs)),
+            Err(e) => {
+                error!("get_sol_usd_price_failed::mint::{}::error::{}", mint, e);
+                None
+            },
+        };
+
+        let bundle = self
+            .bundle_analyzer
+            .analyze_with_graph(&mint, &connection_graph, updated_at)
+            .await;
+
         // Publish event
         let event_data = TokenAnalyzedCache {
             mint: mint.to_string(),
@@ -140,12 +191,16 @@ impl CreatorHandlerMetadata {
             updated_at,
             node_count: connection_graph.get_node_count(),
             edge_count: connection_graph.get_edge_count(),
+            funding_amount_sol,
+            funding_amount_usd: sol_usd.as_ref().map(|price| price.to_usd(funding_amount_sol)),
+            sol_usd_price: sol_usd.as_ref().map(|price| price.price),
+            bundle,
             graph: connection_graph,
         };
 
         // debug!("publishing_token_cex_updated::mint::{}::cex::{}", mint, cex.name);
 
-        if let Err(e) = self.db.redis.queue.publish("token_cex_updated", &event_data).await {
+        if let Err(e) = self.db.backend.events.publish("token_cex_updated", &event_data).await {
             error!("publish_token_cex_updated_event_failed::{}::mint::{}::error::{}", cex.name, mint, e);
         }
 
@@ -160,6 +215,7 @@ impl CreatorHandlerMetadata {
         child_token: CancellationToken,
         creator_analyzer_config: Arc<CreatorAnalyzerConfig>,
         depth: usize,
+        crawl: CrawlGuard,
     ) -> Result<()> {
         let db_engine = self.db.clone();
         let shutdown_signal = self.shutdown.clone();
@@ -171,6 +227,7 @@ impl CreatorHandlerMetadata {
             operator_receiver,
             operator_sender.clone(),
             self.rpc_config.clone(),
+            self.sol_usd_max_age_secs,
         ));
 
         let max_depth = creator_metadata.max_depth;
@@ -183,6 +240,7 @@ impl CreatorHandlerMetadata {
             creator_analyzer_config.clone(),
             self.rpc_config.clone(),
             Arc::new(RwLock::new(current_depth)),
+            crawl,
         );
         let operator_sender = operator_sender.clone();
 
@@ -215,6 +273,7 @@ impl CreatorHandlerMetadata {
         child_token: CancellationToken,
         creator_analyzer_config: Arc<CreatorAnalyzerConfig>,
         depth: usize,
+        lease: AccountLease,
     ) -> Result<()> {
         let db_engine = self.db.clone();
         let shutdown_signal = self.shutdown.clone();
@@ -226,11 +285,23 @@ impl CreatorHandlerMetadata {
             operator_receiver,
             operator_sender.clone(),
             self.rpc_config.clone(),
+            self.sol_usd_max_age_secs,
         ));
 
         let max_depth = creator_metadata.max_depth;
         let current_depth = depth + 1;
 
+        // The lease is held, and kept alive by heartbeats, until every BFS level started from this
+        // account is done. Failures were requeued as new entries, either way the lease is done then
+        let (crawl, crawl_finished) = CrawlGuard::new();
+        let work_queue = db_engine.backend.work_queue.clone();
+        tokio::spawn(async move {
+            crawl_finished.wait().await;
+            if let Err(e) = work_queue.release_account_lease(&lease).await {
+                error!("failed_to_release_account_lease::lease::{}::error::{}", lease.id, e);
+            }
+        });
+
         let processor = CreatorInstructionProcessor::new(
             creator_handler.clone(),
             creator_metadata.clone(),
@@ -238,6 +309,7 @@ impl CreatorHandlerMetadata {
             creator_analyzer_config.clone(),
             self.rpc_config.clone(),
             Arc::new(RwLock::new(current_depth)),
+            crawl,
         );
         let operator_sender = operator_sender.clone();
         tokio::spawn(async move {
@@ -282,17 +354,21 @@ impl CreatorHandlerMetadata {
             .as_secs();
 
         // Store the connection graph in pgrouting
-        if let Err(e) = self.db.postgres.graph.store_connection_graph(&mint, &connection_graph).await {
+        if let Err(e) = self.db.backend.graphs.store_connection_graph(&mint, &connection_graph).await {
             error!("store_connection_graph_pgrouting_failed::mint::{}::error::{}", mint, e);
         }
 
         // Store connection graph in Redis
         let graph_key = format!("developer_connection_graph:{}", mint);
-        if let Err(e) = self.db.redis.kv.set_graph(&graph_key, &connection_graph).await {
+        if let Err(e) = self.db.backend.kv.set(&graph_key, &connection_graph).await {
             error!("store_connection_graph_redis_failed::mint::{}::error::{}", mint, e);
         }
 
         let dev_name = Dev::get_dev_name(creator_metadata.original_creator.clone()).unwrap_or_default();
+        let bundle = self
+            .bundle_analyzer
+            .analyze_with_graph(&mint, &connection_graph, updated_at)
+            .await;
         // Publish event
         let event_data = MaxDepthReachedCache {
             mint: mint.to_string(),
@@ -305,10 +381,11 @@ impl CreatorHandlerMetadata {
             updated_at,
             node_count: connection_graph.get_node_count(),
             edge_count: connection_graph.get_edge_count(),
+            bundle,
             graph: connection_graph,
         };
 
-        if let Err(e) = self.db.redis.queue.publish("max_depth_reached", &event_data).await {
+        if let Err(e) = self.db.backend.events.publish("max_depth_reached", &event_data).await {
             error!("publish_max_depth_reached_event_failed::mint::{}::error::{}", mint, e);
         }
 
@@ -321,39 +398,43 @@ async fn run_creator_handler_metadata(mut creator_handler_metadata: CreatorHandl
         tokio::select! {
             Some(msg) = creator_handler_metadata.receiver.recv() => {
                 match msg {
-                    CreatorHandler::ProcessBfsLevel { creator_metadata, sender, child_token, creator_analyzer_config, depth } => {
-                        if let Err(e) = creator_handler_metadata.process_bfs_level(creator_metadata.clone(), sender, child_token, creator_analyzer_config, depth).await {
+                    CreatorHandler::ProcessBfsLevel { creator_metadata, sender, child_token, creator_analyzer_config, depth, crawl } => {
+                        if let Err(e) = creator_handler_metadata.process_bfs_level(creator_metadata.clone(), sender, child_token, creator_analyzer_config, depth, crawl).await {
                             error!("failed_to_process_sender::error::{}", e);
 
                             // Add to failed queue when process_bfs_level fails
                             let mut failed_metadata = (*creator_metadata).clone();
                             failed_metadata.mark_as_bfs_failed().await;
-                            if let Err(e) = creator_handler_metadata.db.redis.queue.add_failed_account(&failed_metadata).await {
+                            if let Err(e) = creator_handler_metadata.db.backend.work_queue.add_failed_account(&failed_metadata).await {
                                 error!("failed_to_add_to_failed_queue_after_bfs_failure::account::{}::error::{}",
                                     failed_metadata.get_analyzed_account().await, e);
                             }
                         }
                     },
-                    CreatorHandler::CexConnection { cex, cex_connection, mint, name, uri, dev, created_at, bonding_curve } => {
+                    // The crawl guard is held until the connection is stored
+                    CreatorHandler::CexConnection { cex, cex_connection, mint, name, uri, dev, created_at, bonding_curve, crawl: _crawl } => {
                         if let Err(e) = creator_handler_metadata.process_cex_connection(
                             cex.clone(), cex_connection, mint, name, uri, dev, created_at, bonding_curve
                         ).await {
                             error!("cex_failed::{}::mint::{}::error::{}", cex.clone().name, mint, e);
                         }
                     },
-                    CreatorHandler::ProcessRecoveredAccount { creator_metadata, child_token, creator_analyzer_config, depth } => {
+                    CreatorHandler::ProcessRecoveredAccount { creator_metadata, child_token, creator_analyzer_config, depth, lease } => {
                         if let Err(e) = creator_handler_metadata.process_recovered_account(
-                            creator_metadata.clone(), child_token, creator_analyzer_config, depth
+                            creator_metadata.clone(), child_token, creator_analyzer_config, depth, lease.clone()
                         ).await {
                             error!("failed_to_process_recovered_account::error::{}", e);
 
                             // Add back to failed queue when recovery fails
                             let mut failed_metadata = (*creator_metadata).clone();
                             failed_metadata.mark_as_failed().await;
-                            if let Err(e) = creator_handler_metadata.db.redis.queue.add_failed_account(&failed_metadata).await {
+                            if let Err(e) = creator_handler_metadata.db.backend.work_queue.add_failed_account(&failed_metadata).await {
                                 error!("failed_to_requeue_failed_account_after_recovery_failure::account::{}::error::{}",
                                     failed_metadata.get_analyzed_account().await, e);
                             }
+                            if let Err(e) = creator_handler_metadata.db.backend.work_queue.release_account_lease(&lease).await {
+                                error!("failed_to_release_account_lease::lease::{}::error::{}", lease.id, e);
+                            }
                         }
                     },
                     CreatorHandler::MaxDepthReached { creator_metadata, child_token } => {
@@ -389,8 +470,15 @@ impl CreatorHandlerOperator {
         receiver: mpsc::Receiver<CreatorHandler>,
         sender: mpsc::Sender<CreatorHandler>,
         rpc_config: Arc<RpcConfig>,
+        sol_usd_max_age_secs: i64,
     ) -> Self {
-        let metadata = CreatorHandlerMetadata::new(receiver, db.clone(), shutdown.clone(), rpc_config.clone());
+        let metadata = CreatorHandlerMetadata::new(
+            receiver,
+            db.clone(),
+            shutdown.clone(),
+            rpc_config.clone(),
+            sol_usd_max_age_secs,
+        );
 
         // Spawn the actor
         tokio::spawn(run_creator_handler_metadata(metadata));
@@ -412,6 +500,7 @@ impl CreatorHandlerOperator {
         child_token: CancellationToken,
         creator_analyzer_config: Arc<CreatorAnalyzerConfig>,
         depth: usize,
+        crawl: CrawlGuard,
     ) -> Result<()> {
         let wallet_connection = creator_metadata.wallet_connection.clone();
 
@@ -432,6 +521,7 @@ impl CreatorHandlerOperator {
                 dev: creator_metadata.original_creator,
                 created_at: creator_metadata.created_at,
                 bonding_curve: creator_metadata.bonding_curve.unwrap_or_default(),
+                crawl,
             }) {
                 error!("failed_to_send_cex_connection_request::sender::{}::receiver::{}::amount::{}::timestamp::{}::error::{}", sender, receiver, amount, timestamp, e);
                 return Err(err_with_loc!(HandlerError::SendCreatorHandlerError(format!(
@@ -446,7 +536,7 @@ impl CreatorHandlerOperator {
                 "name": cex.name.to_string(),
                 "address": cex.address.to_string(),
             });
-            if let Err(e) = self.db.redis.kv.set(&address_key, &address_data).await {
+            if let Err(e) = self.db.backend.kv.set(&address_key, &address_data).await {
                 error!("store_address_data_redis_failed::{}::error::{}", receiver, e);
             }
 
@@ -475,6 +565,7 @@ impl CreatorHandlerOperator {
             child_token,
             creator_analyzer_config,
             depth,
+            crawl,
         }) {
             error!(
                 "failed_to_send_process_sender_request::sender::{}::receiver::{}::amount::{}::timestamp::{}::error::{}",
@@ -490,17 +581,24 @@ impl CreatorHandlerOperator {
     }
 
     pub async fn get_pending_account_counts(&self) -> Result<(usize, usize)> {
-        self.db.redis.queue.get_pending_account_counts().await.map_err(|e| {
+        self.db.backend.work_queue.get_pending_account_counts().await.map_err(|e| {
             error!("failed_to_get_pending_account_counts: {}", e);
             err_with_loc!(HandlerError::RedisQueryError(format!("Failed to get pending account counts: {}", e)))
         })
     }
 
+    pub async fn get_in_flight_account_count(&self) -> Result<usize> {
+        self.db.backend.work_queue.get_in_flight_account_count().await.map_err(|e| {
+            error!("failed_to_get_in_flight_account_count: {}", e);
+            err_with_loc!(HandlerError::RedisQueryError(format!("Failed to get in-flight account count: {}", e)))
+        })
+    }
+
     pub async fn add_failed_account(
         &self,
         account: &CreatorMetadata,
     ) -> Result<()> {
-        self.db.redis.queue.add_failed_account(account).await.map_err(|e| {
+        self.db.backend.work_queue.add_failed_account(account).await.map_err(|e| {
             error!("failed_to_add_failed_account: {}", e);
             err_with_loc!(HandlerError::RedisQueryError(format!("Failed to add failed account: {}", e)))
         })
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use solana_pubkey::Pubkey;
+use tokio::sync::mpsc;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+use super::DevDumpHandler;
+use crate::Result;
+use crate::config::LaunchConfig;
+use crate::err_with_loc;
+use crate::error::HandlerError;
+use crate::handler::shutdown::ShutdownSignal;
+use crate::model::dump::LaunchTrade;
+use crate::model::dump::LaunchWatch;
+use crate::storage::StorageEngine;
+use crate::storage::backend::EventBusExt;
+use crate::storage::postgres::model::CreatorReputationEvent;
+use crate::storage::redis::model::DevDumpDetectedCache;
+
+/// Outcome recorded on the creator's reputation history when its cluster dumps
+pub const DEV_DUMP_OUTCOME: &str = "dev_dump";
+
+struct DevDumpWatcher {
+    receiver: mpsc::Receiver<DevDumpHandler>,
+    db: Arc<StorageEngine>,
+    shutdown: ShutdownSignal,
+    window_secs: i64,
+    threshold_pct: f64,
+    cluster_refresh_interval: Duration,
+    watches: HashMap<Pubkey, LaunchWatch>,
+    // Cluster lookups run off the actor loop and report back here, one at a time
+    cluster_sender: mpsc::Sender<HashMap<Pubkey, Vec<Pubkey>>>,
+    cluster_receiver: mpsc::Receiver<HashMap<Pubkey, Vec<Pubkey>>>,
+    cluster_refresh_running: bool,
+}
+
+impl DevDumpWatcher {
+    fn new(
+        receiver: mpsc::Receiver<DevDumpHandler>,
+        db: Arc<StorageEngine>,
+        shutdown: ShutdownSignal,
+        launch_config: &LaunchConfig,
+    ) -> Self {
+        let (cluster_sender, cluster_receiver) = mpsc::channel(1);
+        Self {
+            receiver,
+            db,
+            shutdown,
+            window_secs: launch_config.dev_dump_window_secs as i64,
+            threshold_pct: launch_config.dev_dump_threshold_pct,
+            cluster_refresh_interval: Duration::from_secs(launch_config.dev_dump_cluster_refresh_secs.max(1)),
+            watches: HashMap::new(),
+            cluster_sender,
+            cluster_receiver,
+            cluster_refresh_running: false,
+        }
+    }
+
+    async fn process_trade(
+        &mut self,
+        trade: LaunchTrade,
+    ) -> Result<()> {
+        let window_secs = self.window_secs;
+        let Some(watch) = self.watches.get_mut(&trade.mint) else {
+            return Ok(());
+        };
+        if trade.timestamp - watch.launched_at > window_secs {
+            return Ok(());
+        }
+
+        watch.apply_trade(&trade);
+        if watch.detected || trade.is_buy || !watch.cluster.contains(&trade.wallet) {
+            return Ok(());
+        }
+
+        let sold_pct = watch.cluster_sold_pct();
+        if sold_pct < self.threshold_pct {
+            return Ok(());
+        }
+        watch.detected = true;
+        let watch = watch.clone();
+
+        let (sellers, totals) = watch.cluster_totals();
+        let price_impact_pct = watch.price_impact_pct();
+        let event = DevDumpDetectedCache {
+            mint: watch.mint.to_string(),
+            creator: watch.creator.to_string(),
+            sellers: sellers.iter().map(|wallet| wallet.to_string()).collect(),
+            bought_amount: totals.bought,
+            sold_amount: totals.sold,
+            sold_pct,
+            sol_received: totals.sol_received,
+            price_before: watch.price_before_dump.unwrap_or_default(),
+            price_after: watch.last_price,
+            price_impact_pct,
+            launched_at: watch.launched_at,
+            detected_at: trade.timestamp,
+        };
+        info!(
+            "dev_dump_detected::mint::{}::creator::{}::sold_pct::{:.2}::price_impact_pct::{:?}",
+            watch.mint, watch.creator, sold_pct, price_impact_pct
+        );
+        if let Err(e) = self.db.backend.events.broadcast("dev_dump_detected", &event).await {
+            error!("publish_dev_dump_detected_failed::mint::{}::error::{}", watch.mint, e);
+        }
+
+        self.db
+            .backend
+            .launches
+            .record_creator_outcome(&CreatorReputationEvent {
+                creator: watch.creator,
+                mint: watch.mint,
+                outcome: String::from(DEV_DUMP_OUTCOME),
+                wallets: sellers,
+                token_amount: totals.sold,
+                sol_amount: totals.sol_received,
+                share_pct: Some(sold_pct),
+                price_impact_pct,
+                occurred_at: trade.timestamp,
+            })
+            .await
+    }
+
+    // Drop expired launches and look up wallets Baseer has since clustered with the creator, the
+    // lookup is spawned so trades keep flowing while it runs
+    fn refresh_watches(&mut self) {
+        let now = chrono::Utc::now().timestamp();
+        let window_secs = self.window_secs;
+        self.watches.retain(|_, watch| now - watch.launched_at <= window_secs);
+        debug!("dev_dump_watches::count::{}", self.watches.len());
+
+        if self.cluster_refresh_running {
+            return;
+        }
+        let mints: Vec<Pubkey> = self
+            .watches
+            .iter()
+            .filter(|(_, watch)| !watch.detected)
+            .map(|(mint, _)| *mint)
+            .collect();
+        if mints.is_empty() {
+            return;
+        }
+
+        self.cluster_refresh_running = true;
+        let db = self.db.clone();
+        let cluster_sender = self.cluster_sender.clone();
+        tokio::spawn(async move {
+            let clusters = match db.backend.launches.get_cluster_wallets(&mints).await {
+                Ok(clusters) => clusters,
+                Err(e) => {
+                    error!("get_cluster_wallets_failed::mints::{}::error::{}", mints.len(), e);
+                    HashMap::new()
+                },
+            };
+            let _ = cluster_sender.send(clusters).await;
+        });
+    }
+
+    fn apply_clusters(
+        &mut self,
+        clusters: HashMap<Pubkey, Vec<Pubkey>>,
+    ) {
+        self.cluster_refresh_running = false;
+        for (mint, wallets) in clusters {
+            if let Some(watch) = self.watches.get_mut(&mint) {
+                watch.cluster.extend(wallets);
+            }
+        }
+    }
+}
+
+async fn run_dev_dump_watcher(mut dev_dump_watcher: DevDumpWatcher) {
+    let mut cluster_refresh = tokio::time::interval(dev_dump_watcher.cluster_refresh_interval);
+    loop {
+        tokio::select! {
+            Some(msg) = dev_dump_watcher.receiver.recv() => {
+                match msg {
+                    DevDumpHandler::WatchLaunch { mint, creator, launched_at } => {
+                        dev_dump_watcher.watches.insert(mint, LaunchWatch::new(mint, creator, launched_at));
+                    },
+                    DevDumpHandler::Trade { trade } => {
+                        let mint = trade.mint;
+                        if let Err(e) = dev_dump_watcher.process_trade(trade).await {
+                            error!("process_launch_trade_failed::mint::{}::error::{}", mint, e);
+                        }
+                    },
+                }
+            },
+            _ = cluster_refresh.tick() => {
+                dev_dump_watcher.refresh_watches();
+            },
+            Some(clusters) = dev_dump_watcher.cluster_receiver.recv() => {
+                dev_dump_watcher.apply_clusters(clusters);
+            },
+            _ = dev_dump_watcher.shutdown.wait_for_shutdown() => {
+                debug!("dev_dump_watcher::received_shutdown_signal");
+                break;
+            },
+            else => {
+                debug!("dev_dump_watcher::all_senders_dropped");
+                break;
+            }
+        }
+    }
+
+    info!("dev_dump_watcher::shutdown");
+}
+
+#[derive(Debug, Clone)]
+pub struct DevDumpWatcherOperator {
+    sender: mpsc::Sender<DevDumpHandler>,
+    shutdown: ShutdownSignal,
+}
+
+impl DevDumpWatcherOperator {
+    pub fn new(
+        db: Arc<StorageEngine>,
+        shutdown: ShutdownSignal,
+        launch_config: &LaunchConfig,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel(10000);
+
+        let receiver = DevDumpWatcher::new(receiver, db, shutdown.clone(), launch_config);
+
+        // Spawn the actor
+        tokio::spawn(run_dev_dump_watcher(receiver));
+
+        Self {
+            sender,
+            shutdown,
+        }
+    }
+
+    fn send(
+        &self,
+        msg: DevDumpHandler,
+    ) -> Result<()> {
+        // Use try_send for backpressure handling
+        self.sender.try_send(msg).map_err(|e| {
+            error!("send_dev_dump_watcher_failed: {}", e);
+            err_with_loc!(HandlerError::SendTokenHandlerError(format!("send_dev_dump_watcher_failed:{}", e)))
+        })
+    }
+
+    pub async fn watch_launch(
+        &self,
+        mint: Pubkey,
+        creator: Pubkey,
+        launched_at: i64,
+    ) -> Result<()> {
+        self.send(DevDumpHandler::WatchLaunch {
+            mint,
+            creator,
+            launched_at,
+        })
+    }
+
+    pub async fn record_trade(
+        &self,
+        trade: LaunchTrade,
+    ) -> Result<()> {
+        self.send(DevDumpHandler::Trade {
+            trade,
+        })
+    }
+
+    pub fn shutdown(&self) {
+        self.shutdown.shutdown();
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use solana_account_decoder::UiAccountEncoding;
+use solana_account_decoder::UiDataSliceConfig;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_client::rpc_config::RpcProgramAccountsConfig;
+use solana_client::rpc_filter::Memcmp;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_commitment_config::CommitmentConfig;
+use solana_pubkey::Pubkey;
+use tracing::debug;
+use tracing::error;
+
+use crate::Result;
+use crate::config::RpcConfig;
+use crate::config::RpcProviderRole;
+use crate::constants::ASSOCIATED_TOKEN_PROGRAM_ID;
+use crate::constants::TOKEN_PROGRAM_ID;
+use crate::err_with_loc;
+use crate::error::HandlerError;
+use crate::model::price::derive_bonding_curve;
+use crate::model::price::derive_pump_swap_pool;
+use crate::storage::StorageBackend;
+use crate::storage::postgres::model::HolderSnapshot;
+use crate::storage::postgres::model::TokenHolder;
+
+/// Size of an spl-token account, token-2022 accounts with extensions are larger
+const TOKEN_ACCOUNT_LEN: u64 = 165;
+const TOP_HOLDERS: usize = 10;
+
+/// Takes holder concentration snapshots of a mint from its largest token accounts
+pub struct HolderSnapshotter {
+    db: StorageBackend,
+    rpc_config: Arc<RpcConfig>,
+}
+
+impl HolderSnapshotter {
+    pub fn new(
+        db: StorageBackend,
+        rpc_config: Arc<RpcConfig>,
+    ) -> Self {
+        Self {
+            db,
+            rpc_config,
+        }
+    }
+
+    /// Take a snapshot and store it
+    pub async fn snapshot_and_store(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<HolderSnapshot> {
+        let snapshot = self.snapshot(mint).await?;
+        self.db.time_series.insert_holder_snapshot(&snapshot).await?;
+        debug!(
+            "holder_snapshot_stored::mint::{}::holders::{}::top10_share::{:.4}::funded_holders::{}",
+            mint,
+            snapshot.holder_count,
+            snapshot.top10_share,
+            snapshot.funded_holders().count()
+        );
+        Ok(snapshot)
+    }
+
+    /// Largest holders, holder count and top-10 share of a mint. The bonding curve, its
+    /// `associated_bonding_curve` and the PumpSwap pool hold unsold supply, not holders, so
+    /// they are left out of every figure except the total supply.
+    pub async fn snapshot(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<HolderSnapshot> {
+        let Some((client, provider)) = self
+            .rpc_config
+            .get_next_client_for_role(&RpcProviderRole::TransactionFetcher, CommitmentConfig::confirmed())
+            .await
+        else {
+            return Err(err_with_loc!(HandlerError::RpcError(String::from("no_rpc_provider_for_holder_snapshot"))));
+        };
+
+        let bonding_curve = derive_bonding_curve(mint);
+        let excluded_owners: HashSet<Pubkey> = HashSet::from([bonding_curve, derive_pump_swap_pool(mint)]);
+
+        let supply = client.get_token_supply(mint).await.map_err(|e| {
+            error!("failed_to_get_token_supply::mint::{}::provider::{}::error::{}", mint, provider, e);
+            err_with_loc!(HandlerError::RpcError(format!("failed_to_get_token_supply::{}::{}", mint, e)))
+        })?;
+        let total_supply = supply.amount.parse::<u64>().map_err(|e| {
+            error!("invalid_token_supply::mint::{}::amount::{}::error::{}", mint, supply.amount, e);
+            err_with_loc!(HandlerError::RpcError(format!("invalid_token_supply::{}::{}::{}", mint, supply.amount, e)))
+        })?;
+
+        let largest = client.get_token_largest_accounts(mint).await.map_err(|e| {
+            error!("failed_to_get_token_largest_accounts::mint::{}::provider::{}::error::{}", mint, provider, e);
+            err_with_loc!(HandlerError::RpcError(format!("failed_to_get_token_largest_accounts::{}::{}", mint, e)))
+        })?;
+        let token_accounts: Vec<Pubkey> = largest
+            .iter()
+            .filter_map(|balance| Pubkey::from_str(&balance.address).ok())
+            .collect();
+
+        // Largest accounts only carry the token account, the owner sits at bytes 32..64 of its data
+        let accounts = client.get_multiple_accounts(&token_accounts).await.map_err(|e| {
+            error!("failed_to_get_largest_token_account_owners::mint::{}::provider::{}::error::{}", mint, provider, e);
+            err_with_loc!(HandlerError::RpcError(format!(
+                "failed_to_get_largest_token_account_owners::{}::{}",
+                mint, e
+            )))
+        })?;
+        let token_program = accounts
+            .iter()
+            .flatten()
+            .map(|account| account.owner)
+            .next()
+            .unwrap_or(TOKEN_PROGRAM_ID);
+        let associated_bonding_curve = Pubkey::find_program_address(
+            &[bonding_curve.as_ref(), token_program.as_ref(), mint.as_ref()],
+            &ASSOCIATED_TOKEN_PROGRAM_ID,
+        )
+        .0;
+
+        let funding_graph = self.db.graphs.load_connection_graph(&[*mint]).await?;
+
+        let mut holders = Vec::with_capacity(token_accounts.len());
+        for (token_account, account) in token_accounts.iter().zip(accounts) {
+            let Some(account) = account else {
+                continue;
+            };
+            let (Some(owner), Some(amount)) = (Self::read_owner(&account.data), Self::read_amount(&account.data))
+            else {
+                continue;
+            };
+            if amount == 0 || *token_account == associated_bonding_curve || excluded_owners.contains(&owner) {
+                continue;
+            }
+            holders.push(TokenHolder {
+                rank: holders.len() as u32 + 1,
+                token_account: *token_account,
+                owner,
+                amount,
+                share: Self::share(amount, total_supply),
+                in_funding_graph: funding_graph.get_node_by_address(owner).is_some(),
+            });
+        }
+
+        let holder_count = self
+            .count_holders(&client, &provider, mint, &token_program, &excluded_owners)
+            .await?;
+        let top10_amount: u64 = holders.iter().take(TOP_HOLDERS).map(|holder| holder.amount).sum();
+
+        Ok(HolderSnapshot {
+            mint: *mint,
+            taken_at: chrono::Utc::now().timestamp(),
+            total_supply,
+            holder_count,
+            top10_amount,
+            top10_share: Self::share(top10_amount, total_supply),
+            holders,
+        })
+    }
+
+    // Non-empty token accounts of the mint, fetching only owner and amount of each
+    async fn count_holders(
+        &self,
+        client: &RpcClient,
+        provider: &str,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+        excluded_owners: &HashSet<Pubkey>,
+    ) -> Result<u64> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, mint.as_ref()))];
+        if *token_program == TOKEN_PROGRAM_ID {
+            filters.push(RpcFilterType::DataSize(TOKEN_ACCOUNT_LEN));
+        }
+
+        let accounts = client
+            .get_program_accounts_with_config(token_program, RpcProgramAccountsConfig {
+                filters: Some(filters),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    data_slice: Some(UiDataSliceConfig {
+                        offset: 32,
+                        length: 40,
+                    }),
+                    commitment: Some(CommitmentConfig::confirmed()),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            })
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_token_holders::mint::{}::provider::{}::error::{}", mint, provider, e);
+                err_with_loc!(HandlerError::RpcError(format!("failed_to_get_token_holders::{}::{}", mint, e)))
+            })?;
+
+        Ok(accounts
+            .iter()
+            .filter(|(_, account)| {
+                let data = &account.data;
+                let owner = data.get(..32).and_then(|bytes| Pubkey::try_from(bytes).ok());
+                let amount = data.get(32..40).and_then(|bytes| bytes.try_into().ok()).map(u64::from_le_bytes);
+                matches!((owner, amount), (Some(owner), Some(amount)) if amount > 0 && !excluded_owners.contains(&owner))
+            })
+            .count() as u64)
+    }
+
+    fn read_owner(data: &[u8]) -> Option<Pubkey> {
+        data.get(32..64).and_then(|bytes| Pubkey::try_from(bytes).ok())
+    }
+
+    fn read_amount(data: &[u8]) -> Option<u64> {
+        data.get(64..72).and_then(|bytes| bytes.try_into().ok()).map(u64::from_le_bytes)
+    }
+
+    fn share(
+        amount: u64,
+        total_supply: u64,
+    ) -> f64 {
+        if total_supply == 0 {
+            return 0.0;
+        }
+        amount as f64 / total_supply as f64
+    }
+}
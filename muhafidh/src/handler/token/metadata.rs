@@ -9,17 +9,23 @@ use tracing::info;
 
 use super::TokenHandler;
 use crate::Result;
+use crate::config::RpcConfig;
 use crate::err_with_loc;
 use crate::error::HandlerError;
 use crate::handler::shutdown::ShutdownSignal;
+use crate::handler::token::bundle::BundleAnalyzer;
+use crate::model::bundle::LaunchWindow;
 use crate::model::platform::Platform;
 use crate::model::token::TokenMetadata;
 use crate::storage::StorageEngine;
+use crate::storage::backend::EventBusExt;
+use crate::storage::backend::KvCacheExt;
 use crate::storage::redis::model::NewTokenCache;
 struct TokenHandlerMetadata {
     receiver: mpsc::Receiver<TokenHandler>,
     db: Arc<StorageEngine>,
     shutdown: ShutdownSignal,
+    bundle_analyzer: Arc<BundleAnalyzer>,
 }
 
 impl TokenHandlerMetadata {
@@ -27,11 +33,14 @@ impl TokenHandlerMetadata {
         receiver: mpsc::Receiver<TokenHandler>,
         db: Arc<StorageEngine>,
         shutdown: ShutdownSignal,
+        rpc_config: Arc<RpcConfig>,
     ) -> Self {
+        let bundle_analyzer = Arc::new(BundleAnalyzer::new(db.clone(), rpc_config));
         Self {
             receiver,
             db,
             shutdown,
+            bundle_analyzer,
         }
     }
 
@@ -40,7 +49,7 @@ impl TokenHandlerMetadata {
         token: TokenMetadata,
     ) -> Result<()> {
         // First check Redis cache
-        let cached_token = self.db.redis.kv.get::<TokenMetadata>(&token.mint.to_string()).await?;
+        let cached_token = self.db.backend.kv.get::<TokenMetadata>(&token.mint.to_string()).await?;
 
         // Skip if we already have this token with the same data
         if let Some(existing) = cached_token {
@@ -51,14 +60,35 @@ impl TokenHandlerMetadata {
         }
 
         // Store in Postgres
-        self.db.postgres.db.insert_token_metadata(&token).await?;
+        self.db.backend.tokens.insert_token_metadata(&token).await?;
 
         // Update Redis cache
-        self.db.redis.kv.set(&token.mint.to_string(), &token).await?;
+        self.db.backend.kv.set(&token.mint.to_string(), &token).await?;
 
         // Publish event for cross-service communication
         let new_token_cache = NewTokenCache::from(token.clone());
-        self.db.redis.queue.publish("new_token_created", &new_token_cache).await?;
+        self.db.backend.events.publish("new_token_created", &new_token_cache).await?;
+        Ok(())
+    }
+
+    // The launch is analyzed right away against whatever funding graph Baseer already stored,
+    // Baseer analyzes it again once its graph lands
+    async fn store_launch_window(
+        &self,
+        window: LaunchWindow,
+    ) -> Result<()> {
+        self.db.backend.launches.store_launch_window(&window).await?;
+        debug!("launch_window_stored::{}::early_buys::{}", window.mint, window.buys.len());
+
+        // Buyer funding lookups go to RPC, keep them off the actor loop
+        let bundle_analyzer = self.bundle_analyzer.clone();
+        tokio::spawn(async move {
+            let analyzed_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            bundle_analyzer.analyze_with_window(&window, analyzed_at).await;
+        });
         Ok(())
     }
 }
@@ -73,7 +103,13 @@ async fn run_token_handler_metadata(mut token_creation_metadata: TokenHandlerMet
                             error!("store_token_metadata_failed:{}", e);
                         }
                     },
-                    // Only handle store token messages
+                    TokenHandler::StoreLaunchWindow { window } => {
+                        let mint = window.mint;
+                        if let Err(e) = token_creation_metadata.store_launch_window(window).await {
+                            error!("store_launch_window_failed::{}: {}", mint, e);
+                        }
+                    },
+                    // Bonded token updates are not handled here
                     _ => {}
                 }
             },
@@ -101,10 +137,11 @@ impl TokenHandlerMetadataOperator {
     pub fn new(
         db: Arc<StorageEngine>,
         shutdown: ShutdownSignal,
+        rpc_config: Arc<RpcConfig>,
     ) -> Self {
         let (sender, receiver) = mpsc::channel(1000);
 
-        let receiver = TokenHandlerMetadata::new(receiver, db, shutdown.clone());
+        let receiver = TokenHandlerMetadata::new(receiver, db, shutdown.clone(), rpc_config);
 
         // Spawn the actor
         tokio::spawn(run_token_handler_metadata(receiver));
@@ -151,6 +188,21 @@ impl TokenHandlerMetadataOperator {
         }
     }
 
+    pub async fn store_launch_window(
+        &self,
+        window: LaunchWindow,
+    ) -> Result<()> {
+        match self.sender.try_send(TokenHandler::StoreLaunchWindow {
+            window,
+        }) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                error!("send_token_handler_failed: {}", e);
+                Err(err_with_loc!(HandlerError::SendTokenHandlerError(format!("send_token_handler_failed:{}", e))))
+            },
+        }
+    }
+
     pub fn shutdown(&self) {
         self.shutdown.shutdown();
     }
@@ -1,4 +1,7 @@
+pub mod bundle;
 pub mod creator;
+pub mod dump;
+pub mod holders;
 pub mod metadata;
 
 use std::sync::Arc;
@@ -7,10 +10,14 @@ use solana_pubkey::Pubkey;
 use tokio_util::sync::CancellationToken;
 
 use crate::config::CreatorAnalyzerConfig;
+use crate::model::bundle::LaunchWindow;
 use crate::model::cex::Cex;
+use crate::model::creator::crawl::CrawlGuard;
 use crate::model::creator::graph::SharedCreatorConnectionGraph;
 use crate::model::creator::metadata::CreatorMetadata;
+use crate::model::dump::LaunchTrade;
 use crate::model::token::TokenMetadata;
+use crate::storage::redis::queue::AccountLease;
 
 pub enum TokenHandler {
     StoreToken {
@@ -19,6 +26,20 @@ pub enum TokenHandler {
     UpdateBondedToken {
         token_metadata: TokenMetadata,
     },
+    StoreLaunchWindow {
+        window: LaunchWindow,
+    },
+}
+
+pub enum DevDumpHandler {
+    WatchLaunch {
+        mint: Pubkey,
+        creator: Pubkey,
+        launched_at: i64,
+    },
+    Trade {
+        trade: LaunchTrade,
+    },
 }
 
 pub enum CreatorHandler {
@@ -28,6 +49,7 @@ pub enum CreatorHandler {
         child_token: CancellationToken,
         creator_analyzer_config: Arc<CreatorAnalyzerConfig>,
         depth: usize,
+        crawl: CrawlGuard,
     },
     CexConnection {
         cex: Cex,
@@ -38,12 +60,15 @@ pub enum CreatorHandler {
         dev: Pubkey,
         created_at: u64,
         bonding_curve: Pubkey,
+        crawl: CrawlGuard,
     },
     ProcessRecoveredAccount {
         creator_metadata: Arc<CreatorMetadata>,
         child_token: CancellationToken,
         creator_analyzer_config: Arc<CreatorAnalyzerConfig>,
         depth: usize,
+        // Released once the account's crawl is done, until then it survives a crash of this worker
+        lease: AccountLease,
     },
     MaxDepthReached {
         creator_metadata: Arc<CreatorMetadata>,
@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+
+use serde::Deserialize;
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::model::price::TokenAth;
+use crate::model::price::TokenPrice;
+
+/// What a price alert rule watches for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AlertCondition {
+    // Market cap moves from below to at or above `market_cap_sol`
+    MarketCapCross {
+        market_cap_sol: f64,
+    },
+    // Price reaches `multiple` times its low of the last `window_secs`
+    PriceMultiple {
+        multiple: f64,
+        window_secs: i64,
+    },
+    // Price sits `drawdown_pct` or more below the all-time high
+    AthDrawdown {
+        drawdown_pct: f64,
+    },
+}
+
+impl AlertCondition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertCondition::MarketCapCross {
+                ..
+            } => "market_cap_cross",
+            AlertCondition::PriceMultiple {
+                ..
+            } => "price_multiple",
+            AlertCondition::AthDrawdown {
+                ..
+            } => "ath_drawdown",
+        }
+    }
+
+    /// Rebuild a condition from its stored `kind`, `threshold` and `window_secs` columns
+    pub fn from_parts(
+        kind: &str,
+        threshold: f64,
+        window_secs: Option<i64>,
+    ) -> Option<Self> {
+        match kind {
+            "market_cap_cross" => Some(AlertCondition::MarketCapCross {
+                market_cap_sol: threshold,
+            }),
+            "price_multiple" => Some(AlertCondition::PriceMultiple {
+                multiple: threshold,
+                window_secs: window_secs?,
+            }),
+            "ath_drawdown" => Some(AlertCondition::AthDrawdown {
+                drawdown_pct: threshold,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn threshold(&self) -> f64 {
+        match self {
+            AlertCondition::MarketCapCross {
+                market_cap_sol,
+            } => *market_cap_sol,
+            AlertCondition::PriceMultiple {
+                multiple, ..
+            } => *multiple,
+            AlertCondition::AthDrawdown {
+                drawdown_pct,
+            } => *drawdown_pct,
+        }
+    }
+
+    pub fn window_secs(&self) -> Option<i64> {
+        match self {
+            AlertCondition::PriceMultiple {
+                window_secs, ..
+            } => Some(*window_secs),
+            _ => None,
+        }
+    }
+
+    /// Check the condition against a new price, returns the observed value when it holds
+    pub fn evaluate(
+        &self,
+        price: &TokenPrice,
+        previous_market_cap_sol: Option<f64>,
+        window: Option<&PriceWindow>,
+        ath: Option<&TokenAth>,
+    ) -> Option<f64> {
+        match self {
+            AlertCondition::MarketCapCross {
+                market_cap_sol,
+            } => {
+                let previous = previous_market_cap_sol?;
+                (previous < *market_cap_sol && price.market_cap_sol >= *market_cap_sol).then_some(price.market_cap_sol)
+            },
+            AlertCondition::PriceMultiple {
+                multiple,
+                window_secs,
+            } => {
+                let low = window?.low_since(price.timestamp - window_secs).filter(|low| *low > 0)?;
+                let observed = price.price as f64 / low as f64;
+                (observed >= *multiple).then_some(observed)
+            },
+            AlertCondition::AthDrawdown {
+                drawdown_pct,
+            } => {
+                let ath = ath.filter(|ath| ath.price > 0)?;
+                let observed = (1.0 - price.price as f64 / ath.price as f64) * 100.0;
+                (observed >= *drawdown_pct).then_some(observed)
+            },
+        }
+    }
+}
+
+/// An operator-defined rule evaluated against every price update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub id: i32,
+    pub name: String,
+    // Only this mint, or every tracked mint when unset
+    pub mint: Option<Pubkey>,
+    // Only mints whose creator was funded from a CEX
+    pub cex_funded_only: bool,
+    pub condition: AlertCondition,
+    // Minimum time between two hits of the rule on the same mint
+    pub cooldown_secs: i64,
+    pub enabled: bool,
+}
+
+impl AlertRule {
+    pub fn applies_to(
+        &self,
+        mint: &Pubkey,
+        cex_funded: bool,
+    ) -> bool {
+        self.enabled && self.mint.is_none_or(|rule_mint| rule_mint == *mint) && (!self.cex_funded_only || cex_funded)
+    }
+}
+
+/// Recent prices of one mint, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct PriceWindow {
+    prices: VecDeque<(i64, u64)>,
+}
+
+impl PriceWindow {
+    pub fn push(
+        &mut self,
+        timestamp: i64,
+        price: u64,
+        keep_secs: i64,
+    ) {
+        self.prices.push_back((timestamp, price));
+        while self.prices.front().is_some_and(|(at, _)| *at < timestamp - keep_secs) {
+            self.prices.pop_front();
+        }
+    }
+
+    /// Lowest price seen at or after `since`
+    pub fn low_since(
+        &self,
+        since: i64,
+    ) -> Option<u64> {
+        self.prices.iter().filter(|(at, _)| *at >= since).map(|(_, price)| *price).min()
+    }
+
+    pub fn last_at(&self) -> Option<i64> {
+        self.prices.back().map(|(at, _)| *at)
+    }
+}
+
+/// A rule that fired on a mint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertHit {
+    pub rule_id: i32,
+    pub rule_name: String,
+    pub mint: Pubkey,
+    pub condition: AlertCondition,
+    // Market cap, multiple or drawdown percentage, depending on the condition
+    pub observed: f64,
+    pub price_sol: f64,
+    pub market_cap_sol: f64,
+    pub market_cap_usd: Option<f64>,
+    pub triggered_at: i64,
+}
+
+impl AlertHit {
+    pub fn message(&self) -> String {
+        let detail = match &self.condition {
+            AlertCondition::MarketCapCross {
+                market_cap_sol,
+            } => format!("market cap crossed {} SOL", market_cap_sol),
+            AlertCondition::PriceMultiple {
+                window_secs, ..
+            } => format!("{:.2}x within {}s", self.observed, window_secs),
+            AlertCondition::AthDrawdown {
+                ..
+            } => format!("{:.2}% below ATH", self.observed),
+        };
+        format!(
+            "price_alert::{}::mint::{}::{}::price_sol::{}::market_cap_sol::{:.2}::market_cap_usd::{:?}",
+            self.rule_name, self.mint, detail, self.price_sol, self.market_cap_sol, self.market_cap_usd
+        )
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::error::Error;
+use crate::error::anyhow;
+use crate::model::dev::Dev;
+
+const DAY_SECS: u64 = 86_400;
+
+/// One historical launch with the signals cohorts are built from and its recorded outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestSample {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub platform: String,
+    pub created_at: u64,
+    pub is_bonded: bool,
+    // Names of the exchanges the creator was funded from, empty when none was found
+    pub funding_cexes: Vec<String>,
+    // Share of supply bought by suspected bundlers, when the launch was analyzed
+    pub bundled_supply_share: Option<f64>,
+    pub launch_price: Option<u64>,
+    pub ath_price: Option<u64>,
+    pub ath_at: Option<u64>,
+    // Last recorded price within the first 24h, only set once the token is 24h old
+    pub price_24h: Option<u64>,
+}
+
+impl BacktestSample {
+    pub fn ath_multiple(&self) -> Option<f64> {
+        let launch_price = self.launch_price.filter(|price| *price > 0)?;
+        Some(self.ath_price? as f64 / launch_price as f64)
+    }
+
+    pub fn time_to_ath_secs(&self) -> Option<f64> {
+        Some(self.ath_at?.saturating_sub(self.created_at) as f64)
+    }
+
+    pub fn return_24h_pct(&self) -> Option<f64> {
+        let launch_price = self.launch_price.filter(|price| *price > 0)?;
+        Some((self.price_24h? as f64 / launch_price as f64 - 1.0) * 100.0)
+    }
+
+    pub fn is_24h_old(
+        &self,
+        now: u64,
+    ) -> bool {
+        now >= self.created_at + DAY_SECS
+    }
+
+    pub fn dev_label(&self) -> String {
+        Dev::get_dev_name(self.creator)
+            .map(|dev_name| dev_name.to_string())
+            .unwrap_or_else(|| String::from("unlabeled"))
+    }
+
+    /// Launch risk from the bundled share of supply: below 10% low, below 30% medium, high otherwise
+    pub fn risk_band(&self) -> &'static str {
+        match self.bundled_supply_share {
+            None => "unknown",
+            Some(share) if share < 0.1 => "low",
+            Some(share) if share < 0.3 => "medium",
+            Some(_) => "high",
+        }
+    }
+}
+
+/// What launches are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CohortDimension {
+    #[serde(rename = "funding_source")]
+    FundingSource,
+    #[serde(rename = "risk_band")]
+    RiskBand,
+    #[serde(rename = "dev_label")]
+    DevLabel,
+    #[serde(rename = "platform")]
+    Platform,
+}
+
+impl FromStr for CohortDimension {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "funding_source" | "cex" => Ok(CohortDimension::FundingSource),
+            "risk_band" | "risk" => Ok(CohortDimension::RiskBand),
+            "dev_label" | "dev" => Ok(CohortDimension::DevLabel),
+            "platform" => Ok(CohortDimension::Platform),
+            _ => Err(anyhow!("unsupported_cohort::{}", s)),
+        }
+    }
+}
+
+impl CohortDimension {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CohortDimension::FundingSource => "funding_source",
+            CohortDimension::RiskBand => "risk_band",
+            CohortDimension::DevLabel => "dev_label",
+            CohortDimension::Platform => "platform",
+        }
+    }
+
+    /// Cohorts a sample falls in, a launch funded from several exchanges counts towards each of them
+    pub fn cohorts(
+        &self,
+        sample: &BacktestSample,
+    ) -> Vec<String> {
+        match self {
+            CohortDimension::FundingSource if sample.funding_cexes.is_empty() => vec![String::from("none")],
+            CohortDimension::FundingSource => sample.funding_cexes.clone(),
+            CohortDimension::RiskBand => vec![sample.risk_band().to_string()],
+            CohortDimension::DevLabel => vec![sample.dev_label()],
+            CohortDimension::Platform => vec![sample.platform.clone()],
+        }
+    }
+}
+
+/// Summary of one outcome metric across a cohort
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Distribution {
+    pub count: usize,
+    pub mean: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub p90: f64,
+}
+
+impl Distribution {
+    pub fn from_values(mut values: Vec<f64>) -> Option<Self> {
+        values.retain(|value| value.is_finite());
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+        Some(Self {
+            count: values.len(),
+            mean: values.iter().sum::<f64>() / values.len() as f64,
+            p25: percentile(&values, 0.25),
+            median: percentile(&values, 0.5),
+            p75: percentile(&values, 0.75),
+            p90: percentile(&values, 0.9),
+        })
+    }
+}
+
+// Linear interpolation between closest ranks, same as Postgres `percentile_cont`
+fn percentile(
+    sorted: &[f64],
+    fraction: f64,
+) -> f64 {
+    let rank = fraction * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f64)
+}
+
+/// Outcomes of the launches in one cohort
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CohortReport {
+    pub dimension: String,
+    pub cohort: String,
+    pub token_count: usize,
+    pub bonded_count: usize,
+    pub bonded_rate: f64,
+    pub ath_multiple: Option<Distribution>,
+    pub time_to_ath_secs: Option<Distribution>,
+    pub return_24h_pct: Option<Distribution>,
+}
+
+impl CohortReport {
+    fn from_samples(
+        dimension: &str,
+        cohort: String,
+        samples: &[&BacktestSample],
+        now: u64,
+    ) -> Self {
+        let bonded_count = samples.iter().filter(|sample| sample.is_bonded).count();
+        Self {
+            dimension: dimension.to_string(),
+            cohort,
+            token_count: samples.len(),
+            bonded_count,
+            bonded_rate: if samples.is_empty() {
+                0.0
+            } else {
+                bonded_count as f64 / samples.len() as f64
+            },
+            ath_multiple: Distribution::from_values(
+                samples.iter().filter_map(|sample| sample.ath_multiple()).collect(),
+            ),
+            time_to_ath_secs: Distribution::from_values(
+                samples.iter().filter_map(|sample| sample.time_to_ath_secs()).collect(),
+            ),
+            // Tokens younger than a day have no 24h outcome yet
+            return_24h_pct: Distribution::from_values(
+                samples
+                    .iter()
+                    .filter(|sample| sample.is_24h_old(now))
+                    .filter_map(|sample| sample.return_24h_pct())
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn csv_header() -> String {
+        let mut columns = vec![
+            String::from("dimension"),
+            String::from("cohort"),
+            String::from("token_count"),
+            String::from("bonded_count"),
+            String::from("bonded_rate"),
+        ];
+        for metric in ["ath_multiple", "time_to_ath_secs", "return_24h_pct"] {
+            for stat in ["count", "mean", "p25", "median", "p75", "p90"] {
+                columns.push(format!("{}_{}", metric, stat));
+            }
+        }
+        columns.join(",")
+    }
+
+    pub fn to_csv_row(&self) -> String {
+        let mut columns = vec![
+            csv_field(&self.dimension),
+            csv_field(&self.cohort),
+            self.token_count.to_string(),
+            self.bonded_count.to_string(),
+            format!("{:.4}", self.bonded_rate),
+        ];
+        for distribution in [&self.ath_multiple, &self.time_to_ath_secs, &self.return_24h_pct] {
+            match distribution {
+                Some(d) => {
+                    columns.push(d.count.to_string());
+                    for value in [d.mean, d.p25, d.median, d.p75, d.p90] {
+                        columns.push(format!("{:.4}", value));
+                    }
+                },
+                None => {
+                    columns.push(String::from("0"));
+                    columns.extend(std::iter::repeat_n(String::new(), 5));
+                },
+            }
+        }
+        columns.join(",")
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Group launches into cohorts along each dimension and summarize their outcomes,
+/// led by an `all` baseline the cohorts can be compared against
+pub fn run_backtest(
+    samples: &[BacktestSample],
+    dimensions: &[CohortDimension],
+    now: u64,
+) -> Vec<CohortReport> {
+    let all: Vec<&BacktestSample> = samples.iter().collect();
+    let mut reports = vec![CohortReport::from_samples("all", String::from("all"), &all, now)];
+
+    for dimension in dimensions {
+        let mut cohorts: BTreeMap<String, Vec<&BacktestSample>> = BTreeMap::new();
+        for sample in samples {
+            for cohort in dimension.cohorts(sample) {
+                cohorts.entry(cohort).or_default().push(sample);
+            }
+        }
+        for (cohort, members) in cohorts {
+            reports.push(CohortReport::from_samples(dimension.as_str(), cohort, &members, now));
+        }
+    }
+
+    reports
+}
+
+pub fn reports_to_csv(reports: &[CohortReport]) -> String {
+    let mut csv = CohortReport::csv_header();
+    csv.push('\n');
+    for report in reports {
+        csv.push_str(&report.to_csv_row());
+        csv.push('\n');
+    }
+    csv
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::constants::PUMP_TOKEN_TOTAL_SUPPLY;
+use crate::model::creator::graph::CreatorConnectionGraph;
+
+/// A buy on the bonding curve within the first slots after `Create`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EarlyBuy {
+    pub signature: String,
+    pub buyer: Pubkey,
+    pub fee_payer: Pubkey,
+    pub slot: u64,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+}
+
+/// Buys of a freshly created mint collected until its bundle window closes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchWindow {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub create_slot: u64,
+    pub window_slots: u64,
+    pub buys: Vec<EarlyBuy>,
+}
+
+impl LaunchWindow {
+    pub fn new(
+        mint: Pubkey,
+        creator: Pubkey,
+        create_slot: u64,
+        window_slots: u64,
+    ) -> Self {
+        Self {
+            mint,
+            creator,
+            create_slot,
+            window_slots,
+            buys: Vec::new(),
+        }
+    }
+
+    pub fn is_open(
+        &self,
+        slot: u64,
+    ) -> bool {
+        slot <= self.create_slot + self.window_slots
+    }
+}
+
+/// Why a wallet is suspected of bundling
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BundleSignal {
+    // The creator buying its own launch
+    Creator,
+    // Buyer appears in the creator's funding graph
+    FundingGraph,
+    // The creator sent SOL to the buyer shortly before it bought
+    CreatorFunded,
+    // The same fee payer signed buys for several wallets
+    SharedFeePayer,
+    // Another wallet bought the exact same lamport amount
+    IdenticalAmount,
+}
+
+impl BundleSignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BundleSignal::Creator => "creator",
+            BundleSignal::FundingGraph => "funding_graph",
+            BundleSignal::CreatorFunded => "creator_funded",
+            BundleSignal::SharedFeePayer => "shared_fee_payer",
+            BundleSignal::IdenticalAmount => "identical_amount",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuspectedBundler {
+    pub wallet: Pubkey,
+    pub signals: Vec<BundleSignal>,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    // Fraction of the total supply bought inside the window, 0.0 - 1.0
+    pub supply_share: f64,
+}
+
+/// Bundle findings for one launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleAnalysis {
+    pub mint: Pubkey,
+    pub create_slot: u64,
+    pub window_slots: u64,
+    pub early_buy_count: usize,
+    pub bundled_token_amount: u64,
+    pub bundled_supply_share: f64,
+    pub bundlers: Vec<SuspectedBundler>,
+}
+
+impl BundleAnalysis {
+    /// Flag early buyers that are the creator, sit in its funding graph, were funded by the
+    /// creator, share a fee payer with another buyer or bought the exact same amount as another buyer
+    pub fn analyze(
+        window: &LaunchWindow,
+        funding_graph: &CreatorConnectionGraph,
+        creator_funded: &HashSet<Pubkey>,
+    ) -> Self {
+        let buys = &window.buys;
+        let mut buyers_per_fee_payer: HashMap<Pubkey, HashSet<Pubkey>> = HashMap::new();
+        let mut buyers_per_amount: HashMap<u64, HashSet<Pubkey>> = HashMap::new();
+        for buy in buys {
+            buyers_per_fee_payer.entry(buy.fee_payer).or_default().insert(buy.buyer);
+            buyers_per_amount.entry(buy.sol_amount).or_default().insert(buy.buyer);
+        }
+
+        // Keep buyers in the order they first bought
+        let mut bundlers: Vec<SuspectedBundler> = Vec::new();
+        let mut index: HashMap<Pubkey, usize> = HashMap::new();
+        for buy in buys {
+            let mut signals = Vec::new();
+            if buy.buyer == window.creator {
+                signals.push(BundleSignal::Creator);
+            } else if funding_graph.get_node_by_address(buy.buyer).is_some() {
+                signals.push(BundleSignal::FundingGraph);
+            }
+            if creator_funded.contains(&buy.buyer) {
+                signals.push(BundleSignal::CreatorFunded);
+            }
+            if buyers_per_fee_payer.get(&buy.fee_payer).is_some_and(|buyers| buyers.len() > 1) {
+                signals.push(BundleSignal::SharedFeePayer);
+            }
+            if buyers_per_amount.get(&buy.sol_amount).is_some_and(|buyers| buyers.len() > 1) {
+                signals.push(BundleSignal::IdenticalAmount);
+            }
+
+            let i = *index.entry(buy.buyer).or_insert_with(|| {
+                bundlers.push(SuspectedBundler {
+                    wallet: buy.buyer,
+                    signals: Vec::new(),
+                    sol_amount: 0,
+                    token_amount: 0,
+                    supply_share: 0.0,
+                });
+                bundlers.len() - 1
+            });
+            let bundler = &mut bundlers[i];
+            bundler.sol_amount = bundler.sol_amount.saturating_add(buy.sol_amount);
+            bundler.token_amount = bundler.token_amount.saturating_add(buy.token_amount);
+            for signal in signals {
+                if !bundler.signals.contains(&signal) {
+                    bundler.signals.push(signal);
+                }
+            }
+        }
+
+        bundlers.retain(|bundler| !bundler.signals.is_empty());
+        for bundler in &mut bundlers {
+            bundler.supply_share = bundler.token_amount as f64 / PUMP_TOKEN_TOTAL_SUPPLY as f64;
+        }
+        let bundled_token_amount: u64 = bundlers.iter().map(|bundler| bundler.token_amount).sum();
+
+        Self {
+            mint: window.mint,
+            create_slot: window.create_slot,
+            window_slots: window.window_slots,
+            early_buy_count: buys.len(),
+            bundled_token_amount,
+            bundled_supply_share: bundled_token_amount as f64 / PUMP_TOKEN_TOTAL_SUPPLY as f64,
+            bundlers,
+        }
+    }
+
+    pub fn is_bundled(&self) -> bool {
+        self.bundlers.iter().any(|bundler| bundler.signals != [BundleSignal::Creator])
+    }
+}
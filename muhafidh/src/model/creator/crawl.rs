@@ -0,0 +1,35 @@
+use tokio::sync::mpsc;
+
+/// Held by every part of one creator crawl (each BFS level's pipeline and the messages that start
+/// them). The crawl is over once the last clone is dropped, however its levels ended
+#[derive(Debug, Clone)]
+pub struct CrawlGuard {
+    _alive: mpsc::Sender<()>,
+}
+
+/// Resolves once every `CrawlGuard` of its crawl is dropped
+#[derive(Debug)]
+pub struct CrawlFinished {
+    receiver: mpsc::Receiver<()>,
+}
+
+impl CrawlGuard {
+    pub fn new() -> (Self, CrawlFinished) {
+        let (sender, receiver) = mpsc::channel(1);
+        (
+            Self {
+                _alive: sender,
+            },
+            CrawlFinished {
+                receiver,
+            },
+        )
+    }
+}
+
+impl CrawlFinished {
+    pub async fn wait(mut self) {
+        // Nothing is ever sent, `recv` returns `None` once all senders are gone
+        let _ = self.receiver.recv().await;
+    }
+}
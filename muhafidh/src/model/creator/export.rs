@@ -0,0 +1,229 @@
+use std::fmt::Write;
+use std::str::FromStr;
+
+use super::graph::AddressNode;
+use super::graph::CreatorConnectionGraph;
+use crate::error::Error;
+use crate::error::anyhow;
+use crate::model::cex::Cex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphExportFormat {
+    GraphMl,
+    Gexf,
+    Dot,
+}
+
+impl FromStr for GraphExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "graphml" => Ok(GraphExportFormat::GraphMl),
+            "gexf" => Ok(GraphExportFormat::Gexf),
+            "dot" | "gv" => Ok(GraphExportFormat::Dot),
+            _ => Err(anyhow!("unsupported_export_format::{}", s)),
+        }
+    }
+}
+
+impl GraphExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            GraphExportFormat::GraphMl => "graphml",
+            GraphExportFormat::Gexf => "gexf",
+            GraphExportFormat::Dot => "dot",
+        }
+    }
+}
+
+fn cex_name(node: &AddressNode) -> String {
+    if !node.is_cex {
+        return String::new();
+    }
+    Cex::get_exchange_name(node.detail.address)
+        .map(|name| name.to_string())
+        .unwrap_or_default()
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Writing into a String cannot fail, so the fmt::Result of write! is ignored throughout
+impl CreatorConnectionGraph {
+    pub fn export(
+        &self,
+        format: GraphExportFormat,
+    ) -> String {
+        match format {
+            GraphExportFormat::GraphMl => self.to_graphml(),
+            GraphExportFormat::Gexf => self.to_gexf(),
+            GraphExportFormat::Dot => self.to_dot(),
+        }
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+        out.push_str("  <key id=\"address\" for=\"node\" attr.name=\"address\" attr.type=\"string\"/>\n");
+        out.push_str("  <key id=\"is_cex\" for=\"node\" attr.name=\"is_cex\" attr.type=\"boolean\"/>\n");
+        out.push_str("  <key id=\"cex_name\" for=\"node\" attr.name=\"cex_name\" attr.type=\"string\"/>\n");
+        out.push_str("  <key id=\"balance\" for=\"node\" attr.name=\"balance\" attr.type=\"double\"/>\n");
+        out.push_str("  <key id=\"amount\" for=\"edge\" attr.name=\"amount\" attr.type=\"double\"/>\n");
+        out.push_str("  <key id=\"transfer_count\" for=\"edge\" attr.name=\"transfer_count\" attr.type=\"long\"/>\n");
+        out.push_str("  <key id=\"first_timestamp\" for=\"edge\" attr.name=\"first_timestamp\" attr.type=\"long\"/>\n");
+        out.push_str("  <key id=\"last_timestamp\" for=\"edge\" attr.name=\"last_timestamp\" attr.type=\"long\"/>\n");
+        out.push_str("  <graph id=\"funding\" edgedefault=\"directed\">\n");
+
+        for node in self.get_nodes() {
+            let address = node.detail.address.to_string();
+            let _ = write!(
+                out,
+                "    <node id=\"{address}\">\n      <data key=\"address\">{address}</data>\n      <data \
+                 key=\"is_cex\">{}</data>\n      <data key=\"cex_name\">{}</data>\n      <data \
+                 key=\"balance\">{}</data>\n    </node>\n",
+                node.is_cex,
+                escape_xml(&cex_name(&node)),
+                node.detail.sol_balance,
+            );
+        }
+
+        for (i, edge) in self.get_edges().iter().enumerate() {
+            let _ = write!(
+                out,
+                "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\">\n      <data key=\"amount\">{}</data>\n      \
+                 <data key=\"transfer_count\">{}</data>\n      <data key=\"first_timestamp\">{}</data>\n      <data \
+                 key=\"last_timestamp\">{}</data>\n    </edge>\n",
+                edge.from, edge.to, edge.amount, edge.transfer_count, edge.first_timestamp, edge.last_timestamp,
+            );
+        }
+
+        out.push_str("  </graph>\n</graphml>\n");
+        out
+    }
+
+    pub fn to_gexf(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n");
+        out.push_str("  <graph defaultedgetype=\"directed\" mode=\"static\">\n");
+        out.push_str("    <attributes class=\"node\">\n");
+        out.push_str("      <attribute id=\"0\" title=\"address\" type=\"string\"/>\n");
+        out.push_str("      <attribute id=\"1\" title=\"is_cex\" type=\"boolean\"/>\n");
+        out.push_str("      <attribute id=\"2\" title=\"cex_name\" type=\"string\"/>\n");
+        out.push_str("      <attribute id=\"3\" title=\"balance\" type=\"double\"/>\n");
+        out.push_str("    </attributes>\n");
+        out.push_str("    <attributes class=\"edge\">\n");
+        out.push_str("      <attribute id=\"0\" title=\"amount\" type=\"double\"/>\n");
+        out.push_str("      <attribute id=\"1\" title=\"transfer_count\" type=\"long\"/>\n");
+        out.push_str("      <attribute id=\"2\" title=\"first_timestamp\" type=\"long\"/>\n");
+        out.push_str("      <attribute id=\"3\" title=\"last_timestamp\" type=\"long\"/>\n");
+        out.push_str("    </attributes>\n");
+
+        out.push_str("    <nodes>\n");
+        for node in self.get_nodes() {
+            let address = node.detail.address.to_string();
+            let cex_name = escape_xml(&cex_name(&node));
+            let label = if cex_name.is_empty() {
+                address.clone()
+            } else {
+                cex_name.clone()
+            };
+            let _ = write!(
+                out,
+                "      <node id=\"{address}\" label=\"{label}\">\n        <attvalues>\n          <attvalue for=\"0\" \
+                 value=\"{address}\"/>\n          <attvalue for=\"1\" value=\"{}\"/>\n          <attvalue for=\"2\" \
+                 value=\"{cex_name}\"/>\n          <attvalue for=\"3\" value=\"{}\"/>\n        </attvalues>\n      \
+                 </node>\n",
+                node.is_cex, node.detail.sol_balance,
+            );
+        }
+        out.push_str("    </nodes>\n");
+
+        out.push_str("    <edges>\n");
+        for (i, edge) in self.get_edges().iter().enumerate() {
+            let _ = write!(
+                out,
+                "      <edge id=\"{i}\" source=\"{}\" target=\"{}\" weight=\"{}\">\n        <attvalues>\n          \
+                 <attvalue for=\"0\" value=\"{}\"/>\n          <attvalue for=\"1\" value=\"{}\"/>\n          \
+                 <attvalue for=\"2\" value=\"{}\"/>\n          <attvalue for=\"3\" value=\"{}\"/>\n        \
+                 </attvalues>\n      </edge>\n",
+                edge.from,
+                edge.to,
+                edge.amount,
+                edge.amount,
+                edge.transfer_count,
+                edge.first_timestamp,
+                edge.last_timestamp,
+            );
+        }
+        out.push_str("    </edges>\n");
+
+        out.push_str("  </graph>\n</gexf>\n");
+        out
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        out.push_str("digraph funding {\n");
+        out.push_str("  node [shape=box];\n");
+
+        for node in self.get_nodes() {
+            let address = node.detail.address.to_string();
+            let cex_name = escape_dot(&cex_name(&node));
+            let label = if cex_name.is_empty() {
+                address.clone()
+            } else {
+                format!("{}\\n{}", cex_name, address)
+            };
+            let _ = writeln!(
+                out,
+                "  \"{address}\" [label=\"{label}\", address=\"{address}\", is_cex={}, cex_name=\"{cex_name}\", \
+                 balance={}{}];",
+                node.is_cex,
+                node.detail.sol_balance,
+                if node.is_cex {
+                    ", style=filled, fillcolor=gold"
+                } else {
+                    ""
+                },
+            );
+        }
+
+        for edge in self.get_edges() {
+            let _ = writeln!(
+                out,
+                "  \"{}\" -> \"{}\" [label=\"{} ({}x)\", amount={}, transfer_count={}, first_timestamp={}, \
+                 last_timestamp={}];",
+                edge.from,
+                edge.to,
+                edge.amount,
+                edge.transfer_count,
+                edge.amount,
+                edge.transfer_count,
+                edge.first_timestamp,
+                edge.last_timestamp,
+            );
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
@@ -34,12 +34,96 @@ pub struct AddressNode {
     pub is_cex: bool,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferRecord {
+    // Signature and instruction path, the same under every mint the transfer is stored for
+    pub transfer_id: String,
+    pub amount: f64,
+    pub timestamp: i64,
+}
+
+// All transfers between one pair of wallets, aggregated into a single weighted edge
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TransactionEdge {
     pub from: solana_pubkey::Pubkey,
     pub to: solana_pubkey::Pubkey,
+    // Total amount over all transfers
     pub amount: f64,
-    pub timestamp: i64,
+    #[serde(default = "default_transfer_count")]
+    pub transfer_count: u64,
+    #[serde(alias = "timestamp")]
+    pub first_timestamp: i64,
+    #[serde(default)]
+    pub last_timestamp: i64,
+    // Kept out of cached and published graphs, individual transfers are read from `GraphDb::get_transfers`
+    #[serde(skip)]
+    pub transfers: Vec<TransferRecord>,
+}
+
+fn default_transfer_count() -> u64 {
+    1
+}
+
+impl TransactionEdge {
+    pub fn new(
+        from: Pubkey,
+        to: Pubkey,
+        amount: f64,
+        timestamp: i64,
+        transfer_id: String,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            amount,
+            transfer_count: 1,
+            first_timestamp: timestamp,
+            last_timestamp: timestamp,
+            transfers: vec![TransferRecord {
+                transfer_id,
+                amount,
+                timestamp,
+            }],
+        }
+    }
+
+    pub fn record_transfer(
+        &mut self,
+        amount: f64,
+        timestamp: i64,
+        transfer_id: String,
+    ) {
+        self.amount += amount;
+        self.transfer_count += 1;
+        self.first_timestamp = self.first_timestamp.min(timestamp);
+        self.last_timestamp = self.last_timestamp.max(timestamp);
+        self.transfers.push(TransferRecord {
+            transfer_id,
+            amount,
+            timestamp,
+        });
+    }
+
+    pub fn has_transfer(
+        &self,
+        transfer_id: &str,
+    ) -> bool {
+        self.transfers.iter().any(|t| t.transfer_id == transfer_id)
+    }
+
+    // Fold in a copy of this edge that only carries totals, as cached and published graphs do. Copies
+    // from several mints describe the same transfers, so the larger totals win instead of adding up
+    fn merge_totals(
+        &mut self,
+        other: &TransactionEdge,
+    ) {
+        if other.transfer_count > self.transfer_count {
+            self.amount = other.amount;
+            self.transfer_count = other.transfer_count;
+        }
+        self.first_timestamp = self.first_timestamp.min(other.first_timestamp);
+        self.last_timestamp = self.last_timestamp.max(other.last_timestamp);
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -65,6 +149,10 @@ impl CreatorConnectionGraph {
                 self.node_indices.insert(node.detail.address, node_index);
             }
         }
+        // Edges serialized before aggregation only carry a single timestamp
+        for edge in self.graph.edge_weights_mut() {
+            edge.last_timestamp = edge.last_timestamp.max(edge.first_timestamp);
+        }
     }
 
     // Ensure indices are available (rebuild if empty and graph has nodes)
@@ -111,15 +199,22 @@ impl CreatorConnectionGraph {
         to: NodeIndex,
         amount: f64,
         timestamp: i64,
+        transfer_id: String,
     ) {
+        // Repeated transfers between the same pair are folded into the existing edge, a transfer seen
+        // before is only counted once
+        if let Some(edge_idx) = self.graph.find_edge(from, to) {
+            if let Some(edge) = self.graph.edge_weight_mut(edge_idx) {
+                if !edge.has_transfer(&transfer_id) {
+                    edge.record_transfer(amount, timestamp, transfer_id);
+                }
+            }
+            return;
+        }
+
         let sender = self.graph.node_weight(from).unwrap();
         let receiver = self.graph.node_weight(to).unwrap();
-        let edge = TransactionEdge {
-            from: sender.detail.address,
-            to: receiver.detail.address,
-            amount,
-            timestamp,
-        };
+        let edge = TransactionEdge::new(sender.detail.address, receiver.detail.address, amount, timestamp, transfer_id);
 
         self.graph.add_edge(from, to, edge);
     }
@@ -156,11 +251,59 @@ impl CreatorConnectionGraph {
         from: Pubkey,
         to: Pubkey,
     ) -> Option<TransactionEdge> {
+        let from = self.node_indices.get(&from)?;
+        let to = self.node_indices.get(&to)?;
         self.graph
-            .find_edge(self.node_indices[&from], self.node_indices[&to])
+            .find_edge(*from, *to)
             .and_then(|edge_idx| self.graph.edge_weight(edge_idx).cloned())
     }
 
+    // Merge another graph into this one, skipping transfers that are already present by their id
+    pub fn merge(
+        &mut self,
+        other: &CreatorConnectionGraph,
+    ) {
+        for node in other.get_nodes() {
+            let idx = self.add_node(node.detail.address, node.detail.sol_balance, node.is_cex);
+            if let Some(existing) = self.graph.node_weight_mut(idx) {
+                existing.is_cex |= node.is_cex;
+                if existing.detail.last_updated < node.detail.last_updated {
+                    existing.detail.sol_balance = node.detail.sol_balance;
+                    existing.detail.last_updated = node.detail.last_updated;
+                }
+            }
+        }
+
+        for edge in other.get_edges() {
+            let from = self.node_indices[&edge.from];
+            let to = self.node_indices[&edge.to];
+            if edge.transfers.is_empty() {
+                match self.graph.find_edge(from, to) {
+                    Some(idx) => self.graph[idx].merge_totals(&edge),
+                    None => {
+                        self.graph.add_edge(from, to, edge);
+                    },
+                }
+                continue;
+            }
+
+            for transfer in edge.transfers {
+                self.add_edge(from, to, transfer.amount, transfer.timestamp, transfer.transfer_id);
+            }
+        }
+    }
+
+    // Individual transfers folded into the edge between two wallets
+    pub fn get_transfers(
+        &self,
+        from: Pubkey,
+        to: Pubkey,
+    ) -> Vec<TransferRecord> {
+        self.get_edge_by_addresses(from, to)
+            .map(|edge| edge.transfers)
+            .unwrap_or_default()
+    }
+
     pub async fn update_node_balance(
         &mut self,
         rpc_config: Arc<RpcConfig>,
@@ -239,8 +382,9 @@ impl SharedCreatorConnectionGraph {
         to: NodeIndex,
         amount: f64,
         timestamp: i64,
+        transfer_id: String,
     ) {
-        self.inner.write().await.add_edge(from, to, amount, timestamp);
+        self.inner.write().await.add_edge(from, to, amount, timestamp, transfer_id);
     }
 
     pub async fn get_node_count(&self) -> usize {
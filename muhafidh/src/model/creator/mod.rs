@@ -1,2 +1,4 @@
+pub mod crawl;
+pub mod export;
 pub mod graph;
 pub mod metadata;
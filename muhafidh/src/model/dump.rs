@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::model::price::reserves_scaled_price;
+
+/// A bonding-curve trade on a mint that is still inside its dev dump window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchTrade {
+    pub mint: Pubkey,
+    pub wallet: Pubkey,
+    pub is_buy: bool,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    // Curve reserves right after the trade
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub timestamp: i64,
+}
+
+impl LaunchTrade {
+    /// Price right after the trade, scaled like `token_price_history`
+    pub fn price_after(&self) -> u64 {
+        reserves_scaled_price(self.virtual_sol_reserves, self.virtual_token_reserves)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WalletPosition {
+    pub bought: u64,
+    pub sold: u64,
+    pub sol_received: u64,
+}
+
+/// Everything traded on one launch during its dev dump window
+#[derive(Debug, Clone)]
+pub struct LaunchWatch {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub launched_at: i64,
+    // Creator plus the wallets clustered with it: funding graph and launch bundlers
+    pub cluster: HashSet<Pubkey>,
+    pub positions: HashMap<Pubkey, WalletPosition>,
+    pub last_price: u64,
+    // Price right before the cluster's first sell
+    pub price_before_dump: Option<u64>,
+    pub detected: bool,
+}
+
+impl LaunchWatch {
+    pub fn new(
+        mint: Pubkey,
+        creator: Pubkey,
+        launched_at: i64,
+    ) -> Self {
+        Self {
+            mint,
+            creator,
+            launched_at,
+            cluster: HashSet::from([creator]),
+            positions: HashMap::new(),
+            last_price: 0,
+            price_before_dump: None,
+            detected: false,
+        }
+    }
+
+    pub fn apply_trade(
+        &mut self,
+        trade: &LaunchTrade,
+    ) {
+        let position = self.positions.entry(trade.wallet).or_default();
+        if trade.is_buy {
+            position.bought = position.bought.saturating_add(trade.token_amount);
+        } else {
+            position.sold = position.sold.saturating_add(trade.token_amount);
+            position.sol_received = position.sol_received.saturating_add(trade.sol_amount);
+            if self.price_before_dump.is_none() && self.cluster.contains(&trade.wallet) {
+                self.price_before_dump = Some(self.last_price);
+            }
+        }
+        self.last_price = trade.price_after();
+    }
+
+    /// Cluster wallets that sold, with what the cluster bought and sold in total
+    pub fn cluster_totals(&self) -> (Vec<Pubkey>, WalletPosition) {
+        let mut sellers = Vec::new();
+        let mut totals = WalletPosition::default();
+        for wallet in &self.cluster {
+            let Some(position) = self.positions.get(wallet) else {
+                continue;
+            };
+            if position.sold > 0 {
+                sellers.push(*wallet);
+            }
+            totals.bought = totals.bought.saturating_add(position.bought);
+            totals.sold = totals.sold.saturating_add(position.sold);
+            totals.sol_received = totals.sol_received.saturating_add(position.sol_received);
+        }
+        (sellers, totals)
+    }
+
+    /// Share of its holdings the cluster sold, in percent
+    pub fn cluster_sold_pct(&self) -> f64 {
+        let (_, totals) = self.cluster_totals();
+        if totals.bought == 0 {
+            return 0.0;
+        }
+        (totals.sold as f64 / totals.bought as f64 * 100.0).min(100.0)
+    }
+
+    /// Price change from before the cluster's first sell to now, in percent
+    pub fn price_impact_pct(&self) -> Option<f64> {
+        let before = self.price_before_dump.filter(|price| *price > 0)?;
+        Some((self.last_price as f64 / before as f64 - 1.0) * 100.0)
+    }
+}
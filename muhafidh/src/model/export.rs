@@ -0,0 +1,429 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use arrow_array::ArrayRef;
+use arrow_array::BooleanArray;
+use arrow_array::Float64Array;
+use arrow_array::Int64Array;
+use arrow_array::RecordBatch;
+use arrow_array::StringArray;
+use arrow_schema::DataType;
+use arrow_schema::Field;
+use arrow_schema::Schema;
+use arrow_schema::SchemaRef;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::error::anyhow;
+
+// Rows buffered before they are written out as one Parquet record batch
+const PARQUET_BATCH_ROWS: usize = 8_192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Parquet,
+    Ndjson,
+}
+
+impl FromStr for ExportFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "parquet" => Ok(ExportFormat::Parquet),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            _ => Err(anyhow!("unsupported_export_format::{}", s)),
+        }
+    }
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Text,
+    Int,
+    Float,
+    Bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ExportColumn {
+    pub name: &'static str,
+    pub kind: ColumnType,
+}
+
+const fn column(
+    name: &'static str,
+    kind: ColumnType,
+) -> ExportColumn {
+    ExportColumn {
+        name,
+        kind,
+    }
+}
+
+const TOKEN_COLUMNS: &[ExportColumn] = &[
+    column("mint", ColumnType::Text),
+    column("name", ColumnType::Text),
+    column("symbol", ColumnType::Text),
+    column("uri", ColumnType::Text),
+    column("creator", ColumnType::Text),
+    column("platform", ColumnType::Text),
+    column("created_at", ColumnType::Int),
+    // Comma separated CEX addresses
+    column("cex_sources", ColumnType::Text),
+    column("cex_updated_at", ColumnType::Int),
+    column("updated_at", ColumnType::Int),
+    column("associated_bonding_curve", ColumnType::Text),
+    column("is_bonded", ColumnType::Bool),
+    column("bonded_at", ColumnType::Int),
+    column("all_time_high_price", ColumnType::Int),
+    column("all_time_high_price_at", ColumnType::Int),
+    column("changed_at", ColumnType::Int),
+];
+
+const CEX_RELATION_COLUMNS: &[ExportColumn] = &[
+    column("cex_address", ColumnType::Text),
+    column("cex_name", ColumnType::Text),
+    column("token_mint", ColumnType::Text),
+    column("created_at", ColumnType::Int),
+];
+
+const WALLET_NODE_COLUMNS: &[ExportColumn] = &[
+    column("pubkey", ColumnType::Text),
+    column("is_cex", ColumnType::Bool),
+    column("cex_name", ColumnType::Text),
+    column("total_received", ColumnType::Float),
+    column("total_balance", ColumnType::Float),
+    column("created_at", ColumnType::Int),
+    column("changed_at", ColumnType::Int),
+];
+
+const WALLET_EDGE_COLUMNS: &[ExportColumn] = &[
+    column("source_pubkey", ColumnType::Text),
+    column("target_pubkey", ColumnType::Text),
+    column("mint", ColumnType::Text),
+    column("amount", ColumnType::Float),
+    column("transfer_count", ColumnType::Int),
+    column("first_timestamp", ColumnType::Int),
+    column("last_timestamp", ColumnType::Int),
+    column("changed_at", ColumnType::Int),
+];
+
+const PRICE_HISTORY_COLUMNS: &[ExportColumn] = &[
+    column("mint", ColumnType::Text),
+    column("trade_id", ColumnType::Text),
+    column("price", ColumnType::Int),
+    column("volume", ColumnType::Int),
+    column("timestamp", ColumnType::Int),
+    column("changed_at", ColumnType::Int),
+];
+
+/// A table (or join) exported as its own directory of day partitions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportDataset {
+    Tokens,
+    CexRelations,
+    WalletNodes,
+    WalletEdges,
+    PriceHistory,
+}
+
+impl FromStr for ExportDataset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tokens" => Ok(ExportDataset::Tokens),
+            "cex_relations" => Ok(ExportDataset::CexRelations),
+            "wallet_nodes" => Ok(ExportDataset::WalletNodes),
+            "wallet_edges" => Ok(ExportDataset::WalletEdges),
+            "price_history" => Ok(ExportDataset::PriceHistory),
+            _ => Err(anyhow!("unsupported_dataset::{}", s)),
+        }
+    }
+}
+
+impl ExportDataset {
+    pub const ALL: [ExportDataset; 5] = [
+        ExportDataset::Tokens,
+        ExportDataset::CexRelations,
+        ExportDataset::WalletNodes,
+        ExportDataset::WalletEdges,
+        ExportDataset::PriceHistory,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportDataset::Tokens => "tokens",
+            ExportDataset::CexRelations => "cex_relations",
+            ExportDataset::WalletNodes => "wallet_nodes",
+            ExportDataset::WalletEdges => "wallet_edges",
+            ExportDataset::PriceHistory => "price_history",
+        }
+    }
+
+    /// Columns in the order the export query selects them
+    pub fn columns(&self) -> &'static [ExportColumn] {
+        match self {
+            ExportDataset::Tokens => TOKEN_COLUMNS,
+            ExportDataset::CexRelations => CEX_RELATION_COLUMNS,
+            ExportDataset::WalletNodes => WALLET_NODE_COLUMNS,
+            ExportDataset::WalletEdges => WALLET_EDGE_COLUMNS,
+            ExportDataset::PriceHistory => PRICE_HISTORY_COLUMNS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportValue {
+    Text(Option<String>),
+    Int(Option<i64>),
+    Float(Option<f64>),
+    Bool(Option<bool>),
+}
+
+impl ExportValue {
+    fn to_json(&self) -> Value {
+        match self {
+            ExportValue::Text(value) => value.clone().map(Value::String).unwrap_or(Value::Null),
+            ExportValue::Int(value) => value.map(Value::from).unwrap_or(Value::Null),
+            // NaN and infinities have no JSON form
+            ExportValue::Float(value) => value
+                .and_then(serde_json::Number::from_f64)
+                .map(Value::Number)
+                .unwrap_or(Value::Null),
+            ExportValue::Bool(value) => value.map(Value::Bool).unwrap_or(Value::Null),
+        }
+    }
+
+    fn as_text(&self) -> Option<&str> {
+        match self {
+            ExportValue::Text(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    fn as_int(&self) -> Option<i64> {
+        match self {
+            ExportValue::Int(value) => *value,
+            _ => None,
+        }
+    }
+
+    fn as_float(&self) -> Option<f64> {
+        match self {
+            ExportValue::Float(value) => *value,
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            ExportValue::Bool(value) => *value,
+            _ => None,
+        }
+    }
+}
+
+fn arrow_schema(columns: &[ExportColumn]) -> SchemaRef {
+    let fields = columns
+        .iter()
+        .map(|column| {
+            let data_type = match column.kind {
+                ColumnType::Text => DataType::Utf8,
+                ColumnType::Int => DataType::Int64,
+                ColumnType::Float => DataType::Float64,
+                ColumnType::Bool => DataType::Boolean,
+            };
+            Field::new(column.name, data_type, true)
+        })
+        .collect::<Vec<Field>>();
+    Arc::new(Schema::new(fields))
+}
+
+fn record_batch(
+    schema: &SchemaRef,
+    columns: &[ExportColumn],
+    rows: &[Vec<ExportValue>],
+) -> Result<RecordBatch> {
+    let arrays = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| -> ArrayRef {
+            match column.kind {
+                ColumnType::Text => {
+                    Arc::new(StringArray::from(rows.iter().map(|row| row[i].as_text()).collect::<Vec<Option<&str>>>()))
+                },
+                ColumnType::Int => {
+                    Arc::new(Int64Array::from(rows.iter().map(|row| row[i].as_int()).collect::<Vec<_>>()))
+                },
+                ColumnType::Float => {
+                    Arc::new(Float64Array::from(rows.iter().map(|row| row[i].as_float()).collect::<Vec<_>>()))
+                },
+                ColumnType::Bool => {
+                    Arc::new(BooleanArray::from(rows.iter().map(|row| row[i].as_bool()).collect::<Vec<_>>()))
+                },
+            }
+        })
+        .collect::<Vec<ArrayRef>>();
+
+    Ok(RecordBatch::try_new(schema.clone(), arrays)?)
+}
+
+/// `YYYY-MM-DD` of a day counted from the unix epoch
+pub fn day_label(day: i64) -> String {
+    chrono::DateTime::from_timestamp(day * 86_400, 0)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+enum DayFile {
+    Ndjson(BufWriter<File>),
+    Parquet {
+        writer: ArrowWriter<File>,
+        buffer: Vec<Vec<ExportValue>>,
+    },
+}
+
+/// Writes one dataset as `{root}/{dataset}/day=YYYY-MM-DD/part-{run_id}.{ext}`. Rows must arrive
+/// ordered by day: a day's file is finished as soon as the next day starts
+pub struct DayPartitionedWriter {
+    root: PathBuf,
+    dataset: ExportDataset,
+    format: ExportFormat,
+    run_id: String,
+    schema: SchemaRef,
+    current: Option<(i64, DayFile)>,
+    // Every file written so far with its row count
+    written: Vec<(PathBuf, u64)>,
+}
+
+impl DayPartitionedWriter {
+    pub fn new(
+        root: &Path,
+        dataset: ExportDataset,
+        format: ExportFormat,
+        run_id: &str,
+    ) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            dataset,
+            format,
+            run_id: run_id.to_string(),
+            schema: arrow_schema(dataset.columns()),
+            current: None,
+            written: Vec::new(),
+        }
+    }
+
+    pub fn write_row(
+        &mut self,
+        day: i64,
+        row: Vec<ExportValue>,
+    ) -> Result<()> {
+        let same_day = matches!(&self.current, Some((current_day, _)) if *current_day == day);
+        if !same_day {
+            self.finish_day()?;
+            self.current = Some((day, self.open_day(day)?));
+        }
+
+        let columns = self.dataset.columns();
+        if let Some((_, file)) = self.current.as_mut() {
+            match file {
+                DayFile::Ndjson(writer) => {
+                    let line = columns
+                        .iter()
+                        .zip(row.iter())
+                        .map(|(column, value)| (column.name.to_string(), value.to_json()))
+                        .collect::<Map<String, Value>>();
+                    serde_json::to_writer(&mut *writer, &line)?;
+                    writer.write_all(b"\n")?;
+                },
+                DayFile::Parquet {
+                    writer,
+                    buffer,
+                } => {
+                    buffer.push(row);
+                    if buffer.len() >= PARQUET_BATCH_ROWS {
+                        writer.write(&record_batch(&self.schema, columns, buffer)?)?;
+                        buffer.clear();
+                    }
+                },
+            }
+        }
+        if let Some((_, rows)) = self.written.last_mut() {
+            *rows += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Finish the open file and return every file written with its row count
+    pub fn finish(mut self) -> Result<Vec<(PathBuf, u64)>> {
+        self.finish_day()?;
+        Ok(self.written)
+    }
+
+    fn open_day(
+        &mut self,
+        day: i64,
+    ) -> Result<DayFile> {
+        let dir = self.root.join(self.dataset.as_str()).join(format!("day={}", day_label(day)));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("part-{}.{}", self.run_id, self.format.extension()));
+        let file = File::create(&path)?;
+        self.written.push((path, 0));
+
+        let day_file = match self.format {
+            ExportFormat::Ndjson => DayFile::Ndjson(BufWriter::new(file)),
+            ExportFormat::Parquet => {
+                let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+                DayFile::Parquet {
+                    writer: ArrowWriter::try_new(file, self.schema.clone(), Some(properties))?,
+                    buffer: Vec::with_capacity(PARQUET_BATCH_ROWS),
+                }
+            },
+        };
+        Ok(day_file)
+    }
+
+    fn finish_day(&mut self) -> Result<()> {
+        match self.current.take() {
+            Some((_, DayFile::Ndjson(mut writer))) => writer.flush()?,
+            Some((
+                _,
+                DayFile::Parquet {
+                    mut writer,
+                    buffer,
+                },
+            )) => {
+                if !buffer.is_empty() {
+                    writer.write(&record_batch(&self.schema, self.dataset.columns(), &buffer)?)?;
+                }
+                writer.close()?;
+            },
+            None => {},
+        }
+        Ok(())
+    }
+}
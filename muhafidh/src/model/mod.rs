@@ -1,5 +1,11 @@
+pub mod alert;
+pub mod backtest;
+pub mod bundle;
 pub mod cex;
 pub mod creator;
 pub mod dev;
+pub mod dump;
+pub mod export;
 pub mod platform;
+pub mod price;
 pub mod token;
@@ -0,0 +1,378 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+use tokio::sync::RwLock;
+
+use crate::constants::PUMP_FUN_PROGRAM_ID;
+use crate::constants::PUMP_SWAP_PROGRAM_ID;
+use crate::constants::PUMP_TOKEN_DECIMALS;
+use crate::constants::PUMP_TOKEN_TOTAL_SUPPLY;
+use crate::constants::WSOL_MINT_KEY;
+use crate::utils::lamports_to_sol;
+
+/// Stored prices are lamports per whole token multiplied by this factor, so sub-lamport
+/// bonding-curve prices survive the BIGINT columns of the price tables
+pub const PRICE_SCALE: u64 = 1_000_000_000;
+
+/// Redis key holding the latest `SolUsdPrice`
+pub const SOL_USD_PRICE_KEY: &str = "sol_usd_price";
+
+/// SOL/USD reference price and where it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SolUsdPrice {
+    pub price: f64,
+    pub confidence: Option<f64>,
+    pub source: String,
+    pub publish_time: i64,
+}
+
+impl SolUsdPrice {
+    pub fn is_fresh(
+        &self,
+        max_age_secs: i64,
+    ) -> bool {
+        chrono::Utc::now().timestamp() - self.publish_time <= max_age_secs
+    }
+
+    pub fn to_usd(
+        &self,
+        sol: f64,
+    ) -> f64 {
+        sol * self.price
+    }
+}
+
+/// Bonding curve a mint is tracked through, keyed by the bonding curve address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedCurve {
+    pub mint: Pubkey,
+    pub created_at: u64,
+}
+
+pub type TrackedCurves = Arc<RwLock<HashMap<Pubkey, TrackedCurve>>>;
+
+/// Bonding curve PDA of a pump.fun mint
+pub fn derive_bonding_curve(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"bonding-curve", mint.as_ref()], &PUMP_FUN_PROGRAM_ID).0
+}
+
+/// PumpSwap pool a graduated mint is priced through, keyed by the pool address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedPool {
+    pub mint: Pubkey,
+    pub bonded_at: u64,
+}
+
+pub type TrackedPools = Arc<RwLock<HashMap<Pubkey, TrackedPool>>>;
+
+/// Canonical mint/WSOL PumpSwap pool that pump.fun creates when a bonding curve completes
+pub fn derive_pump_swap_pool(mint: &Pubkey) -> Pubkey {
+    let pool_authority = Pubkey::find_program_address(&[b"pool-authority", mint.as_ref()], &PUMP_FUN_PROGRAM_ID).0;
+    Pubkey::find_program_address(
+        &[
+            b"pool",
+            &0u16.to_le_bytes(),
+            pool_authority.as_ref(),
+            mint.as_ref(),
+            WSOL_MINT_KEY.as_ref(),
+        ],
+        &PUMP_SWAP_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Spot price in SOL per whole token of a SOL/token reserve pair
+pub fn reserves_price_sol(
+    sol_reserves: u64,
+    token_reserves: u64,
+) -> f64 {
+    if token_reserves == 0 {
+        return 0.0;
+    }
+    let tokens = token_reserves as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32);
+    lamports_to_sol(sol_reserves) / tokens
+}
+
+/// Spot price in lamports per whole token of a SOL/token reserve pair, scaled by `PRICE_SCALE`
+pub fn reserves_scaled_price(
+    sol_reserves: u64,
+    token_reserves: u64,
+) -> u64 {
+    if token_reserves == 0 {
+        return 0;
+    }
+    let numerator = sol_reserves as u128 * 10u128.pow(PUMP_TOKEN_DECIMALS) * PRICE_SCALE as u128;
+    (numerator / token_reserves as u128).min(u64::MAX as u128) as u64
+}
+
+/// Where a token price was read from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PriceSource {
+    #[default]
+    BondingCurve,
+    PumpSwap,
+}
+
+/// Reserves decoded from a pump.fun bonding curve account update
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BondingCurveState {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    pub slot: u64,
+    // Block time of the slot the account changed in
+    pub timestamp: i64,
+}
+
+impl BondingCurveState {
+    /// Spot price in SOL per whole token
+    pub fn price_sol(&self) -> f64 {
+        reserves_price_sol(self.virtual_sol_reserves, self.virtual_token_reserves)
+    }
+
+    /// Spot price in lamports per whole token, scaled by `PRICE_SCALE`
+    pub fn scaled_price(&self) -> u64 {
+        reserves_scaled_price(self.virtual_sol_reserves, self.virtual_token_reserves)
+    }
+
+    /// Fully diluted market cap in SOL
+    pub fn market_cap_sol(&self) -> f64 {
+        let supply = self.token_total_supply as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32);
+        self.price_sol() * supply
+    }
+}
+
+/// Volume of a pump.fun trade, decoded from its `TradeEvent`. The price comes from the curve account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurveTradeState {
+    pub mint: Pubkey,
+    // Lamports paid or received by the trader
+    pub sol_volume: u64,
+    // Signature and instruction path, unique per trade even when a transaction trades twice
+    pub trade_id: String,
+    pub slot: u64,
+    // Block time of the trade
+    pub timestamp: i64,
+}
+
+// Slots kept by `SlotClock`, about half an hour of blocks
+const SLOT_CLOCK_CAPACITY: usize = 4_096;
+// Target slot duration, to date slots whose block hasn't been seen
+const SLOT_DURATION_MS: i64 = 400;
+
+/// Block times seen on the block stream by slot. Account updates only carry their slot, this dates
+/// them on-chain
+#[derive(Debug, Clone, Default)]
+pub struct SlotClock {
+    block_times: Arc<RwLock<BTreeMap<u64, i64>>>,
+}
+
+impl SlotClock {
+    pub async fn record(
+        &self,
+        slot: u64,
+        block_time: i64,
+    ) {
+        let mut block_times = self.block_times.write().await;
+        block_times.insert(slot, block_time);
+        while block_times.len() > SLOT_CLOCK_CAPACITY {
+            block_times.pop_first();
+        }
+    }
+
+    /// Block time of `slot`, extrapolated from the nearest slot seen when its own block wasn't
+    pub async fn block_time(
+        &self,
+        slot: u64,
+    ) -> Option<i64> {
+        let block_times = self.block_times.read().await;
+        if let Some((&seen, &block_time)) = block_times.range(..=slot).next_back() {
+            return Some(block_time + (slot - seen) as i64 * SLOT_DURATION_MS / 1_000);
+        }
+        block_times
+            .range(slot..)
+            .next()
+            .map(|(&seen, &block_time)| block_time - (seen - slot) as i64 * SLOT_DURATION_MS / 1_000)
+    }
+}
+
+/// Pool reserves right after a PumpSwap trade, decoded from its buy or sell event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PoolSwapState {
+    pub mint: Pubkey,
+    pub pool: Pubkey,
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+    // Lamports paid or received by the trader
+    pub quote_volume: u64,
+    // Signature and instruction path, unique per swap even when a transaction swaps twice
+    pub trade_id: String,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+impl PoolSwapState {
+    /// Spot price in SOL per whole token
+    pub fn price_sol(&self) -> f64 {
+        reserves_price_sol(self.quote_reserves, self.base_reserves)
+    }
+
+    /// Spot price in lamports per whole token, scaled by `PRICE_SCALE`
+    pub fn scaled_price(&self) -> u64 {
+        reserves_scaled_price(self.quote_reserves, self.base_reserves)
+    }
+
+    /// Fully diluted market cap in SOL
+    pub fn market_cap_sol(&self) -> f64 {
+        let supply = PUMP_TOKEN_TOTAL_SUPPLY as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32);
+        self.price_sol() * supply
+    }
+
+    /// Value of both sides of the pool in SOL
+    pub fn liquidity_sol(&self) -> f64 {
+        lamports_to_sol(self.quote_reserves) * 2.0
+    }
+}
+
+/// Latest known price of a token, cached in Redis under `latest_price:{mint}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPrice {
+    pub mint: Pubkey,
+    pub price: u64,
+    pub price_sol: f64,
+    pub market_cap_sol: f64,
+    #[serde(default)]
+    pub price_usd: Option<f64>,
+    #[serde(default)]
+    pub market_cap_usd: Option<f64>,
+    // SOL locked in the curve, or both sides of the pool once graduated
+    #[serde(default)]
+    pub liquidity_sol: Option<f64>,
+    #[serde(default)]
+    pub source: PriceSource,
+    // Pool reserves once the token trades on PumpSwap
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub complete: bool,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+impl TokenPrice {
+    pub fn from_bonding_curve(state: &BondingCurveState) -> Self {
+        Self {
+            mint: state.mint,
+            price: state.scaled_price(),
+            price_sol: state.price_sol(),
+            market_cap_sol: state.market_cap_sol(),
+            price_usd: None,
+            market_cap_usd: None,
+            liquidity_sol: Some(lamports_to_sol(state.real_sol_reserves)),
+            source: PriceSource::BondingCurve,
+            virtual_sol_reserves: state.virtual_sol_reserves,
+            virtual_token_reserves: state.virtual_token_reserves,
+            complete: state.complete,
+            slot: state.slot,
+            timestamp: state.timestamp,
+        }
+    }
+
+    pub fn from_pool_swap(state: &PoolSwapState) -> Self {
+        Self {
+            mint: state.mint,
+            price: state.scaled_price(),
+            price_sol: state.price_sol(),
+            market_cap_sol: state.market_cap_sol(),
+            price_usd: None,
+            market_cap_usd: None,
+            liquidity_sol: Some(state.liquidity_sol()),
+            source: PriceSource::PumpSwap,
+            virtual_sol_reserves: state.quote_reserves,
+            virtual_token_reserves: state.base_reserves,
+            complete: true,
+            slot: state.slot,
+            timestamp: state.timestamp,
+        }
+    }
+
+    pub fn with_usd(
+        mut self,
+        sol_usd: Option<&SolUsdPrice>,
+    ) -> Self {
+        self.price_usd = sol_usd.map(|p| p.to_usd(self.price_sol));
+        self.market_cap_usd = sol_usd.map(|p| p.to_usd(self.market_cap_sol));
+        self
+    }
+
+    pub fn redis_key(mint: &Pubkey) -> String {
+        format!("latest_price:{}", mint)
+    }
+}
+
+/// All-time high of a token, cached in Redis under `token_ath:{mint}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenAth {
+    pub mint: Pubkey,
+    pub price: u64,
+    pub price_sol: f64,
+    pub market_cap_sol: f64,
+    pub market_cap_usd: Option<f64>,
+    pub at: i64,
+    // ATH at the time the last `token_new_ath` event went out
+    pub announced_price: u64,
+}
+
+impl TokenAth {
+    pub fn from_price(price: &TokenPrice) -> Self {
+        Self {
+            mint: price.mint,
+            price: price.price,
+            price_sol: price.price_sol,
+            market_cap_sol: price.market_cap_sol,
+            market_cap_usd: price.market_cap_usd,
+            at: price.timestamp,
+            announced_price: price.price,
+        }
+    }
+
+    /// ATH read back from `tokens`, which keeps only the scaled price and when it was set
+    pub fn from_stored(
+        mint: Pubkey,
+        price: u64,
+        at: i64,
+    ) -> Self {
+        let price_sol = lamports_to_sol(price) / PRICE_SCALE as f64;
+        let supply = PUMP_TOKEN_TOTAL_SUPPLY as f64 / 10f64.powi(PUMP_TOKEN_DECIMALS as i32);
+        Self {
+            mint,
+            price,
+            price_sol,
+            market_cap_sol: price_sol * supply,
+            market_cap_usd: None,
+            at,
+            // Announced before the restart, or never worth announcing
+            announced_price: price,
+        }
+    }
+
+    /// How far the ATH rose above the last announced one, in percent
+    pub fn rise_over_announced_pct(&self) -> f64 {
+        if self.announced_price == 0 {
+            return 0.0;
+        }
+        (self.price as f64 / self.announced_price as f64 - 1.0) * 100.0
+    }
+
+    pub fn redis_key(mint: &Pubkey) -> String {
+        format!("token_ath:{}", mint)
+    }
+}
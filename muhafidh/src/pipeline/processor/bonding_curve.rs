@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use carbon_core::account::AccountProcessorInputType;
+use carbon_core::error::CarbonResult;
+use carbon_core::metrics::MetricsCollection;
+use carbon_core::processor::Processor;
+use carbon_pumpfun_decoder::accounts::PumpfunAccount;
+use tracing::debug;
+use tracing::error;
+
+use crate::handler::price::token::TokenPriceHandlerOperator;
+use crate::model::price::BondingCurveState;
+use crate::model::price::SlotClock;
+use crate::model::price::TrackedCurves;
+use crate::model::price::TrackedPool;
+use crate::model::price::TrackedPools;
+use crate::model::price::derive_pump_swap_pool;
+
+pub struct BondingCurveAccountProcessor {
+    price_handler: Arc<TokenPriceHandlerOperator>,
+    tracked: TrackedCurves,
+    tracked_pools: TrackedPools,
+    slot_clock: SlotClock,
+}
+
+impl BondingCurveAccountProcessor {
+    pub fn new(
+        price_handler: Arc<TokenPriceHandlerOperator>,
+        tracked: TrackedCurves,
+        tracked_pools: TrackedPools,
+        slot_clock: SlotClock,
+    ) -> Self {
+        Self {
+            price_handler,
+            tracked,
+            tracked_pools,
+            slot_clock,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Processor for BondingCurveAccountProcessor {
+    type InputType = AccountProcessorInputType<PumpfunAccount>;
+
+    async fn process(
+        &mut self,
+        data: Self::InputType,
+        _metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let (meta, account, _raw_account) = data;
+        let PumpfunAccount::BondingCurve(curve) = account.data else {
+            return Ok(());
+        };
+
+        // The program subscription sees every bonding curve, only price the ones we track
+        let Some(tracked) = self.tracked.read().await.get(&meta.pubkey).cloned() else {
+            return Ok(());
+        };
+
+        // The account update only carries its slot, date it by the slot's block time
+        let timestamp = self
+            .slot_clock
+            .block_time(meta.slot)
+            .await
+            .unwrap_or_else(|| chrono::Utc::now().timestamp());
+
+        let state = BondingCurveState {
+            mint: tracked.mint,
+            bonding_curve: meta.pubkey,
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            virtual_token_reserves: curve.virtual_token_reserves,
+            real_sol_reserves: curve.real_sol_reserves,
+            real_token_reserves: curve.real_token_reserves,
+            token_total_supply: curve.token_total_supply,
+            complete: curve.complete,
+            slot: meta.slot,
+            timestamp,
+        };
+
+        // A completed curve migrates to PumpSwap, keep pricing the mint through its pool. It bonded at the
+        // block time of the slot that completed it
+        if curve.complete {
+            let pool = derive_pump_swap_pool(&tracked.mint);
+            debug!("bonding_curve_complete::mint::{}::pool::{}::slot::{}", tracked.mint, pool, meta.slot);
+            self.tracked.write().await.remove(&meta.pubkey);
+            self.tracked_pools.write().await.insert(pool, TrackedPool {
+                mint: tracked.mint,
+                bonded_at: timestamp.max(0) as u64,
+            });
+        }
+
+        if let Err(e) = self.price_handler.update_bonding_curve(state).await {
+            error!("update_bonding_curve_failed::mint::{}::error::{}", tracked.mint, e);
+        }
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ use tracing::error;
 use crate::config::CreatorAnalyzerConfig;
 use crate::config::RpcConfig;
 use crate::handler::token::creator::CreatorHandlerOperator;
+use crate::model::creator::crawl::CrawlGuard;
 use crate::model::creator::metadata::CreatorMetadata;
 use crate::utils::lamports_to_sol;
 
@@ -27,6 +28,8 @@ pub struct CreatorInstructionProcessor {
     creator_analyzer_config: Arc<CreatorAnalyzerConfig>,
     rpc_config: Arc<RpcConfig>,
     current_depth: Arc<RwLock<usize>>,
+    // Keeps the crawl alive while this level's pipeline runs
+    crawl: CrawlGuard,
 }
 
 impl CreatorInstructionProcessor {
@@ -37,6 +40,7 @@ impl CreatorInstructionProcessor {
         creator_analyzer_config: Arc<CreatorAnalyzerConfig>,
         rpc_config: Arc<RpcConfig>,
         current_depth: Arc<RwLock<usize>>,
+        crawl: CrawlGuard,
     ) -> Self {
         Self {
             creator_metadata,
@@ -45,6 +49,7 @@ impl CreatorInstructionProcessor {
             creator_analyzer_config,
             rpc_config,
             current_depth,
+            crawl,
         }
     }
 
@@ -135,9 +140,16 @@ impl Processor for CreatorInstructionProcessor {
                         let destination_idx =
                             self.creator_metadata.wallet_connection.add_node(destination, false).await;
 
+                        let transfer_id = format!("{}:{:?}", meta.transaction_metadata.signature, meta.absolute_path);
                         self.creator_metadata
                             .wallet_connection
-                            .add_edge(source_idx, destination_idx, amount, chrono::Utc::now().timestamp_millis())
+                            .add_edge(
+                                source_idx,
+                                destination_idx,
+                                amount,
+                                chrono::Utc::now().timestamp_millis(),
+                                transfer_id,
+                            )
                             .await;
                         let depth = self.get_current_depth().await;
                         creator_metadata.push_to_queue((source, depth + 1, analyzed_account)).await;
@@ -158,6 +170,7 @@ impl Processor for CreatorInstructionProcessor {
                                 cancellation_token,
                                 creator_analyzer_config,
                                 depth,
+                                self.crawl.clone(),
                             )
                             .await
                         {
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use carbon_core::error::CarbonResult;
+use carbon_core::instruction::InstructionProcessorInputType;
+use carbon_core::metrics::MetricsCollection;
+use carbon_core::processor::Processor;
+use carbon_pumpfun_decoder::instructions::PumpfunInstruction;
+use tracing::error;
+
+use crate::handler::price::token::TokenPriceHandlerOperator;
+use crate::model::price::CurveTradeState;
+use crate::model::price::SlotClock;
+use crate::model::price::TrackedCurves;
+use crate::model::price::TrackedPools;
+use crate::model::price::derive_bonding_curve;
+use crate::model::price::derive_pump_swap_pool;
+
+pub struct CurveTradeEventProcessor {
+    price_handler: Arc<TokenPriceHandlerOperator>,
+    tracked: TrackedCurves,
+    tracked_pools: TrackedPools,
+    slot_clock: SlotClock,
+}
+
+impl CurveTradeEventProcessor {
+    pub fn new(
+        price_handler: Arc<TokenPriceHandlerOperator>,
+        tracked: TrackedCurves,
+        tracked_pools: TrackedPools,
+        slot_clock: SlotClock,
+    ) -> Self {
+        Self {
+            price_handler,
+            tracked,
+            tracked_pools,
+            slot_clock,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Processor for CurveTradeEventProcessor {
+    type InputType = InstructionProcessorInputType<PumpfunInstruction>;
+
+    async fn process(
+        &mut self,
+        data: Self::InputType,
+        _metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let (meta, instruction, _nested_instructions, _solana_instruction) = data;
+        let PumpfunInstruction::TradeEvent(trade) = &instruction.data else {
+            return Ok(());
+        };
+
+        // Any trade dates its slot, so curve account updates of the same slot get its block time
+        self.slot_clock.record(meta.transaction_metadata.slot, trade.timestamp).await;
+
+        // Every pump.fun trade shows up in the block stream, only count volume of tracked mints. The trade
+        // that completes a curve can land after the account update moved the mint over to its pool
+        let tracked = self.tracked.read().await.contains_key(&derive_bonding_curve(&trade.mint))
+            || self
+                .tracked_pools
+                .read()
+                .await
+                .contains_key(&derive_pump_swap_pool(&trade.mint));
+        if !tracked {
+            return Ok(());
+        }
+
+        let state = CurveTradeState {
+            mint: trade.mint,
+            sol_volume: trade.sol_amount,
+            trade_id: format!("{}:{:?}", meta.transaction_metadata.signature, meta.absolute_path),
+            slot: meta.transaction_metadata.slot,
+            timestamp: trade.timestamp,
+        };
+
+        if let Err(e) = self.price_handler.update_curve_trade(state).await {
+            error!("update_curve_trade_failed::mint::{}::error::{}", trade.mint, e);
+        }
+        Ok(())
+    }
+}
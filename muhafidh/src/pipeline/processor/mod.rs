@@ -1,3 +1,6 @@
+pub mod bonding_curve;
 pub mod creator;
+pub mod curve_trade;
+pub mod pump_swap;
 pub mod pumpfun;
 pub mod raydium_launchpad;
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use carbon_core::error::CarbonResult;
+use carbon_core::instruction::InstructionProcessorInputType;
+use carbon_core::metrics::MetricsCollection;
+use carbon_core::processor::Processor;
+use carbon_pump_swap_decoder::instructions::PumpSwapInstruction;
+use tracing::error;
+
+use crate::handler::price::token::TokenPriceHandlerOperator;
+use crate::model::price::PoolSwapState;
+use crate::model::price::TrackedPools;
+
+pub struct PumpSwapEventProcessor {
+    price_handler: Arc<TokenPriceHandlerOperator>,
+    tracked_pools: TrackedPools,
+}
+
+impl PumpSwapEventProcessor {
+    pub fn new(
+        price_handler: Arc<TokenPriceHandlerOperator>,
+        tracked_pools: TrackedPools,
+    ) -> Self {
+        Self {
+            price_handler,
+            tracked_pools,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Processor for PumpSwapEventProcessor {
+    type InputType = InstructionProcessorInputType<PumpSwapInstruction>;
+
+    async fn process(
+        &mut self,
+        data: Self::InputType,
+        _metrics: Arc<MetricsCollection>,
+    ) -> CarbonResult<()> {
+        let (meta, instruction, _nested_instructions, _solana_instruction) = data;
+
+        // Events carry the pool reserves from before the trade, move them by the traded amounts
+        let (pool, base_reserves, quote_reserves, quote_volume, timestamp) = match &instruction.data {
+            PumpSwapInstruction::BuyEvent(event) => (
+                event.pool,
+                event.pool_base_token_reserves.saturating_sub(event.base_amount_out),
+                event.pool_quote_token_reserves.saturating_add(event.quote_amount_in),
+                event.user_quote_amount_in,
+                event.timestamp,
+            ),
+            PumpSwapInstruction::SellEvent(event) => (
+                event.pool,
+                event.pool_base_token_reserves.saturating_add(event.base_amount_in),
+                event.pool_quote_token_reserves.saturating_sub(event.quote_amount_out),
+                event.user_quote_amount_out,
+                event.timestamp,
+            ),
+            _ => return Ok(()),
+        };
+
+        // Every PumpSwap pool shows up in the block stream, only price graduated mints we track
+        let Some(tracked) = self.tracked_pools.read().await.get(&pool).cloned() else {
+            return Ok(());
+        };
+
+        let state = PoolSwapState {
+            mint: tracked.mint,
+            pool,
+            base_reserves,
+            quote_reserves,
+            quote_volume,
+            trade_id: format!("{}:{:?}", meta.transaction_metadata.signature, meta.absolute_path),
+            slot: meta.transaction_metadata.slot,
+            timestamp,
+        };
+
+        if let Err(e) = self.price_handler.update_pool_swap(state).await {
+            error!("update_pool_swap_failed::mint::{}::error::{}", tracked.mint, e);
+        }
+        Ok(())
+    }
+}
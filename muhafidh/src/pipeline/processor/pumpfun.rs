@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use carbon_core::deserialize::ArrangeAccounts;
@@ -7,19 +8,62 @@ use carbon_core::metrics::MetricsCollection;
 use carbon_core::processor::Processor;
 use carbon_pumpfun_decoder::instructions::PumpfunInstruction;
 use carbon_pumpfun_decoder::instructions::create::Create;
+use solana_pubkey::Pubkey;
 use tracing::error;
 
+use crate::handler::token::dump::DevDumpWatcherOperator;
 use crate::handler::token::metadata::TokenHandlerMetadataOperator;
+use crate::model::bundle::EarlyBuy;
+use crate::model::bundle::LaunchWindow;
+use crate::model::dump::LaunchTrade;
 use crate::model::platform::Platform;
 
 pub struct PfProgramInstructionProcessor {
     token_handler: Arc<TokenHandlerMetadataOperator>,
+    // Launches still inside their bundle window, keyed by mint
+    launch_windows: HashMap<Pubkey, LaunchWindow>,
+    bundle_window_slots: u64,
+    dev_dump_watcher: Arc<DevDumpWatcherOperator>,
+    // Launch time of mints still inside their dev dump window
+    watched_launches: HashMap<Pubkey, i64>,
+    dev_dump_window_secs: i64,
 }
 
 impl PfProgramInstructionProcessor {
-    pub fn new(token_handler: Arc<TokenHandlerMetadataOperator>) -> Self {
+    pub fn new(
+        token_handler: Arc<TokenHandlerMetadataOperator>,
+        bundle_window_slots: u64,
+        dev_dump_watcher: Arc<DevDumpWatcherOperator>,
+        dev_dump_window_secs: u64,
+    ) -> Self {
         Self {
             token_handler,
+            launch_windows: HashMap::new(),
+            bundle_window_slots,
+            dev_dump_watcher,
+            watched_launches: HashMap::new(),
+            dev_dump_window_secs: dev_dump_window_secs as i64,
+        }
+    }
+
+    // Hand every window the stream has moved past over for storage
+    async fn close_launch_windows(
+        &mut self,
+        slot: u64,
+    ) {
+        let closed: Vec<Pubkey> = self
+            .launch_windows
+            .values()
+            .filter(|window| !window.is_open(slot))
+            .map(|window| window.mint)
+            .collect();
+        for mint in closed {
+            let Some(window) = self.launch_windows.remove(&mint) else {
+                continue;
+            };
+            if let Err(e) = self.token_handler.store_launch_window(window).await {
+                error!("store_launch_window_failed::{}: {}", mint, e);
+            }
         }
     }
 }
@@ -34,6 +78,9 @@ impl Processor for PfProgramInstructionProcessor {
         _metrics: Arc<MetricsCollection>,
     ) -> CarbonResult<()> {
         let (meta, instruction, _nested_instructions, _solana_instruction) = data;
+        let slot = meta.transaction_metadata.slot;
+        self.close_launch_windows(slot).await;
+
         match &instruction.data {
             PumpfunInstruction::Create(account_meta) => {
                 // process_account_meta(account_meta);
@@ -55,6 +102,61 @@ impl Processor for PfProgramInstructionProcessor {
                     {
                         error!("store_token_failed::{}: {}", accounts.mint, e);
                     }
+
+                    self.launch_windows.insert(
+                        accounts.mint,
+                        LaunchWindow::new(accounts.mint, account_meta.creator, slot, self.bundle_window_slots),
+                    );
+
+                    let launched_at = block_time as i64;
+                    let window_secs = self.dev_dump_window_secs;
+                    self.watched_launches
+                        .retain(|_, watched_at| launched_at - *watched_at <= window_secs);
+                    self.watched_launches.insert(accounts.mint, launched_at);
+                    if let Err(e) = self
+                        .dev_dump_watcher
+                        .watch_launch(accounts.mint, account_meta.creator, launched_at)
+                        .await
+                    {
+                        error!("watch_launch_failed::{}: {}", accounts.mint, e);
+                    }
+                }
+            },
+            PumpfunInstruction::TradeEvent(trade) => {
+                // Buys landing in a launch's first slots, including the dev buy bundled with `Create`
+                if trade.is_buy {
+                    if let Some(window) = self.launch_windows.get_mut(&trade.mint) {
+                        window.buys.push(EarlyBuy {
+                            signature: meta.transaction_metadata.signature.to_string(),
+                            buyer: trade.user,
+                            fee_payer: meta.transaction_metadata.fee_payer,
+                            slot,
+                            sol_amount: trade.sol_amount,
+                            token_amount: trade.token_amount,
+                        });
+                    }
+                }
+
+                // Every trade on a watched launch, so the watcher can size the creator cluster's holdings
+                if let Some(launched_at) = self.watched_launches.get(&trade.mint).copied() {
+                    if trade.timestamp - launched_at > self.dev_dump_window_secs {
+                        self.watched_launches.remove(&trade.mint);
+                    } else if let Err(e) = self
+                        .dev_dump_watcher
+                        .record_trade(LaunchTrade {
+                            mint: trade.mint,
+                            wallet: trade.user,
+                            is_buy: trade.is_buy,
+                            sol_amount: trade.sol_amount,
+                            token_amount: trade.token_amount,
+                            virtual_sol_reserves: trade.virtual_sol_reserves,
+                            virtual_token_reserves: trade.virtual_token_reserves,
+                            timestamp: trade.timestamp,
+                        })
+                        .await
+                    {
+                        error!("record_launch_trade_failed::{}: {}", trade.mint, e);
+                    }
                 }
             },
             _ => {},
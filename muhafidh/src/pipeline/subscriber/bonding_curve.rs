@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use carbon_core::pipeline::Pipeline;
+use carbon_core::pipeline::ShutdownStrategy;
+use carbon_log_metrics::LogMetrics;
+use carbon_pumpfun_decoder::PumpfunDecoder;
+use carbon_rpc_program_subscribe_datasource::Filters;
+use carbon_rpc_program_subscribe_datasource::RpcProgramSubscribe;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::rpc_config::RpcAccountInfoConfig;
+use solana_client::rpc_config::RpcProgramAccountsConfig;
+use solana_client::rpc_filter::Memcmp;
+use solana_client::rpc_filter::RpcFilterType;
+use solana_commitment_config::CommitmentConfig;
+use tracing::debug;
+use tracing::info;
+
+use crate::constants::PUMP_BONDING_CURVE_DISCRIMINATOR;
+use crate::constants::PUMP_FUN_PROGRAM_ID;
+use crate::engine::siraaj::Siraaj;
+use crate::pipeline::processor::bonding_curve::BondingCurveAccountProcessor;
+
+pub fn make_bonding_curve_subscriber_pipeline(siraaj: &Siraaj) -> Result<Pipeline> {
+    let ws_url = siraaj.config.rpc.get_ws_url();
+
+    info!("siraaj::bonding_curve::subscriber::ws_url: {}", ws_url);
+
+    // Only bonding curve accounts, the processor narrows them down to tracked mints
+    let filters = Filters::new(
+        PUMP_FUN_PROGRAM_ID,
+        Some(RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                0,
+                &PUMP_BONDING_CURVE_DISCRIMINATOR,
+            ))]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(CommitmentConfig::confirmed()),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        }),
+    );
+    debug!("siraaj::bonding_curve::subscriber::filters: {:?}", filters);
+
+    let rpc_program_subscribe = RpcProgramSubscribe::new(ws_url, filters);
+    let pipeline = Pipeline::builder()
+        .datasource(rpc_program_subscribe)
+        .metrics(Arc::new(LogMetrics::new()))
+        .metrics_flush_interval(3)
+        .account(
+            PumpfunDecoder,
+            BondingCurveAccountProcessor::new(
+                siraaj.price_handler.clone(),
+                siraaj.tracked.clone(),
+                siraaj.tracked_pools.clone(),
+                siraaj.slot_clock.clone(),
+            ),
+        )
+        .shutdown_strategy(ShutdownStrategy::Immediate)
+        .build()?;
+
+    Ok(pipeline)
+}
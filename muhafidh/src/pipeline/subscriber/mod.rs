@@ -1 +1,4 @@
+pub mod bonding_curve;
+pub mod curve_trade;
+pub mod pump_swap;
 pub mod pumpfun;
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use carbon_core::pipeline::Pipeline;
+use carbon_core::pipeline::ShutdownStrategy;
+use carbon_log_metrics::LogMetrics;
+use carbon_pump_swap_decoder::PumpSwapDecoder;
+use carbon_rpc_block_subscribe_datasource::Filters;
+use carbon_rpc_block_subscribe_datasource::RpcBlockSubscribe;
+use solana_client::rpc_config::RpcBlockSubscribeConfig;
+use solana_client::rpc_config::RpcBlockSubscribeFilter;
+use solana_commitment_config::CommitmentConfig;
+use tracing::debug;
+use tracing::info;
+
+use crate::constants::PUMP_SWAP_PROGRAM_ID;
+use crate::engine::siraaj::Siraaj;
+use crate::pipeline::processor::pump_swap::PumpSwapEventProcessor;
+
+pub fn make_pump_swap_subscriber_pipeline(siraaj: &Siraaj) -> Result<Pipeline> {
+    let ws_url = siraaj.config.rpc.get_ws_url();
+
+    info!("siraaj::pump_swap::subscriber::ws_url: {}", ws_url);
+
+    let filters = Filters::new(
+        RpcBlockSubscribeFilter::MentionsAccountOrProgram(PUMP_SWAP_PROGRAM_ID.to_string()),
+        Some(RpcBlockSubscribeConfig {
+            max_supported_transaction_version: Some(0),
+            commitment: Some(CommitmentConfig::confirmed()),
+            ..RpcBlockSubscribeConfig::default()
+        }),
+    );
+    debug!("siraaj::pump_swap::subscriber::filters: {:?}", filters);
+
+    let rpc_block_subscribe = RpcBlockSubscribe::new(ws_url, filters);
+    let pipeline = Pipeline::builder()
+        .datasource(rpc_block_subscribe)
+        .metrics(Arc::new(LogMetrics::new()))
+        .metrics_flush_interval(3)
+        .instruction(
+            PumpSwapDecoder,
+            PumpSwapEventProcessor::new(siraaj.price_handler.clone(), siraaj.tracked_pools.clone()),
+        )
+        .shutdown_strategy(ShutdownStrategy::Immediate)
+        .build()?;
+
+    Ok(pipeline)
+}
@@ -37,7 +37,15 @@ pub fn make_pumpfun_subscriber_pipeline(raqib: Raqib) -> Result<Pipeline> {
         .datasource(rpc_program_subscribe)
         .metrics(Arc::new(LogMetrics::new()))
         .metrics_flush_interval(3)
-        .instruction(PumpfunDecoder, PfProgramInstructionProcessor::new(raqib.token_handler.clone()))
+        .instruction(
+            PumpfunDecoder,
+            PfProgramInstructionProcessor::new(
+                raqib.token_handler.clone(),
+                raqib.config.launch.bundle_window_slots,
+                raqib.dev_dump_watcher.clone(),
+                raqib.config.launch.dev_dump_window_secs,
+            ),
+        )
         .shutdown_strategy(ShutdownStrategy::Immediate)
         .build()?;
 
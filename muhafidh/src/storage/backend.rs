@@ -0,0 +1,912 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Instant;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use solana_pubkey::Pubkey;
+use tracing::error;
+use tracing::warn;
+
+use crate::RedisClientError;
+use crate::Result;
+use crate::err_with_loc;
+use crate::model::alert::AlertHit;
+use crate::model::alert::AlertRule;
+use crate::model::bundle::BundleAnalysis;
+use crate::model::bundle::LaunchWindow;
+use crate::model::creator::graph::CreatorConnectionGraph;
+use crate::model::creator::metadata::CreatorMetadata;
+use crate::model::token::TokenMetadata;
+use crate::storage::memory::InMemoryStorage;
+use crate::storage::postgres::PostgresClient;
+use crate::storage::postgres::alert::AlertDb;
+use crate::storage::postgres::db::TokenMetadataDb;
+use crate::storage::postgres::graph::GraphDb;
+use crate::storage::postgres::launch::LaunchDb;
+use crate::storage::postgres::model::Candle;
+use crate::storage::postgres::model::CandleInterval;
+use crate::storage::postgres::model::CreatorReputationEvent;
+use crate::storage::postgres::model::HolderSnapshot;
+use crate::storage::postgres::time_series::TimeSeriesDb;
+use crate::storage::redis::RedisClient;
+use crate::storage::redis::TokenMetadataCoordinator;
+use crate::storage::redis::TokenMetadataKv;
+use crate::storage::redis::TokenMetadataQueue;
+use crate::storage::redis::TokenMetadataStream;
+use crate::storage::redis::queue::AccountLease;
+use crate::storage::redis::stream::StreamEvent;
+
+/// Token records: metadata, CEX funding, bonding and all-time highs
+#[async_trait::async_trait]
+pub trait TokenStore: Send + Sync + Debug {
+    async fn insert_token_metadata(
+        &self,
+        token: &TokenMetadata,
+    ) -> Result<()>;
+
+    async fn get_token_by_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<TokenMetadata>>;
+
+    async fn update_token_cex_sources(
+        &self,
+        mint: &Pubkey,
+        cex_sources: &[Pubkey],
+        cex_updated_at: u64,
+    ) -> Result<()>;
+
+    /// Returns false when the mint was already recorded for this CEX
+    async fn record_cex_activity(
+        &self,
+        cex_name: &str,
+        cex_address: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<bool>;
+
+    async fn update_token_ath(
+        &self,
+        mint: &Pubkey,
+        ath_price: u64,
+        ath_at: u64,
+    ) -> Result<()>;
+
+    async fn mark_token_bonded(
+        &self,
+        mint: &Pubkey,
+        bonded_at: u64,
+    ) -> Result<()>;
+
+    async fn get_bonded_tokens_since(
+        &self,
+        platform: &str,
+        bonded_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>>;
+
+    async fn get_unbonded_tokens_since(
+        &self,
+        platform: &str,
+        created_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>>;
+
+    async fn get_cex_funded_tokens_since(
+        &self,
+        created_after: u64,
+    ) -> Result<Vec<Pubkey>>;
+}
+
+/// Creator funding graphs, stored per mint
+#[async_trait::async_trait]
+pub trait GraphStore: Send + Sync + Debug {
+    async fn store_connection_graph(
+        &self,
+        mint: &Pubkey,
+        connection_graph: &CreatorConnectionGraph,
+    ) -> Result<()>;
+
+    /// The funding graphs of several mints merged into one
+    async fn load_connection_graph(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<CreatorConnectionGraph>;
+}
+
+/// Price, volume, candle, launch and holder history
+#[async_trait::async_trait]
+pub trait TimeSeriesStore: Send + Sync + Debug {
+    /// One row per `trade_id`; writing the same trade again overwrites it
+    async fn add_token_price(
+        &self,
+        mint: &str,
+        price: u64,
+        timestamp: i64,
+        trade_id: &str,
+    ) -> Result<()>;
+
+    async fn add_token_volume(
+        &self,
+        mint: &str,
+        volume: u64,
+        timestamp: i64,
+        trade_id: &str,
+    ) -> Result<()>;
+
+    async fn record_cex_launch(
+        &self,
+        cex_address: &str,
+        launched_at: i64,
+    ) -> Result<()>;
+
+    /// Recompute the candles overlapping `from..=to` from stored trades, whole buckets at a time
+    async fn rebuild_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64>;
+
+    /// Recompute the `interval` candles overlapping `from..=to` from the stored 1m candles
+    async fn rollup_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64>;
+
+    async fn get_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<Candle>>;
+
+    async fn insert_holder_snapshot(
+        &self,
+        snapshot: &HolderSnapshot,
+    ) -> Result<()>;
+
+    async fn get_holder_snapshots(
+        &self,
+        mint: &Pubkey,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<HolderSnapshot>>;
+}
+
+/// Key-value cache of JSON documents, typed access goes through `KvCacheExt`
+#[async_trait::async_trait]
+pub trait KvCache: Send + Sync + Debug {
+    async fn get_value(
+        &self,
+        key: &str,
+    ) -> Result<Option<Value>>;
+
+    async fn set_value(
+        &self,
+        key: &str,
+        value: &Value,
+    ) -> Result<()>;
+}
+
+/// Outgoing events, typed access goes through `EventBusExt`
+#[async_trait::async_trait]
+pub trait EventBus: Send + Sync + Debug {
+    /// Durable delivery: consumers that were down still receive the event
+    async fn publish_value(
+        &self,
+        channel: &str,
+        payload: &Value,
+    ) -> Result<()>;
+
+    /// Fire-and-forget delivery to whoever is listening right now
+    async fn broadcast_value(
+        &self,
+        channel: &str,
+        payload: &Value,
+    ) -> Result<()>;
+}
+
+/// Creator accounts waiting to be crawled, handed out under expiring leases
+#[async_trait::async_trait]
+pub trait WorkQueue: Send + Sync + Debug {
+    async fn add_unprocessed_account(
+        &self,
+        account: &CreatorMetadata,
+    ) -> Result<()>;
+
+    async fn add_failed_account(
+        &self,
+        failed: &CreatorMetadata,
+    ) -> Result<()>;
+
+    async fn lease_next_failed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>>;
+
+    async fn lease_next_unprocessed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>>;
+
+    async fn release_account_lease(
+        &self,
+        lease: &AccountLease,
+    ) -> Result<()>;
+
+    async fn heartbeat_account_leases(&self) -> Result<usize>;
+
+    async fn reap_expired_account_leases(&self) -> Result<usize>;
+
+    /// (failed, unprocessed)
+    async fn get_pending_account_counts(&self) -> Result<(usize, usize)>;
+
+    async fn get_in_flight_account_count(&self) -> Result<usize>;
+}
+
+/// Launch windows, their bundle findings and the creator outcomes recorded from them
+#[async_trait::async_trait]
+pub trait LaunchStore: Send + Sync + Debug {
+    /// Buys already stored for the mint are kept, the window itself is written once
+    async fn store_launch_window(
+        &self,
+        window: &LaunchWindow,
+    ) -> Result<()>;
+
+    async fn get_launch_window(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<LaunchWindow>>;
+
+    /// Replaces earlier findings; ignored while the mint has no stored window
+    async fn store_bundle_analysis(
+        &self,
+        analysis: &BundleAnalysis,
+        analyzed_at: u64,
+    ) -> Result<()>;
+
+    /// Funding graph wallets and suspected bundlers of each mint
+    async fn get_cluster_wallets(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<HashMap<Pubkey, Vec<Pubkey>>>;
+
+    /// Once per creator, mint and outcome
+    async fn record_creator_outcome(
+        &self,
+        event: &CreatorReputationEvent,
+    ) -> Result<()>;
+}
+
+/// Price alert rules and the hits they produced
+#[async_trait::async_trait]
+pub trait AlertStore: Send + Sync + Debug {
+    async fn create_alert_rule(
+        &self,
+        rule: &AlertRule,
+    ) -> Result<i32>;
+
+    async fn set_alert_rule_enabled(
+        &self,
+        rule_id: i32,
+        enabled: bool,
+    ) -> Result<()>;
+
+    /// In id order
+    async fn get_enabled_alert_rules(&self) -> Result<Vec<AlertRule>>;
+
+    async fn record_alert_hit(
+        &self,
+        hit: &AlertHit,
+    ) -> Result<()>;
+}
+
+/// Instances of one service: who is alive, and which instance analyzes which mint
+#[async_trait::async_trait]
+pub trait MintCoordinator: Send + Sync + Debug {
+    /// Owner recorded on this instance's locks and registry entry
+    fn worker(&self) -> &str;
+
+    /// False when another instance holds the lock, a lock this instance holds counts as taken
+    async fn try_lock_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<bool>;
+
+    async fn release_mint_lock(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<()>;
+
+    async fn renew_mint_locks(&self) -> Result<usize>;
+
+    async fn heartbeat_instance(&self) -> Result<()>;
+
+    async fn deregister_instance(&self) -> Result<()>;
+
+    async fn get_live_instances(&self) -> Result<Vec<String>>;
+
+    /// Each dead instance is returned to exactly one caller
+    async fn take_dead_instances(&self) -> Result<Vec<String>>;
+
+    async fn release_instance_mint_locks(
+        &self,
+        worker: &str,
+    ) -> Result<usize>;
+}
+
+/// Consuming side of `EventBus::publish_value`: entries stay pending for this service until acknowledged,
+/// typed reads go through `EventStreamExt`
+#[async_trait::async_trait]
+pub trait EventStream: Send + Sync + Debug {
+    async fn ensure_group(
+        &self,
+        channel: &str,
+    ) -> Result<()>;
+
+    /// Entries left pending once every claim interval (starting with the first call), new entries otherwise
+    async fn next_batch_values(
+        &self,
+        channel: &str,
+        last_claim: &mut Option<Instant>,
+    ) -> Result<Vec<StreamEvent<Value>>>;
+
+    async fn ack(
+        &self,
+        channel: &str,
+        id: &str,
+    ) -> Result<()>;
+
+    /// Returns whether the consumer was removed, it is kept while entries are pending for it
+    async fn remove_idle_consumer(
+        &self,
+        channel: &str,
+        consumer: &str,
+    ) -> Result<bool>;
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value> {
+    serde_json::to_value(value).map_err(|e| {
+        error!("serialize_value_failed: {}", e);
+        err_with_loc!(RedisClientError::SerializeError(e))
+    })
+}
+
+#[async_trait::async_trait]
+pub trait KvCacheExt: KvCache {
+    async fn get<T: DeserializeOwned + Send>(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>> {
+        match self.get_value(key).await? {
+            Some(value) => {
+                let value = serde_json::from_value(value).map_err(|e| {
+                    error!("deserialize_value_failed::{}: {}", key, e);
+                    err_with_loc!(RedisClientError::DeserializeError(e))
+                })?;
+                Ok(Some(value))
+            },
+            None => Ok(None),
+        }
+    }
+
+    async fn set<T: Serialize + Send + Sync>(
+        &self,
+        key: &str,
+        value: &T,
+    ) -> Result<()> {
+        self.set_value(key, &to_value(value)?).await
+    }
+}
+
+impl<C: KvCache + ?Sized> KvCacheExt for C {
+}
+
+#[async_trait::async_trait]
+pub trait EventBusExt: EventBus {
+    async fn publish<T: Serialize + Send + Sync>(
+        &self,
+        channel: &str,
+        payload: &T,
+    ) -> Result<()> {
+        self.publish_value(channel, &to_value(payload)?).await
+    }
+
+    async fn broadcast<T: Serialize + Send + Sync>(
+        &self,
+        channel: &str,
+        payload: &T,
+    ) -> Result<()> {
+        self.broadcast_value(channel, &to_value(payload)?).await
+    }
+}
+
+impl<B: EventBus + ?Sized> EventBusExt for B {
+}
+
+#[async_trait::async_trait]
+pub trait EventStreamExt: EventStream {
+    // Entries whose payload doesn't decode are acknowledged and dropped so they can't wedge the group
+    async fn next_batch<T: DeserializeOwned + Send>(
+        &self,
+        channel: &str,
+        last_claim: &mut Option<Instant>,
+    ) -> Result<Vec<StreamEvent<T>>> {
+        let values = self.next_batch_values(channel, last_claim).await?;
+        let mut events = Vec::with_capacity(values.len());
+        for event in values {
+            match serde_json::from_value::<T>(event.payload) {
+                Ok(payload) => events.push(StreamEvent {
+                    id: event.id,
+                    payload,
+                }),
+                Err(e) => {
+                    warn!("malformed_stream_entry::{}::id::{}::error::{}", channel, event.id, e);
+                    if let Err(e) = self.ack(channel, &event.id).await {
+                        error!("ack_malformed_stream_entry_failed::{}::id::{}::error::{}", channel, event.id, e);
+                    }
+                },
+            }
+        }
+        Ok(events)
+    }
+}
+
+impl<S: EventStream + ?Sized> EventStreamExt for S {
+}
+
+/// Every storage capability behind its trait, so handlers can run on Postgres and Redis or fully in-process
+#[derive(Debug, Clone)]
+pub struct StorageBackend {
+    pub tokens: Arc<dyn TokenStore>,
+    pub graphs: Arc<dyn GraphStore>,
+    pub time_series: Arc<dyn TimeSeriesStore>,
+    pub kv: Arc<dyn KvCache>,
+    pub events: Arc<dyn EventBus>,
+    pub work_queue: Arc<dyn WorkQueue>,
+    pub launches: Arc<dyn LaunchStore>,
+    pub alerts: Arc<dyn AlertStore>,
+    pub coordinator: Arc<dyn MintCoordinator>,
+    pub streams: Arc<dyn EventStream>,
+}
+
+impl StorageBackend {
+    pub fn new(
+        postgres: &PostgresClient,
+        redis: Arc<RedisClient>,
+    ) -> Self {
+        Self {
+            tokens: Arc::new(postgres.db.clone()),
+            graphs: Arc::new(postgres.graph.clone()),
+            time_series: Arc::new(postgres.time_series.clone()),
+            launches: Arc::new(postgres.launch.clone()),
+            alerts: Arc::new(postgres.alert.clone()),
+            kv: redis.kv.clone(),
+            work_queue: redis.queue.clone(),
+            coordinator: redis.coordinator.clone(),
+            streams: redis.stream.clone(),
+            events: redis,
+        }
+    }
+
+    /// Everything in one process; keep the handle to inspect what was written
+    pub fn in_memory(memory: Arc<InMemoryStorage>) -> Self {
+        Self {
+            tokens: memory.clone(),
+            graphs: memory.clone(),
+            time_series: memory.clone(),
+            kv: memory.clone(),
+            events: memory.clone(),
+            work_queue: memory.clone(),
+            launches: memory.clone(),
+            alerts: memory.clone(),
+            coordinator: memory.clone(),
+            streams: memory,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenStore for TokenMetadataDb {
+    async fn insert_token_metadata(
+        &self,
+        token: &TokenMetadata,
+    ) -> Result<()> {
+        TokenMetadataDb::insert_token_metadata(self, token).await
+    }
+
+    async fn get_token_by_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<TokenMetadata>> {
+        TokenMetadataDb::get_token_by_mint(self, mint).await
+    }
+
+    async fn update_token_cex_sources(
+        &self,
+        mint: &Pubkey,
+        cex_sources: &[Pubkey],
+        cex_updated_at: u64,
+    ) -> Result<()> {
+        TokenMetadataDb::update_token_cex_sources(self, mint, cex_sources, cex_updated_at).await
+    }
+
+    async fn record_cex_activity(
+        &self,
+        cex_name: &str,
+        cex_address: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<bool> {
+        TokenMetadataDb::record_cex_activity(self, cex_name, cex_address, mint).await
+    }
+
+    async fn update_token_ath(
+        &self,
+        mint: &Pubkey,
+        ath_price: u64,
+        ath_at: u64,
+    ) -> Result<()> {
+        TokenMetadataDb::update_token_ath(self, mint, ath_price, ath_at).await
+    }
+
+    async fn mark_token_bonded(
+        &self,
+        mint: &Pubkey,
+        bonded_at: u64,
+    ) -> Result<()> {
+        TokenMetadataDb::mark_token_bonded(self, mint, bonded_at).await
+    }
+
+    async fn get_bonded_tokens_since(
+        &self,
+        platform: &str,
+        bonded_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        TokenMetadataDb::get_bonded_tokens_since(self, platform, bonded_after).await
+    }
+
+    async fn get_unbonded_tokens_since(
+        &self,
+        platform: &str,
+        created_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        TokenMetadataDb::get_unbonded_tokens_since(self, platform, created_after).await
+    }
+
+    async fn get_cex_funded_tokens_since(
+        &self,
+        created_after: u64,
+    ) -> Result<Vec<Pubkey>> {
+        TokenMetadataDb::get_cex_funded_tokens_since(self, created_after).await
+    }
+}
+
+#[async_trait::async_trait]
+impl GraphStore for GraphDb {
+    async fn store_connection_graph(
+        &self,
+        mint: &Pubkey,
+        connection_graph: &CreatorConnectionGraph,
+    ) -> Result<()> {
+        GraphDb::store_connection_graph(self, mint, connection_graph).await
+    }
+
+    async fn load_connection_graph(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<CreatorConnectionGraph> {
+        GraphDb::load_connection_graph(self, mints).await
+    }
+}
+
+#[async_trait::async_trait]
+impl TimeSeriesStore for TimeSeriesDb {
+    async fn add_token_price(
+        &self,
+        mint: &str,
+        price: u64,
+        timestamp: i64,
+        trade_id: &str,
+    ) -> Result<()> {
+        TimeSeriesDb::add_token_price(self, mint, price, timestamp, trade_id).await
+    }
+
+    async fn add_token_volume(
+        &self,
+        mint: &str,
+        volume: u64,
+        timestamp: i64,
+        trade_id: &str,
+    ) -> Result<()> {
+        TimeSeriesDb::add_token_volume(self, mint, volume, timestamp, trade_id).await
+    }
+
+    async fn record_cex_launch(
+        &self,
+        cex_address: &str,
+        launched_at: i64,
+    ) -> Result<()> {
+        TimeSeriesDb::record_cex_launch(self, cex_address, launched_at).await
+    }
+
+    async fn rebuild_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64> {
+        TimeSeriesDb::rebuild_candles(self, mint, interval, from, to).await
+    }
+
+    async fn rollup_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64> {
+        TimeSeriesDb::rollup_candles(self, mint, interval, from, to).await
+    }
+
+    async fn get_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<Candle>> {
+        TimeSeriesDb::get_candles(self, mint, interval, from, to).await
+    }
+
+    async fn insert_holder_snapshot(
+        &self,
+        snapshot: &HolderSnapshot,
+    ) -> Result<()> {
+        TimeSeriesDb::insert_holder_snapshot(self, snapshot).await
+    }
+
+    async fn get_holder_snapshots(
+        &self,
+        mint: &Pubkey,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<HolderSnapshot>> {
+        TimeSeriesDb::get_holder_snapshots(self, mint, from, to).await
+    }
+}
+
+#[async_trait::async_trait]
+impl KvCache for TokenMetadataKv {
+    async fn get_value(
+        &self,
+        key: &str,
+    ) -> Result<Option<Value>> {
+        TokenMetadataKv::get::<Value>(self, key).await
+    }
+
+    async fn set_value(
+        &self,
+        key: &str,
+        value: &Value,
+    ) -> Result<()> {
+        TokenMetadataKv::set(self, key, value).await
+    }
+}
+
+#[async_trait::async_trait]
+impl EventBus for RedisClient {
+    async fn publish_value(
+        &self,
+        channel: &str,
+        payload: &Value,
+    ) -> Result<()> {
+        self.stream.publish(channel, payload).await
+    }
+
+    async fn broadcast_value(
+        &self,
+        channel: &str,
+        payload: &Value,
+    ) -> Result<()> {
+        self.queue.publish(channel, payload).await
+    }
+}
+
+#[async_trait::async_trait]
+impl WorkQueue for TokenMetadataQueue {
+    async fn add_unprocessed_account(
+        &self,
+        account: &CreatorMetadata,
+    ) -> Result<()> {
+        TokenMetadataQueue::add_unprocessed_account(self, account).await
+    }
+
+    async fn add_failed_account(
+        &self,
+        failed: &CreatorMetadata,
+    ) -> Result<()> {
+        TokenMetadataQueue::add_failed_account(self, failed).await
+    }
+
+    async fn lease_next_failed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        TokenMetadataQueue::lease_next_failed_account(self).await
+    }
+
+    async fn lease_next_unprocessed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        TokenMetadataQueue::lease_next_unprocessed_account(self).await
+    }
+
+    async fn release_account_lease(
+        &self,
+        lease: &AccountLease,
+    ) -> Result<()> {
+        TokenMetadataQueue::release_account_lease(self, lease).await
+    }
+
+    async fn heartbeat_account_leases(&self) -> Result<usize> {
+        TokenMetadataQueue::heartbeat_account_leases(self).await
+    }
+
+    async fn reap_expired_account_leases(&self) -> Result<usize> {
+        TokenMetadataQueue::reap_expired_account_leases(self).await
+    }
+
+    async fn get_pending_account_counts(&self) -> Result<(usize, usize)> {
+        TokenMetadataQueue::get_pending_account_counts(self).await
+    }
+
+    async fn get_in_flight_account_count(&self) -> Result<usize> {
+        TokenMetadataQueue::get_in_flight_account_count(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl LaunchStore for LaunchDb {
+    async fn store_launch_window(
+        &self,
+        window: &LaunchWindow,
+    ) -> Result<()> {
+        LaunchDb::store_launch_window(self, window).await
+    }
+
+    async fn get_launch_window(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<LaunchWindow>> {
+        LaunchDb::get_launch_window(self, mint).await
+    }
+
+    async fn store_bundle_analysis(
+        &self,
+        analysis: &BundleAnalysis,
+        analyzed_at: u64,
+    ) -> Result<()> {
+        LaunchDb::store_bundle_analysis(self, analysis, analyzed_at).await
+    }
+
+    async fn get_cluster_wallets(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<HashMap<Pubkey, Vec<Pubkey>>> {
+        LaunchDb::get_cluster_wallets(self, mints).await
+    }
+
+    async fn record_creator_outcome(
+        &self,
+        event: &CreatorReputationEvent,
+    ) -> Result<()> {
+        LaunchDb::record_creator_outcome(self, event).await
+    }
+}
+
+#[async_trait::async_trait]
+impl AlertStore for AlertDb {
+    async fn create_alert_rule(
+        &self,
+        rule: &AlertRule,
+    ) -> Result<i32> {
+        AlertDb::create_alert_rule(self, rule).await
+    }
+
+    async fn set_alert_rule_enabled(
+        &self,
+        rule_id: i32,
+        enabled: bool,
+    ) -> Result<()> {
+        AlertDb::set_alert_rule_enabled(self, rule_id, enabled).await
+    }
+
+    async fn get_enabled_alert_rules(&self) -> Result<Vec<AlertRule>> {
+        AlertDb::get_enabled_alert_rules(self).await
+    }
+
+    async fn record_alert_hit(
+        &self,
+        hit: &AlertHit,
+    ) -> Result<()> {
+        AlertDb::record_alert_hit(self, hit).await
+    }
+}
+
+#[async_trait::async_trait]
+impl MintCoordinator for TokenMetadataCoordinator {
+    fn worker(&self) -> &str {
+        &self.worker
+    }
+
+    async fn try_lock_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<bool> {
+        TokenMetadataCoordinator::try_lock_mint(self, mint).await
+    }
+
+    async fn release_mint_lock(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<()> {
+        TokenMetadataCoordinator::release_mint_lock(self, mint).await
+    }
+
+    async fn renew_mint_locks(&self) -> Result<usize> {
+        TokenMetadataCoordinator::renew_mint_locks(self).await
+    }
+
+    async fn heartbeat_instance(&self) -> Result<()> {
+        TokenMetadataCoordinator::heartbeat_instance(self).await
+    }
+
+    async fn deregister_instance(&self) -> Result<()> {
+        TokenMetadataCoordinator::deregister_instance(self).await
+    }
+
+    async fn get_live_instances(&self) -> Result<Vec<String>> {
+        TokenMetadataCoordinator::get_live_instances(self).await
+    }
+
+    async fn take_dead_instances(&self) -> Result<Vec<String>> {
+        TokenMetadataCoordinator::take_dead_instances(self).await
+    }
+
+    async fn release_instance_mint_locks(
+        &self,
+        worker: &str,
+    ) -> Result<usize> {
+        TokenMetadataCoordinator::release_instance_mint_locks(self, worker).await
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStream for TokenMetadataStream {
+    async fn ensure_group(
+        &self,
+        channel: &str,
+    ) -> Result<()> {
+        TokenMetadataStream::ensure_group(self, channel).await
+    }
+
+    async fn next_batch_values(
+        &self,
+        channel: &str,
+        last_claim: &mut Option<Instant>,
+    ) -> Result<Vec<StreamEvent<Value>>> {
+        TokenMetadataStream::next_batch::<Value>(self, channel, last_claim).await
+    }
+
+    async fn ack(
+        &self,
+        channel: &str,
+        id: &str,
+    ) -> Result<()> {
+        TokenMetadataStream::ack(self, channel, id).await
+    }
+
+    async fn remove_idle_consumer(
+        &self,
+        channel: &str,
+        consumer: &str,
+    ) -> Result<bool> {
+        TokenMetadataStream::remove_idle_consumer(self, channel, consumer).await
+    }
+}
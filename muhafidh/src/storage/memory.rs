@@ -0,0 +1,1155 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde_json::Value;
+use solana_pubkey::Pubkey;
+use tokio::sync::Mutex;
+use tokio::sync::Notify;
+use tracing::error;
+
+use crate::RedisClientError;
+use crate::Result;
+use crate::config::RedisLeaseConfig;
+use crate::config::RedisStreamConfig;
+use crate::err_with_loc;
+use crate::model::alert::AlertHit;
+use crate::model::alert::AlertRule;
+use crate::model::bundle::BundleAnalysis;
+use crate::model::bundle::LaunchWindow;
+use crate::model::creator::graph::CreatorConnectionGraph;
+use crate::model::creator::metadata::CreatorMetadata;
+use crate::model::token::TokenMetadata;
+use crate::storage::backend::AlertStore;
+use crate::storage::backend::EventBus;
+use crate::storage::backend::EventStream;
+use crate::storage::backend::GraphStore;
+use crate::storage::backend::KvCache;
+use crate::storage::backend::LaunchStore;
+use crate::storage::backend::MintCoordinator;
+use crate::storage::backend::TimeSeriesStore;
+use crate::storage::backend::TokenStore;
+use crate::storage::backend::WorkQueue;
+use crate::storage::postgres::model::Candle;
+use crate::storage::postgres::model::CandleInterval;
+use crate::storage::postgres::model::CreatorReputationEvent;
+use crate::storage::postgres::model::HolderSnapshot;
+use crate::storage::redis::queue::AccountLease;
+use crate::storage::redis::stream::StreamEvent;
+
+const FAILED_ACCOUNTS: &str = "failed_accounts";
+const UNPROCESSED_ACCOUNTS: &str = "unprocessed_accounts";
+
+// The only instance there is, it owns every lock and never dies
+const MEMORY_WORKER: &str = "memory";
+
+#[derive(Debug)]
+struct MemoryLease {
+    queue: &'static str,
+    payload: String,
+    expires_at: i64,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    tokens: HashMap<Pubkey, TokenMetadata>,
+    // (cex_name, cex_address, mint)
+    cex_activity: Vec<(String, Pubkey, Pubkey)>,
+    graphs: HashMap<Pubkey, CreatorConnectionGraph>,
+    // (mint, timestamp, trade_id) -> value
+    prices: BTreeMap<(String, i64, String), u64>,
+    volumes: BTreeMap<(String, i64, String), u64>,
+    // (cex_address, launched_at)
+    cex_launches: Vec<(String, i64)>,
+    // (mint, interval, bucket_start) -> candle
+    candles: BTreeMap<(String, &'static str, i64), Candle>,
+    holder_snapshots: BTreeMap<(Pubkey, i64), HolderSnapshot>,
+    kv: HashMap<String, Value>,
+    // (channel, payload) in publish order, the index is the stream entry id
+    published: Vec<(String, Value)>,
+    broadcast: Vec<(String, Value)>,
+    // Index into `published` up to which each channel was delivered
+    delivered: HashMap<String, usize>,
+    // Delivered entries not acknowledged yet -> last delivery
+    pending: BTreeMap<usize, Instant>,
+    queues: HashMap<&'static str, VecDeque<String>>,
+    leases: HashMap<String, MemoryLease>,
+    lease_counter: u64,
+    launch_windows: HashMap<Pubkey, LaunchWindow>,
+    // mint -> (analysis, analyzed_at)
+    bundle_analyses: HashMap<Pubkey, (BundleAnalysis, u64)>,
+    creator_outcomes: Vec<CreatorReputationEvent>,
+    alert_rules: BTreeMap<i32, AlertRule>,
+    alert_hits: Vec<AlertHit>,
+    // mint -> owning worker
+    mint_locks: HashMap<Pubkey, String>,
+    instances: HashSet<String>,
+}
+
+/// In-process implementation of every storage trait, mirroring the upsert and lease semantics of the
+/// Postgres and Redis backends. Nothing is persisted.
+#[derive(Debug)]
+pub struct InMemoryStorage {
+    state: Mutex<MemoryState>,
+    visibility_timeout_ms: i64,
+    // Wakes stream readers waiting for new entries
+    published: Notify,
+    stream_config: RedisStreamConfig,
+}
+
+impl Default for InMemoryStorage {
+    fn default() -> Self {
+        Self::new(RedisLeaseConfig::default().visibility_timeout_secs)
+    }
+}
+
+impl InMemoryStorage {
+    pub fn new(visibility_timeout_secs: u64) -> Self {
+        Self {
+            state: Mutex::new(MemoryState::default()),
+            visibility_timeout_ms: (visibility_timeout_secs * 1000) as i64,
+            published: Notify::new(),
+            stream_config: RedisStreamConfig::default(),
+        }
+    }
+
+    fn now_ms() -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+
+    pub async fn get_token(
+        &self,
+        mint: &Pubkey,
+    ) -> Option<TokenMetadata> {
+        self.state.lock().await.tokens.get(mint).cloned()
+    }
+
+    /// (timestamp, price) in time order
+    pub async fn get_token_prices(
+        &self,
+        mint: &str,
+    ) -> Vec<(i64, u64)> {
+        let state = self.state.lock().await;
+        state
+            .prices
+            .range((mint.to_string(), i64::MIN, String::new())..)
+            .take_while(|((key, ..), _)| key == mint)
+            .map(|((_, timestamp, _), price)| (*timestamp, *price))
+            .collect()
+    }
+
+    /// (timestamp, volume) in time order
+    pub async fn get_token_volumes(
+        &self,
+        mint: &str,
+    ) -> Vec<(i64, u64)> {
+        let state = self.state.lock().await;
+        state
+            .volumes
+            .range((mint.to_string(), i64::MIN, String::new())..)
+            .take_while(|((key, ..), _)| key == mint)
+            .map(|((_, timestamp, _), volume)| (*timestamp, *volume))
+            .collect()
+    }
+
+    /// (cex_name, cex_address, mint) in record order
+    pub async fn get_cex_activity(&self) -> Vec<(String, Pubkey, Pubkey)> {
+        self.state.lock().await.cex_activity.clone()
+    }
+
+    /// (cex_address, launched_at) in record order
+    pub async fn get_cex_launches(&self) -> Vec<(String, i64)> {
+        self.state.lock().await.cex_launches.clone()
+    }
+
+    /// Events published to a channel through `EventBus::publish_value`, oldest first
+    pub async fn published_events(
+        &self,
+        channel: &str,
+    ) -> Vec<Value> {
+        let state = self.state.lock().await;
+        state
+            .published
+            .iter()
+            .filter(|(c, _)| c == channel)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+
+    /// Events sent to a channel through `EventBus::broadcast_value`, oldest first
+    pub async fn broadcast_events(
+        &self,
+        channel: &str,
+    ) -> Vec<Value> {
+        let state = self.state.lock().await;
+        state
+            .broadcast
+            .iter()
+            .filter(|(c, _)| c == channel)
+            .map(|(_, payload)| payload.clone())
+            .collect()
+    }
+
+    /// Latest bundle findings stored for a mint, with their analysis time
+    pub async fn get_bundle_analysis(
+        &self,
+        mint: &Pubkey,
+    ) -> Option<(BundleAnalysis, u64)> {
+        self.state.lock().await.bundle_analyses.get(mint).cloned()
+    }
+
+    /// Creator outcomes in record order
+    pub async fn get_creator_outcomes(&self) -> Vec<CreatorReputationEvent> {
+        self.state.lock().await.creator_outcomes.clone()
+    }
+
+    /// Alert hits in record order
+    pub async fn get_alert_hits(&self) -> Vec<AlertHit> {
+        self.state.lock().await.alert_hits.clone()
+    }
+
+    fn stream_entry_id(index: usize) -> String {
+        format!("memory-{}", index)
+    }
+
+    // Entries of the channel pending for `claim_idle_ms` on a claim, entries published since the last read otherwise
+    fn take_stream_entries(
+        &self,
+        state: &mut MemoryState,
+        channel: &str,
+        claim: bool,
+    ) -> Vec<StreamEvent<Value>> {
+        let indices: Vec<usize> = if claim {
+            let claim_idle = Duration::from_millis(self.stream_config.claim_idle_ms);
+            state
+                .pending
+                .iter()
+                .filter(|(index, delivered_at)| {
+                    state.published[**index].0 == channel && delivered_at.elapsed() >= claim_idle
+                })
+                .map(|(index, _)| *index)
+                .take(self.stream_config.batch_size)
+                .collect()
+        } else {
+            let from = state.delivered.get(channel).copied().unwrap_or(0);
+            let indices: Vec<usize> = (from..state.published.len())
+                .filter(|index| state.published[*index].0 == channel)
+                .take(self.stream_config.batch_size)
+                .collect();
+            let delivered = indices.last().map_or(state.published.len(), |last| last + 1);
+            state.delivered.insert(channel.to_string(), delivered);
+            indices
+        };
+
+        let now = Instant::now();
+        for index in &indices {
+            state.pending.insert(*index, now);
+        }
+
+        indices
+            .into_iter()
+            .map(|index| StreamEvent {
+                id: Self::stream_entry_id(index),
+                payload: state.published[index].1.clone(),
+            })
+            .collect()
+    }
+
+    async fn push_account(
+        &self,
+        queue: &'static str,
+        account: &CreatorMetadata,
+    ) -> Result<()> {
+        let json = serde_json::to_string(account).map_err(|e| {
+            error!("serialize_account_failed: {}", e);
+            err_with_loc!(RedisClientError::SerializeError(e))
+        })?;
+        self.state.lock().await.queues.entry(queue).or_default().push_back(json);
+        Ok(())
+    }
+
+    async fn lease_next_account(
+        &self,
+        queue: &'static str,
+    ) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        let mut state = self.state.lock().await;
+        let Some(payload) = state.queues.get_mut(queue).and_then(|items| items.pop_front()) else {
+            return Ok(None);
+        };
+
+        // Nothing can ever process it, so it is dropped rather than leased
+        let account = serde_json::from_str(&payload).map_err(|e| {
+            error!("deserialize_leased_account_failed::{}: {}", queue, e);
+            err_with_loc!(RedisClientError::DeserializeError(e))
+        })?;
+
+        state.lease_counter += 1;
+        let lease_id = format!("memory:{}", state.lease_counter);
+        state.leases.insert(lease_id.clone(), MemoryLease {
+            queue,
+            payload,
+            expires_at: Self::now_ms() + self.visibility_timeout_ms,
+        });
+
+        Ok(Some((
+            AccountLease {
+                id: lease_id,
+                queue: queue.to_string(),
+            },
+            account,
+        )))
+    }
+}
+
+#[async_trait::async_trait]
+impl TokenStore for InMemoryStorage {
+    async fn insert_token_metadata(
+        &self,
+        token: &TokenMetadata,
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state
+            .tokens
+            .entry(token.mint)
+            .and_modify(|existing| {
+                existing.name = token.name.clone();
+                existing.symbol = token.symbol.clone();
+                existing.uri = token.uri.clone();
+                existing.cex_sources = token.cex_sources.clone();
+                existing.cex_updated_at = token.cex_updated_at;
+                existing.updated_at = token.updated_at;
+                existing.associated_bonding_curve = token.associated_bonding_curve;
+                if existing.all_time_high_price < token.all_time_high_price {
+                    existing.all_time_high_price = token.all_time_high_price;
+                    existing.all_time_high_price_at = token.all_time_high_price_at;
+                }
+            })
+            .or_insert_with(|| token.clone());
+        Ok(())
+    }
+
+    async fn get_token_by_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<TokenMetadata>> {
+        Ok(self.get_token(mint).await)
+    }
+
+    async fn update_token_cex_sources(
+        &self,
+        mint: &Pubkey,
+        cex_sources: &[Pubkey],
+        cex_updated_at: u64,
+    ) -> Result<()> {
+        if let Some(token) = self.state.lock().await.tokens.get_mut(mint) {
+            token.cex_sources = Some(cex_sources.to_vec());
+            token.cex_updated_at = Some(cex_updated_at);
+        }
+        Ok(())
+    }
+
+    async fn record_cex_activity(
+        &self,
+        cex_name: &str,
+        cex_address: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<bool> {
+        let mut state = self.state.lock().await;
+        if state
+            .cex_activity
+            .iter()
+            .any(|(_, cex, recorded)| cex == cex_address && recorded == mint)
+        {
+            return Ok(false);
+        }
+        state.cex_activity.push((cex_name.to_string(), *cex_address, *mint));
+        Ok(true)
+    }
+
+    async fn update_token_ath(
+        &self,
+        mint: &Pubkey,
+        ath_price: u64,
+        ath_at: u64,
+    ) -> Result<()> {
+        if let Some(token) = self.state.lock().await.tokens.get_mut(mint) {
+            if token.all_time_high_price < ath_price {
+                token.all_time_high_price = ath_price;
+                token.all_time_high_price_at = ath_at;
+            }
+        }
+        Ok(())
+    }
+
+    async fn mark_token_bonded(
+        &self,
+        mint: &Pubkey,
+        bonded_at: u64,
+    ) -> Result<()> {
+        if let Some(token) = self.state.lock().await.tokens.get_mut(mint) {
+            token.is_bonded = true;
+            token.bonded_at = token.bonded_at.filter(|at| *at > 0).or(Some(bonded_at));
+        }
+        Ok(())
+    }
+
+    async fn get_bonded_tokens_since(
+        &self,
+        platform: &str,
+        bonded_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .tokens
+            .values()
+            .filter(|token| token.platform == platform && token.is_bonded)
+            .filter_map(|token| token.bonded_at.filter(|at| *at >= bonded_after).map(|at| (token.mint, at)))
+            .collect())
+    }
+
+    async fn get_unbonded_tokens_since(
+        &self,
+        platform: &str,
+        created_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .tokens
+            .values()
+            .filter(|token| token.platform == platform && !token.is_bonded && token.created_at >= created_after)
+            .map(|token| (token.mint, token.created_at))
+            .collect())
+    }
+
+    async fn get_cex_funded_tokens_since(
+        &self,
+        created_after: u64,
+    ) -> Result<Vec<Pubkey>> {
+        let state = self.state.lock().await;
+        Ok(state
+            .tokens
+            .values()
+            .filter(|token| token.cex_sources.as_ref().is_some_and(|sources| !sources.is_empty()))
+            .filter(|token| token.created_at >= created_after)
+            .map(|token| token.mint)
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl GraphStore for InMemoryStorage {
+    async fn store_connection_graph(
+        &self,
+        mint: &Pubkey,
+        connection_graph: &CreatorConnectionGraph,
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.graphs.entry(*mint).or_default().merge(connection_graph);
+        Ok(())
+    }
+
+    async fn load_connection_graph(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<CreatorConnectionGraph> {
+        let state = self.state.lock().await;
+        let mut merged = CreatorConnectionGraph::new();
+        for mint in mints {
+            if let Some(graph) = state.graphs.get(mint) {
+                merged.merge(graph);
+            }
+        }
+        Ok(merged)
+    }
+}
+
+#[async_trait::async_trait]
+impl TimeSeriesStore for InMemoryStorage {
+    async fn add_token_price(
+        &self,
+        mint: &str,
+        price: u64,
+        timestamp: i64,
+        trade_id: &str,
+    ) -> Result<()> {
+        self.state
+            .lock()
+            .await
+            .prices
+            .insert((mint.to_string(), timestamp, trade_id.to_string()), price);
+        Ok(())
+    }
+
+    async fn add_token_volume(
+        &self,
+        mint: &str,
+        volume: u64,
+        timestamp: i64,
+        trade_id: &str,
+    ) -> Result<()> {
+        self.state
+            .lock()
+            .await
+            .volumes
+            .insert((mint.to_string(), timestamp, trade_id.to_string()), volume);
+        Ok(())
+    }
+
+    async fn record_cex_launch(
+        &self,
+        cex_address: &str,
+        launched_at: i64,
+    ) -> Result<()> {
+        self.state
+            .lock()
+            .await
+            .cex_launches
+            .push((cex_address.to_string(), launched_at));
+        Ok(())
+    }
+
+    async fn rebuild_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64> {
+        let mint_key = Pubkey::from_str(mint)?;
+        let start = interval.bucket_start(from);
+        let end = interval.bucket_start(to) + interval.seconds();
+
+        let mut state = self.state.lock().await;
+        let mut rebuilt: BTreeMap<i64, Candle> = BTreeMap::new();
+        for ((_, timestamp, _), price) in state
+            .prices
+            .range((mint.to_string(), start, String::new())..)
+            .take_while(|((key, timestamp, _), _)| key == mint && *timestamp < end)
+        {
+            rebuilt
+                .entry(interval.bucket_start(*timestamp))
+                .and_modify(|candle| candle.apply_trade(*price, 0, *timestamp))
+                .or_insert_with(|| Candle::new(mint_key, interval, *price, 0, *timestamp));
+        }
+
+        // Volume is summed per bucket, prices and trades aren't matched one to one
+        let mut volumes: BTreeMap<i64, (u64, u64)> = BTreeMap::new();
+        for ((_, timestamp, _), volume) in state
+            .volumes
+            .range((mint.to_string(), start, String::new())..)
+            .take_while(|((key, timestamp, _), _)| key == mint && *timestamp < end)
+        {
+            let bucket = volumes.entry(interval.bucket_start(*timestamp)).or_default();
+            bucket.0 = bucket.0.saturating_add(*volume);
+            bucket.1 += 1;
+        }
+        for (bucket_start, (volume, trade_count)) in volumes {
+            if let Some(candle) = rebuilt.get_mut(&bucket_start) {
+                candle.volume = volume;
+                candle.trade_count = trade_count;
+            }
+        }
+
+        let count = rebuilt.len() as u64;
+        for (bucket_start, candle) in rebuilt {
+            state
+                .candles
+                .insert((mint.to_string(), interval.as_str(), bucket_start), candle);
+        }
+        Ok(count)
+    }
+
+    async fn rollup_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64> {
+        let start = interval.bucket_start(from);
+        let end = interval.bucket_start(to) + interval.seconds();
+        let minute = CandleInterval::M1.as_str();
+
+        let mut state = self.state.lock().await;
+        let mut rolled: BTreeMap<i64, Candle> = BTreeMap::new();
+        for (_, candle) in state
+            .candles
+            .range((mint.to_string(), minute, start)..(mint.to_string(), minute, end))
+        {
+            let bucket_start = interval.bucket_start(candle.bucket_start);
+            rolled
+                .entry(bucket_start)
+                .and_modify(|rollup| rollup.absorb(candle))
+                .or_insert_with(|| Candle {
+                    interval,
+                    bucket_start,
+                    ..candle.clone()
+                });
+        }
+
+        let count = rolled.len() as u64;
+        for (bucket_start, candle) in rolled {
+            state
+                .candles
+                .insert((mint.to_string(), interval.as_str(), bucket_start), candle);
+        }
+        Ok(count)
+    }
+
+    async fn get_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<Candle>> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+        let state = self.state.lock().await;
+        Ok(state
+            .candles
+            .range((mint.to_string(), interval.as_str(), from)..=(mint.to_string(), interval.as_str(), to))
+            .map(|(_, candle)| candle.clone())
+            .collect())
+    }
+
+    async fn insert_holder_snapshot(
+        &self,
+        snapshot: &HolderSnapshot,
+    ) -> Result<()> {
+        self.state
+            .lock()
+            .await
+            .holder_snapshots
+            .insert((snapshot.mint, snapshot.taken_at), snapshot.clone());
+        Ok(())
+    }
+
+    async fn get_holder_snapshots(
+        &self,
+        mint: &Pubkey,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<HolderSnapshot>> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+        let state = self.state.lock().await;
+        Ok(state
+            .holder_snapshots
+            .range((*mint, from)..=(*mint, to))
+            .map(|(_, snapshot)| snapshot.clone())
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl KvCache for InMemoryStorage {
+    async fn get_value(
+        &self,
+        key: &str,
+    ) -> Result<Option<Value>> {
+        Ok(self.state.lock().await.kv.get(key).cloned())
+    }
+
+    async fn set_value(
+        &self,
+        key: &str,
+        value: &Value,
+    ) -> Result<()> {
+        self.state.lock().await.kv.insert(key.to_string(), value.clone());
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl EventBus for InMemoryStorage {
+    async fn publish_value(
+        &self,
+        channel: &str,
+        payload: &Value,
+    ) -> Result<()> {
+        self.state.lock().await.published.push((channel.to_string(), payload.clone()));
+        self.published.notify_waiters();
+        Ok(())
+    }
+
+    async fn broadcast_value(
+        &self,
+        channel: &str,
+        payload: &Value,
+    ) -> Result<()> {
+        self.state.lock().await.broadcast.push((channel.to_string(), payload.clone()));
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl WorkQueue for InMemoryStorage {
+    async fn add_unprocessed_account(
+        &self,
+        account: &CreatorMetadata,
+    ) -> Result<()> {
+        self.push_account(UNPROCESSED_ACCOUNTS, account).await
+    }
+
+    async fn add_failed_account(
+        &self,
+        failed: &CreatorMetadata,
+    ) -> Result<()> {
+        self.push_account(FAILED_ACCOUNTS, failed).await
+    }
+
+    async fn lease_next_failed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        self.lease_next_account(FAILED_ACCOUNTS).await
+    }
+
+    async fn lease_next_unprocessed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        self.lease_next_account(UNPROCESSED_ACCOUNTS).await
+    }
+
+    async fn release_account_lease(
+        &self,
+        lease: &AccountLease,
+    ) -> Result<()> {
+        self.state.lock().await.leases.remove(&lease.id);
+        Ok(())
+    }
+
+    async fn heartbeat_account_leases(&self) -> Result<usize> {
+        let mut state = self.state.lock().await;
+        let expires_at = Self::now_ms() + self.visibility_timeout_ms;
+        for lease in state.leases.values_mut() {
+            lease.expires_at = expires_at;
+        }
+        Ok(state.leases.len())
+    }
+
+    async fn reap_expired_account_leases(&self) -> Result<usize> {
+        let mut state = self.state.lock().await;
+        let now = Self::now_ms();
+        let expired: Vec<String> = state
+            .leases
+            .iter()
+            .filter(|(_, lease)| lease.expires_at <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+
+        for lease_id in &expired {
+            if let Some(lease) = state.leases.remove(lease_id) {
+                state.queues.entry(lease.queue).or_default().push_front(lease.payload);
+            }
+        }
+        Ok(expired.len())
+    }
+
+    async fn get_pending_account_counts(&self) -> Result<(usize, usize)> {
+        let state = self.state.lock().await;
+        let count = |queue: &str| state.queues.get(queue).map_or(0, |items| items.len());
+        Ok((count(FAILED_ACCOUNTS), count(UNPROCESSED_ACCOUNTS)))
+    }
+
+    async fn get_in_flight_account_count(&self) -> Result<usize> {
+        Ok(self.state.lock().await.leases.len())
+    }
+}
+
+#[async_trait::async_trait]
+impl LaunchStore for InMemoryStorage {
+    async fn store_launch_window(
+        &self,
+        window: &LaunchWindow,
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        let stored = state
+            .launch_windows
+            .entry(window.mint)
+            .or_insert_with(|| LaunchWindow::new(window.mint, window.creator, window.create_slot, window.window_slots));
+        for buy in &window.buys {
+            if !stored
+                .buys
+                .iter()
+                .any(|existing| existing.signature == buy.signature && existing.buyer == buy.buyer)
+            {
+                stored.buys.push(buy.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_launch_window(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<LaunchWindow>> {
+        let mut window = self.state.lock().await.launch_windows.get(mint).cloned();
+        if let Some(window) = window.as_mut() {
+            // Stable, so buys of one slot stay in store order
+            window.buys.sort_by_key(|buy| buy.slot);
+        }
+        Ok(window)
+    }
+
+    async fn store_bundle_analysis(
+        &self,
+        analysis: &BundleAnalysis,
+        analyzed_at: u64,
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if state.launch_windows.contains_key(&analysis.mint) {
+            state.bundle_analyses.insert(analysis.mint, (analysis.clone(), analyzed_at));
+        }
+        Ok(())
+    }
+
+    async fn get_cluster_wallets(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<HashMap<Pubkey, Vec<Pubkey>>> {
+        let state = self.state.lock().await;
+        let mut clusters: HashMap<Pubkey, Vec<Pubkey>> = HashMap::new();
+        for mint in mints {
+            let mut wallets: HashSet<Pubkey> = HashSet::new();
+            if let Some((analysis, _)) = state.bundle_analyses.get(mint) {
+                wallets.extend(analysis.bundlers.iter().map(|bundler| bundler.wallet));
+            }
+            if let Some(graph) = state.graphs.get(mint) {
+                wallets.extend(graph.get_edges().iter().flat_map(|edge| [edge.from, edge.to]));
+            }
+            if !wallets.is_empty() {
+                clusters.insert(*mint, wallets.into_iter().collect());
+            }
+        }
+        Ok(clusters)
+    }
+
+    async fn record_creator_outcome(
+        &self,
+        event: &CreatorReputationEvent,
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if !state.creator_outcomes.iter().any(|recorded| {
+            recorded.creator == event.creator && recorded.mint == event.mint && recorded.outcome == event.outcome
+        }) {
+            state.creator_outcomes.push(event.clone());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl AlertStore for InMemoryStorage {
+    async fn create_alert_rule(
+        &self,
+        rule: &AlertRule,
+    ) -> Result<i32> {
+        let mut state = self.state.lock().await;
+        let id = state.alert_rules.keys().next_back().map_or(1, |last| last + 1);
+        state.alert_rules.insert(id, AlertRule {
+            id,
+            ..rule.clone()
+        });
+        Ok(id)
+    }
+
+    async fn set_alert_rule_enabled(
+        &self,
+        rule_id: i32,
+        enabled: bool,
+    ) -> Result<()> {
+        if let Some(rule) = self.state.lock().await.alert_rules.get_mut(&rule_id) {
+            rule.enabled = enabled;
+        }
+        Ok(())
+    }
+
+    async fn get_enabled_alert_rules(&self) -> Result<Vec<AlertRule>> {
+        let state = self.state.lock().await;
+        Ok(state.alert_rules.values().filter(|rule| rule.enabled).cloned().collect())
+    }
+
+    async fn record_alert_hit(
+        &self,
+        hit: &AlertHit,
+    ) -> Result<()> {
+        self.state.lock().await.alert_hits.push(hit.clone());
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl MintCoordinator for InMemoryStorage {
+    fn worker(&self) -> &str {
+        MEMORY_WORKER
+    }
+
+    async fn try_lock_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<bool> {
+        let mut state = self.state.lock().await;
+        let owner = state.mint_locks.entry(*mint).or_insert_with(|| MEMORY_WORKER.to_string());
+        Ok(*owner == MEMORY_WORKER)
+    }
+
+    async fn release_mint_lock(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<()> {
+        let mut state = self.state.lock().await;
+        if state.mint_locks.get(mint).is_some_and(|owner| owner == MEMORY_WORKER) {
+            state.mint_locks.remove(mint);
+        }
+        Ok(())
+    }
+
+    async fn renew_mint_locks(&self) -> Result<usize> {
+        let state = self.state.lock().await;
+        Ok(state.mint_locks.values().filter(|owner| *owner == MEMORY_WORKER).count())
+    }
+
+    async fn heartbeat_instance(&self) -> Result<()> {
+        self.state.lock().await.instances.insert(MEMORY_WORKER.to_string());
+        Ok(())
+    }
+
+    async fn deregister_instance(&self) -> Result<()> {
+        self.release_instance_mint_locks(MEMORY_WORKER).await?;
+        self.state.lock().await.instances.remove(MEMORY_WORKER);
+        Ok(())
+    }
+
+    async fn get_live_instances(&self) -> Result<Vec<String>> {
+        Ok(self.state.lock().await.instances.iter().cloned().collect())
+    }
+
+    async fn take_dead_instances(&self) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    async fn release_instance_mint_locks(
+        &self,
+        worker: &str,
+    ) -> Result<usize> {
+        let mut state = self.state.lock().await;
+        let before = state.mint_locks.len();
+        state.mint_locks.retain(|_, owner| *owner != worker);
+        Ok(before - state.mint_locks.len())
+    }
+}
+
+#[async_trait::async_trait]
+impl EventStream for InMemoryStorage {
+    // Every channel has a group from the start, reading from its first entry
+    async fn ensure_group(
+        &self,
+        _channel: &str,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    async fn next_batch_values(
+        &self,
+        channel: &str,
+        last_claim: &mut Option<Instant>,
+    ) -> Result<Vec<StreamEvent<Value>>> {
+        let claim_interval = Duration::from_secs(self.stream_config.claim_interval_secs);
+        if last_claim.is_none_or(|at| at.elapsed() >= claim_interval) {
+            *last_claim = Some(Instant::now());
+            return Ok(self.take_stream_entries(&mut *self.state.lock().await, channel, true));
+        }
+
+        // Registered before looking, so an entry published in between still wakes us
+        let published = self.published.notified();
+        let events = self.take_stream_entries(&mut *self.state.lock().await, channel, false);
+        if !events.is_empty() {
+            return Ok(events);
+        }
+        let _ = tokio::time::timeout(Duration::from_millis(self.stream_config.block_ms), published).await;
+        Ok(self.take_stream_entries(&mut *self.state.lock().await, channel, false))
+    }
+
+    async fn ack(
+        &self,
+        _channel: &str,
+        id: &str,
+    ) -> Result<()> {
+        if let Some(index) = id.strip_prefix("memory-").and_then(|index| index.parse::<usize>().ok()) {
+            self.state.lock().await.pending.remove(&index);
+        }
+        Ok(())
+    }
+
+    // No other consumer exists in-process, so there is never anything pending for it
+    async fn remove_idle_consumer(
+        &self,
+        _channel: &str,
+        _consumer: &str,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::redis::model::NewTokenCache;
+
+    fn token(
+        mint: Pubkey,
+        name: &str,
+        all_time_high_price: u64,
+        all_time_high_price_at: u64,
+    ) -> TokenMetadata {
+        TokenMetadata::new(
+            mint,
+            None,
+            name.to_string(),
+            String::from("TKN"),
+            String::new(),
+            Pubkey::new_from_array([9; 32]),
+            String::from("pumpfun"),
+            1_000,
+            None,
+            false,
+            all_time_high_price,
+            all_time_high_price_at,
+        )
+    }
+
+    async fn account(mint: Pubkey) -> CreatorMetadata {
+        CreatorMetadata::initialize(
+            NewTokenCache {
+                mint,
+                bonding_curve: None,
+                name: String::from("token"),
+                symbol: String::from("TKN"),
+                uri: String::new(),
+                creator: Pubkey::new_from_array([9; 32]),
+                created_at: 1_000,
+            },
+            3,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn insert_token_metadata_upserts_and_keeps_the_higher_ath() {
+        let storage = InMemoryStorage::default();
+        let mint = Pubkey::new_from_array([1; 32]);
+
+        storage.insert_token_metadata(&token(mint, "first", 500, 10)).await.unwrap();
+        storage.insert_token_metadata(&token(mint, "second", 200, 20)).await.unwrap();
+        let stored = storage.get_token(&mint).await.unwrap();
+        assert_eq!(stored.name, "second");
+        assert_eq!((stored.all_time_high_price, stored.all_time_high_price_at), (500, 10));
+
+        storage.insert_token_metadata(&token(mint, "third", 900, 30)).await.unwrap();
+        let stored = storage.get_token(&mint).await.unwrap();
+        assert_eq!(stored.name, "third");
+        assert_eq!((stored.all_time_high_price, stored.all_time_high_price_at), (900, 30));
+    }
+
+    #[tokio::test]
+    async fn update_token_ath_only_raises_it() {
+        let storage = InMemoryStorage::default();
+        let mint = Pubkey::new_from_array([1; 32]);
+        storage.insert_token_metadata(&token(mint, "token", 500, 10)).await.unwrap();
+
+        storage.update_token_ath(&mint, 400, 20).await.unwrap();
+        let stored = storage.get_token(&mint).await.unwrap();
+        assert_eq!((stored.all_time_high_price, stored.all_time_high_price_at), (500, 10));
+
+        storage.update_token_ath(&mint, 600, 30).await.unwrap();
+        let stored = storage.get_token(&mint).await.unwrap();
+        assert_eq!((stored.all_time_high_price, stored.all_time_high_price_at), (600, 30));
+
+        // Mints that were never stored are not created
+        let unknown = Pubkey::new_from_array([2; 32]);
+        storage.update_token_ath(&unknown, 600, 30).await.unwrap();
+        assert!(storage.get_token(&unknown).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn expired_lease_returns_the_account_to_the_front_of_its_queue() {
+        // Leases expire as soon as they are taken
+        let storage = InMemoryStorage::new(0);
+        let first = Pubkey::new_from_array([1; 32]);
+        let second = Pubkey::new_from_array([2; 32]);
+        storage.add_unprocessed_account(&account(first).await).await.unwrap();
+        storage.add_unprocessed_account(&account(second).await).await.unwrap();
+
+        let (_, leased) = storage.lease_next_unprocessed_account().await.unwrap().unwrap();
+        assert_eq!(leased.mint, first);
+        assert_eq!(storage.get_pending_account_counts().await.unwrap(), (0, 1));
+        assert_eq!(storage.get_in_flight_account_count().await.unwrap(), 1);
+
+        assert_eq!(storage.reap_expired_account_leases().await.unwrap(), 1);
+        assert_eq!(storage.get_in_flight_account_count().await.unwrap(), 0);
+        let (_, leased) = storage.lease_next_unprocessed_account().await.unwrap().unwrap();
+        assert_eq!(leased.mint, first);
+    }
+
+    #[tokio::test]
+    async fn released_lease_is_not_reaped() {
+        let storage = InMemoryStorage::new(0);
+        let mint = Pubkey::new_from_array([1; 32]);
+        storage.add_failed_account(&account(mint).await).await.unwrap();
+
+        let (lease, _) = storage.lease_next_failed_account().await.unwrap().unwrap();
+        assert_eq!(lease.queue, FAILED_ACCOUNTS);
+        storage.release_account_lease(&lease).await.unwrap();
+
+        assert_eq!(storage.reap_expired_account_leases().await.unwrap(), 0);
+        assert_eq!(storage.get_pending_account_counts().await.unwrap(), (0, 0));
+        assert!(storage.lease_next_failed_account().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn rolled_up_candles_match_a_rebuild_from_trades() {
+        let storage = InMemoryStorage::default();
+        let mint = Pubkey::new_from_array([1; 32]).to_string();
+        for (timestamp, price, volume) in [(610, 5, 10), (30, 3, 20), (250, 9, 5), (0, 4, 1)] {
+            let trade_id = timestamp.to_string();
+            storage.add_token_price(&mint, price, timestamp, &trade_id).await.unwrap();
+            storage.add_token_volume(&mint, volume, timestamp, &trade_id).await.unwrap();
+        }
+
+        storage.rebuild_candles(&mint, CandleInterval::M1, 0, 610).await.unwrap();
+        assert_eq!(storage.rollup_candles(&mint, CandleInterval::M5, 0, 610).await.unwrap(), 2);
+        storage.rebuild_candles(&mint, CandleInterval::H1, 0, 610).await.unwrap();
+
+        let rolled = storage.get_candles(&mint, CandleInterval::M5, 0, 600).await.unwrap();
+        let summary: Vec<_> = rolled
+            .iter()
+            .map(|c| (c.bucket_start, c.open, c.high, c.low, c.close, c.volume, c.trade_count))
+            .collect();
+        assert_eq!(summary, vec![(0, 4, 9, 3, 9, 26, 3), (600, 5, 5, 5, 5, 10, 1)]);
+
+        // Rolling the minutes up into the hour gives what rebuilding it from trades gave
+        let rebuilt = storage.get_candles(&mint, CandleInterval::H1, 0, 0).await.unwrap().remove(0);
+        storage.rollup_candles(&mint, CandleInterval::H1, 0, 610).await.unwrap();
+        let rolled = storage.get_candles(&mint, CandleInterval::H1, 0, 0).await.unwrap().remove(0);
+        assert_eq!(
+            (rolled.open, rolled.high, rolled.low, rolled.close, rolled.volume, rolled.trade_count),
+            (rebuilt.open, rebuilt.high, rebuilt.low, rebuilt.close, rebuilt.volume, rebuilt.trade_count)
+        );
+        assert_eq!((rolled.first_trade_at, rolled.last_trade_at), (0, 610));
+    }
+
+    #[tokio::test]
+    async fn live_lease_survives_the_reaper() {
+        let storage = InMemoryStorage::default();
+        let mint = Pubkey::new_from_array([1; 32]);
+        storage.add_unprocessed_account(&account(mint).await).await.unwrap();
+
+        storage.lease_next_unprocessed_account().await.unwrap().unwrap();
+        assert_eq!(storage.heartbeat_account_leases().await.unwrap(), 1);
+        assert_eq!(storage.reap_expired_account_leases().await.unwrap(), 0);
+        assert_eq!(storage.get_in_flight_account_count().await.unwrap(), 1);
+    }
+}
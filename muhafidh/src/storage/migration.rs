@@ -1,7 +1,11 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::Result;
+use chrono::DateTime;
 use chrono::Utc;
+use sha2::Digest;
+use sha2::Sha256;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
@@ -11,7 +15,7 @@ use crate::error::postgres::PostgresClientError;
 use crate::storage::postgres::PostgresPool;
 
 /// Current schema version - increment this when adding new migrations
-pub const CURRENT_SCHEMA_VERSION: i64 = 18;
+pub const CURRENT_SCHEMA_VERSION: i64 = 34;
 
 /// A migration that can be applied to the database
 pub struct Migration {
@@ -21,6 +25,66 @@ pub struct Migration {
     pub name: String,
     /// The SQL to run for this migration - may contain multiple statements separated by semicolons
     pub sql: Vec<&'static str>,
+    /// The SQL that reverts this migration, or `None` when it can't be reverted
+    pub down: Option<Vec<&'static str>>,
+}
+
+impl Migration {
+    /// SHA-256 of the migration SQL. Indentation, runs of whitespace and blank lines are ignored, so
+    /// reformatting the source doesn't count as a change
+    pub fn checksum(&self) -> String {
+        let mut hasher = Sha256::new();
+        for statement in &self.sql {
+            for line in statement.lines().filter(|line| !line.trim().is_empty()) {
+                hasher.update(line.split_whitespace().collect::<Vec<&str>>().join(" ").as_bytes());
+                hasher.update(b"\n");
+            }
+            hasher.update(b";\n");
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// A migration recorded in the `migrations` table
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: i64,
+    pub name: String,
+    pub applied_at: DateTime<Utc>,
+    // None for migrations applied before checksums were recorded
+    pub checksum: Option<String>,
+}
+
+/// Where a migration stands against the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    // Applied, but its SQL changed afterwards
+    Modified,
+    // Recorded in the database but not defined in this build
+    Unknown,
+}
+
+impl MigrationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "modified",
+            MigrationState::Unknown => "unknown",
+        }
+    }
+}
+
+/// One line of `migrate status`
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub reversible: bool,
 }
 
 /// The Migrator manages database migrations
@@ -40,13 +104,24 @@ impl Migrator {
     pub async fn run_migrations(&self) -> Result<()> {
         // Create migrations table if it doesn't exist
         self.create_migrations_table().await?;
+        self.backfill_checksums().await?;
+
+        // Refuse to build on top of migrations whose SQL no longer matches what ran
+        let modified = self.verify_checksums().await?;
+        if !modified.is_empty() {
+            error!("migration_checksum_mismatch: {:?}", modified);
+            return Err(err_with_loc!(PostgresClientError::Other(format!(
+                "migration_checksum_mismatch: {:?}",
+                modified
+            ))));
+        }
 
         // Get all migrations that have been applied
         let applied = self.get_applied_migrations().await?;
 
         // Apply any migrations that haven't been applied yet
         for migration in self.get_migrations() {
-            if !applied.contains(&migration.version) {
+            if !applied.iter().any(|record| record.version == migration.version) {
                 info!("Applying migration {}_{}", migration.version, migration.name);
                 self.apply_migration(&migration).await?;
             }
@@ -55,26 +130,141 @@ impl Migrator {
         Ok(())
     }
 
-    /// Check if the database schema is at the expected version without applying migrations
-    pub async fn check_schema_version(&self) -> Result<bool> {
-        self.create_migrations_table().await?;
-        let applied = self.get_applied_migrations().await?;
+    /// Every known migration and every recorded one, with its state against the database. Read-only,
+    /// services run it at startup
+    pub async fn status(&self) -> Result<Vec<MigrationStatus>> {
+        let mut applied: HashMap<i64, AppliedMigration> = self
+            .get_applied_migrations()
+            .await?
+            .into_iter()
+            .map(|record| (record.version, record))
+            .collect();
+
+        let mut statuses = Vec::new();
+        for migration in self.get_migrations() {
+            let (state, applied_at) = match applied.remove(&migration.version) {
+                Some(record) => {
+                    let state = match &record.checksum {
+                        Some(checksum) if *checksum != migration.checksum() => MigrationState::Modified,
+                        _ => MigrationState::Applied,
+                    };
+                    (state, Some(record.applied_at))
+                },
+                None => (MigrationState::Pending, None),
+            };
+            statuses.push(MigrationStatus {
+                version: migration.version,
+                name: migration.name.clone(),
+                state,
+                applied_at,
+                reversible: migration.down.is_some(),
+            });
+        }
+        for record in applied.into_values() {
+            statuses.push(MigrationStatus {
+                version: record.version,
+                name: record.name,
+                state: MigrationState::Unknown,
+                applied_at: Some(record.applied_at),
+                reversible: false,
+            });
+        }
+        statuses.sort_by_key(|status| status.version);
+
+        Ok(statuses)
+    }
 
-        // Get the highest applied migration version
-        let current_version = applied.iter().max().copied().unwrap_or(0);
+    /// Versions of applied migrations whose SQL changed since they ran
+    pub async fn verify_checksums(&self) -> Result<Vec<i64>> {
+        let statuses = self.status().await?;
+        Ok(statuses
+            .iter()
+            .filter(|status| status.state == MigrationState::Modified)
+            .map(|status| status.version)
+            .collect())
+    }
+
+    /// Check the database schema matches this build without applying migrations: nothing pending,
+    /// nothing modified and nothing recorded that this build doesn't know
+    pub async fn check_schema_version(&self) -> Result<bool> {
+        let statuses = self.status().await?;
+        let versions_in = |state: MigrationState| {
+            statuses
+                .iter()
+                .filter(|status| status.state == state)
+                .map(|status| status.version)
+                .collect::<Vec<i64>>()
+        };
+        let pending = versions_in(MigrationState::Pending);
+        let modified = versions_in(MigrationState::Modified);
+        let unknown = versions_in(MigrationState::Unknown);
 
-        if current_version < CURRENT_SCHEMA_VERSION {
+        if !pending.is_empty() {
             warn!(
-                "Database schema version mismatch. Expected {}, found {}. Please run migrations.",
-                CURRENT_SCHEMA_VERSION, current_version
+                "Database schema version mismatch. Expected {}, pending migrations {:?}. Please run migrations.",
+                CURRENT_SCHEMA_VERSION, pending
             );
+        }
+        if !modified.is_empty() {
+            warn!("Applied migrations {:?} changed since they ran. See `migrate status`.", modified);
+        }
+        if !unknown.is_empty() {
+            warn!("Database has migrations {:?} this build doesn't know. Upgrade or run `migrate down`.", unknown);
+        }
+        if !pending.is_empty() || !modified.is_empty() || !unknown.is_empty() {
             return Ok(false);
         }
 
+        let current_version = statuses.iter().map(|status| status.version).max().unwrap_or(0);
         info!("Database schema version check passed. Current version: {}", current_version);
         Ok(true)
     }
 
+    /// Revert applied migrations newer than `target`, newest first. Returns the reverted versions
+    pub async fn rollback_to(
+        &self,
+        target: i64,
+    ) -> Result<Vec<i64>> {
+        self.create_migrations_table().await?;
+        let applied = self.get_applied_migrations().await?;
+        let migrations: HashMap<i64, Migration> = self
+            .get_migrations()
+            .into_iter()
+            .map(|migration| (migration.version, migration))
+            .collect();
+
+        // Check every step up front so a rollback doesn't stop halfway at an irreversible migration
+        let mut to_revert = Vec::new();
+        for record in applied.iter().rev().filter(|record| record.version > target) {
+            match migrations.get(&record.version) {
+                Some(migration) if migration.down.is_some() => to_revert.push(migration),
+                Some(migration) => {
+                    error!("migration_irreversible: {}_{}", migration.version, migration.name);
+                    return Err(err_with_loc!(PostgresClientError::Other(format!(
+                        "migration_irreversible: {}_{}",
+                        migration.version, migration.name
+                    ))));
+                },
+                None => {
+                    error!("migration_unknown: {}_{}", record.version, record.name);
+                    return Err(err_with_loc!(PostgresClientError::Other(format!(
+                        "migration_unknown: {}_{}",
+                        record.version, record.name
+                    ))));
+                },
+            }
+        }
+
+        let mut reverted = Vec::with_capacity(to_revert.len());
+        for migration in to_revert {
+            info!("Reverting migration {}_{}", migration.version, migration.name);
+            self.revert_migration(migration).await?;
+            reverted.push(migration.version);
+        }
+
+        Ok(reverted)
+    }
+
     /// Create the migrations table if it doesn't exist
     async fn create_migrations_table(&self) -> Result<()> {
         let conn = self.pool.get().await.map_err(|e| {
@@ -96,26 +286,97 @@ impl Migrator {
             err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_create_migrations_table: {}", e)))
         })?;
 
+        conn.execute("ALTER TABLE migrations ADD COLUMN IF NOT EXISTS checksum TEXT DEFAULT NULL", &[])
+            .await
+            .map_err(|e| {
+                error!("failed_to_add_migrations_checksum_column: {}", e);
+                err_with_loc!(PostgresClientError::TransactionError(format!(
+                    "failed_to_add_migrations_checksum_column: {}",
+                    e
+                )))
+            })?;
+
         Ok(())
     }
 
-    /// Get all migrations that have been applied to the database
-    async fn get_applied_migrations(&self) -> Result<Vec<i64>> {
+    /// Get all migrations that have been applied to the database. Only `run_migrations` and `rollback_to`
+    /// create or alter the migrations table, so it may be missing, or lack the checksum column
+    async fn get_applied_migrations(&self) -> Result<Vec<AppliedMigration>> {
         let conn = self.pool.get().await.map_err(|e| {
             error!("failed_to_get_client_pool_connection: {}", e);
             err_with_loc!(PostgresClientError::PoolError(e))
         })?;
 
-        let rows = conn
-            .query("SELECT version FROM migrations ORDER BY version ASC", &[])
+        let columns = conn
+            .query(
+                "SELECT column_name::TEXT FROM information_schema.columns
+                 WHERE table_schema = current_schema() AND table_name = 'migrations'",
+                &[],
+            )
             .await
             .map_err(|e| {
-                error!("failed_to_get_applied_migrations: {}", e);
-                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_applied_migrations: {}", e)))
+                error!("failed_to_get_migrations_columns: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_migrations_columns: {}", e)))
             })?;
+        if columns.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = if columns.iter().any(|row| row.get::<_, &str>(0) == "checksum") {
+            "SELECT version, name, applied_at, checksum FROM migrations ORDER BY version ASC"
+        } else {
+            "SELECT version, name, applied_at, NULL::TEXT FROM migrations ORDER BY version ASC"
+        };
+
+        let rows = conn.query(query, &[]).await.map_err(|e| {
+            error!("failed_to_get_applied_migrations: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_applied_migrations: {}", e)))
+        })?;
 
-        let versions = rows.iter().map(|row| row.get::<_, i64>(0)).collect();
-        Ok(versions)
+        let applied = rows
+            .iter()
+            .map(|row| AppliedMigration {
+                version: row.get(0),
+                name: row.get(1),
+                applied_at: row.get(2),
+                checksum: row.get(3),
+            })
+            .collect();
+        Ok(applied)
+    }
+
+    /// Record checksums for migrations applied before checksums existed, taking the current SQL as
+    /// what ran
+    async fn backfill_checksums(&self) -> Result<()> {
+        let applied = self.get_applied_migrations().await?;
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        for migration in self.get_migrations() {
+            let missing_checksum = applied
+                .iter()
+                .any(|record| record.version == migration.version && record.checksum.is_none());
+            if !missing_checksum {
+                continue;
+            }
+
+            conn.execute("UPDATE migrations SET checksum = $1 WHERE version = $2 AND checksum IS NULL", &[
+                &migration.checksum(),
+                &migration.version,
+            ])
+            .await
+            .map_err(|e| {
+                error!("failed_to_backfill_migration_checksum: {}_{}: {}", migration.version, migration.name, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_backfill_migration_checksum: {}_{}: {}",
+                    migration.version, migration.name, e
+                )))
+            })?;
+            info!("Recorded checksum for migration {}_{}", migration.version, migration.name);
+        }
+
+        Ok(())
     }
 
     /// Apply a migration to the database
@@ -147,11 +408,11 @@ impl Migrator {
 
         // Record that we applied this migration
         let now = Utc::now();
-
-        tx.execute("INSERT INTO migrations (version, name, applied_at) VALUES ($1, $2, $3)", &[
+        tx.execute("INSERT INTO migrations (version, name, applied_at, checksum) VALUES ($1, $2, $3, $4)", &[
             &migration.version,
             &migration.name,
             &now,
+            &migration.checksum(),
         ])
         .await
         .map_err(|e| {
@@ -172,6 +433,57 @@ impl Migrator {
         Ok(())
     }
 
+    /// Run a migration's down SQL and forget it was applied
+    async fn revert_migration(
+        &self,
+        migration: &Migration,
+    ) -> Result<()> {
+        let Some(down) = &migration.down else {
+            return Err(err_with_loc!(PostgresClientError::Other(format!(
+                "migration_irreversible: {}_{}",
+                migration.version, migration.name
+            ))));
+        };
+
+        let mut conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let tx = conn.transaction().await.map_err(|e| {
+            error!("failed_to_start_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
+        })?;
+
+        for (i, sql) in down.iter().enumerate() {
+            tx.execute(*sql, &[]).await.map_err(|e| {
+                error!("failed_to_execute_down_statement {}: {}_{}: {}", i, migration.version, migration.name, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_execute_down_statement {}: {}_{}: {}",
+                    i, migration.version, migration.name, e
+                )))
+            })?;
+        }
+
+        tx.execute("DELETE FROM migrations WHERE version = $1", &[&migration.version])
+            .await
+            .map_err(|e| {
+                error!("failed_to_unrecord_migration: {}_{}: {}", migration.version, migration.name, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_unrecord_migration: {}_{}: {}",
+                    migration.version, migration.name, e
+                )))
+            })?;
+
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        })?;
+
+        info!("Reverted migration {}_{}", migration.version, migration.name);
+        Ok(())
+    }
+
     /// Get all migrations that should be applied to the database
     fn get_migrations(&self) -> Vec<Migration> {
         // Create migrations for all our database objects
@@ -201,6 +513,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS tokens"]),
             },
             // Migration 2: Create CEX metrics table
             Migration {
@@ -219,6 +532,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS cex_metrics"]),
             },
             // Migration 3: Create CEX-token relations table
             Migration {
@@ -235,6 +549,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS cex_token_relations"]),
             },
             // Migration 4: Create CEX token ATH table
             Migration {
@@ -252,6 +567,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS cex_token_ath"]),
             },
             // Migration 5: Create indexes for tokens table
             Migration {
@@ -261,12 +577,17 @@ impl Migrator {
                     "CREATE INDEX IF NOT EXISTS idx_tokens_creator ON tokens(creator)",
                     "CREATE INDEX IF NOT EXISTS idx_tokens_mint ON tokens(mint)",
                 ],
+                down: Some(vec![
+                    "DROP INDEX IF EXISTS idx_tokens_creator",
+                    "DROP INDEX IF EXISTS idx_tokens_mint",
+                ]),
             },
             // Migration 6: Create indexes for CEX metrics table
             Migration {
                 version: 6,
                 name: String::from("create_cex_metrics_indexes"),
                 sql: vec!["CREATE INDEX IF NOT EXISTS idx_cex_metrics_address ON cex_metrics(address)"],
+                down: Some(vec!["DROP INDEX IF EXISTS idx_cex_metrics_address"]),
             },
             // Migration 7: Create indexes for CEX-token relations table
             Migration {
@@ -276,6 +597,10 @@ impl Migrator {
                     "CREATE INDEX IF NOT EXISTS idx_cex_token_relations_cex ON cex_token_relations(cex_address)",
                     "CREATE INDEX IF NOT EXISTS idx_cex_token_relations_token ON cex_token_relations(token_mint)",
                 ],
+                down: Some(vec![
+                    "DROP INDEX IF EXISTS idx_cex_token_relations_cex",
+                    "DROP INDEX IF EXISTS idx_cex_token_relations_token",
+                ]),
             },
             // Migration 8: Create indexes for CEX token ATH table
             Migration {
@@ -285,6 +610,10 @@ impl Migrator {
                     "CREATE INDEX IF NOT EXISTS idx_cex_token_ath_cex ON cex_token_ath(cex_address)",
                     "CREATE INDEX IF NOT EXISTS idx_cex_token_ath_token ON cex_token_ath(token_mint)",
                 ],
+                down: Some(vec![
+                    "DROP INDEX IF EXISTS idx_cex_token_ath_cex",
+                    "DROP INDEX IF EXISTS idx_cex_token_ath_token",
+                ]),
             },
             // Migration 9: Create token price history table
             Migration {
@@ -302,6 +631,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS token_price_history"]),
             },
             // Migration 10: Create token volume history table
             Migration {
@@ -319,6 +649,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS token_volume_history"]),
             },
             // Migration 11: Create CEX activity history table
             Migration {
@@ -336,6 +667,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS cex_activity_history"]),
             },
             // Migration 12: Create indexes for time series tables
             Migration {
@@ -349,18 +681,28 @@ impl Migrator {
                     "CREATE INDEX IF NOT EXISTS idx_cex_activity_history_cex ON cex_activity_history(cex_address)",
                     "CREATE INDEX IF NOT EXISTS idx_cex_activity_history_timestamp ON cex_activity_history(timestamp)",
                 ],
+                down: Some(vec![
+                    "DROP INDEX IF EXISTS idx_token_price_history_mint",
+                    "DROP INDEX IF EXISTS idx_token_price_history_timestamp",
+                    "DROP INDEX IF EXISTS idx_token_volume_history_mint",
+                    "DROP INDEX IF EXISTS idx_token_volume_history_timestamp",
+                    "DROP INDEX IF EXISTS idx_cex_activity_history_cex",
+                    "DROP INDEX IF EXISTS idx_cex_activity_history_timestamp",
+                ]),
             },
             // Migration 13: Create PostGIS extension
             Migration {
                 version: 13,
                 name: String::from("create_postgis_extension"),
                 sql: vec!["CREATE EXTENSION IF NOT EXISTS postgis"],
+                down: Some(vec!["DROP EXTENSION IF EXISTS postgis"]),
             },
             // Migration 14: Create pgRouting extension
             Migration {
                 version: 14,
                 name: String::from("create_pgrouting_extension"),
                 sql: vec!["CREATE EXTENSION IF NOT EXISTS pgrouting"],
+                down: Some(vec!["DROP EXTENSION IF EXISTS pgrouting"]),
             },
             // Migration 15: Create wallet_nodes table
             Migration {
@@ -379,6 +721,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS wallet_nodes"]),
             },
             // Migration 16: Create wallet_edges table
             Migration {
@@ -402,6 +745,7 @@ impl Migrator {
                 )
                 "#,
                 ],
+                down: Some(vec!["DROP TABLE IF EXISTS wallet_edges"]),
             },
             // Migration 17: Create indexes for wallet nodes and edges tables
             Migration {
@@ -413,6 +757,12 @@ impl Migrator {
                     "CREATE INDEX IF NOT EXISTS idx_wallet_edges_pubkeys ON wallet_edges(source_pubkey, target_pubkey)",
                     "CREATE INDEX IF NOT EXISTS idx_wallet_edges_mint ON wallet_edges(mint)",
                 ],
+                down: Some(vec![
+                    "DROP INDEX IF EXISTS idx_wallet_nodes_pubkey",
+                    "DROP INDEX IF EXISTS idx_wallet_edges_source_target",
+                    "DROP INDEX IF EXISTS idx_wallet_edges_pubkeys",
+                    "DROP INDEX IF EXISTS idx_wallet_edges_mint",
+                ]),
             },
             // Migration 18: Add missing columns to tokens table
             Migration {
@@ -428,6 +778,592 @@ impl Migrator {
                     "ALTER TABLE tokens ADD COLUMN IF NOT EXISTS all_time_high_price BIGINT NOT NULL DEFAULT 0",
                     "ALTER TABLE tokens ADD COLUMN IF NOT EXISTS all_time_high_price_at BIGINT NOT NULL DEFAULT 0",
                 ],
+                // The columns are also part of the tokens table from migration 1, nothing to undo
+                down: Some(vec![]),
+            },
+            // Migration 19: Track launch outcomes per CEX-funded token
+            Migration {
+                version: 19,
+                name: String::from("add_cex_token_ath_outcome_columns"),
+                sql: vec![
+                    "ALTER TABLE cex_token_ath ADD COLUMN IF NOT EXISTS launch_price BIGINT NOT NULL DEFAULT 0",
+                    "ALTER TABLE cex_token_ath ADD COLUMN IF NOT EXISTS ath_multiple FLOAT8 DEFAULT NULL",
+                    "ALTER TABLE cex_token_ath ADD COLUMN IF NOT EXISTS time_to_ath_secs BIGINT DEFAULT NULL",
+                    "ALTER TABLE cex_token_ath ADD COLUMN IF NOT EXISTS updated_at TIMESTAMP WITH TIME ZONE DEFAULT \
+                     NOW()",
+                ],
+                down: Some(vec![
+                    "ALTER TABLE cex_token_ath DROP COLUMN IF EXISTS launch_price",
+                    "ALTER TABLE cex_token_ath DROP COLUMN IF EXISTS ath_multiple",
+                    "ALTER TABLE cex_token_ath DROP COLUMN IF EXISTS time_to_ath_secs",
+                    "ALTER TABLE cex_token_ath DROP COLUMN IF EXISTS updated_at",
+                ]),
+            },
+            // Migration 20: Create CEX activity rollups table
+            Migration {
+                version: 20,
+                name: String::from("create_cex_activity_rollups_table"),
+                sql: vec![
+                    r#"
+                CREATE TABLE IF NOT EXISTS cex_activity_rollups (
+                    id SERIAL PRIMARY KEY,
+                    cex_address TEXT NOT NULL,
+                    granularity TEXT NOT NULL,
+                    bucket_start BIGINT NOT NULL,
+                    token_count BIGINT NOT NULL,
+                    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(cex_address, granularity, bucket_start)
+                )
+                "#,
+                    "CREATE INDEX IF NOT EXISTS idx_cex_activity_rollups_bucket ON cex_activity_rollups(granularity, \
+                     bucket_start)",
+                ],
+                down: Some(vec!["DROP TABLE IF EXISTS cex_activity_rollups"]),
+            },
+            // Migration 21: Aggregate repeated transfers into one weighted edge per wallet pair and mint
+            Migration {
+                version: 21,
+                name: String::from("aggregate_wallet_edges"),
+                sql: vec![
+                    r#"
+                CREATE TABLE IF NOT EXISTS wallet_transfers (
+                    id SERIAL PRIMARY KEY,
+                    source_pubkey TEXT NOT NULL,
+                    target_pubkey TEXT NOT NULL,
+                    mint TEXT NOT NULL,
+                    amount FLOAT NOT NULL,
+                    timestamp BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(source_pubkey, target_pubkey, mint, timestamp)
+                )
+                "#,
+                    "CREATE INDEX IF NOT EXISTS idx_wallet_transfers_pair ON wallet_transfers(source_pubkey, \
+                     target_pubkey, mint)",
+                    r#"
+                INSERT INTO wallet_transfers (source_pubkey, target_pubkey, mint, amount, timestamp)
+                SELECT source_pubkey, target_pubkey, mint, amount, timestamp FROM wallet_edges
+                ON CONFLICT (source_pubkey, target_pubkey, mint, timestamp) DO NOTHING
+                "#,
+                    "ALTER TABLE wallet_edges ADD COLUMN IF NOT EXISTS transfer_count BIGINT NOT NULL DEFAULT 1",
+                    "ALTER TABLE wallet_edges ADD COLUMN IF NOT EXISTS first_timestamp BIGINT DEFAULT NULL",
+                    "ALTER TABLE wallet_edges ADD COLUMN IF NOT EXISTS last_timestamp BIGINT DEFAULT NULL",
+                    r#"
+                DELETE FROM wallet_edges e
+                USING wallet_edges keep
+                WHERE e.source_pubkey = keep.source_pubkey
+                AND e.target_pubkey = keep.target_pubkey
+                AND e.mint = keep.mint
+                AND e.id > keep.id
+                "#,
+                    r#"
+                UPDATE wallet_edges e SET
+                    amount = agg.total_amount,
+                    transfer_count = agg.transfer_count,
+                    first_timestamp = agg.first_timestamp,
+                    last_timestamp = agg.last_timestamp,
+                    timestamp = agg.last_timestamp
+                FROM (
+                    SELECT source_pubkey, target_pubkey, mint,
+                        SUM(amount) AS total_amount,
+                        COUNT(*) AS transfer_count,
+                        MIN(timestamp) AS first_timestamp,
+                        MAX(timestamp) AS last_timestamp
+                    FROM wallet_transfers
+                    GROUP BY source_pubkey, target_pubkey, mint
+                ) agg
+                WHERE e.source_pubkey = agg.source_pubkey
+                AND e.target_pubkey = agg.target_pubkey
+                AND e.mint = agg.mint
+                "#,
+                    "ALTER TABLE wallet_edges DROP CONSTRAINT IF EXISTS \
+                     wallet_edges_source_pubkey_target_pubkey_mint_timestamp_key",
+                    "CREATE UNIQUE INDEX IF NOT EXISTS idx_wallet_edges_pair_mint ON wallet_edges(source_pubkey, \
+                     target_pubkey, mint)",
+                ],
+                // Collapsed edges and dropped their per-transfer rows from wallet_edges, which can't be restored
+                down: None,
+            },
+            // Migration 22: Create OHLCV candles table
+            Migration {
+                version: 22,
+                name: String::from("create_token_candles_table"),
+                sql: vec![
+                    r#"
+                CREATE TABLE IF NOT EXISTS token_candles (
+                    id SERIAL PRIMARY KEY,
+                    mint TEXT NOT NULL,
+                    interval TEXT NOT NULL,
+                    bucket_start BIGINT NOT NULL,
+                    open BIGINT NOT NULL,
+                    high BIGINT NOT NULL,
+                    low BIGINT NOT NULL,
+                    close BIGINT NOT NULL,
+                    volume BIGINT NOT NULL DEFAULT 0,
+                    trade_count BIGINT NOT NULL DEFAULT 0,
+                    first_trade_at BIGINT NOT NULL,
+                    last_trade_at BIGINT NOT NULL,
+                    updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(mint, interval, bucket_start)
+                )
+                "#,
+                    "CREATE INDEX IF NOT EXISTS idx_token_candles_interval_bucket ON token_candles(interval, \
+                     bucket_start)",
+                ],
+                down: Some(vec!["DROP TABLE IF EXISTS token_candles"]),
+            },
+            // Migration 23: Create holder concentration snapshot tables
+            Migration {
+                version: 23,
+                name: String::from("create_token_holder_snapshots_tables"),
+                sql: vec![
+                    r#"
+                CREATE TABLE IF NOT EXISTS token_holder_snapshots (
+                    id SERIAL PRIMARY KEY,
+                    mint TEXT NOT NULL,
+                    taken_at BIGINT NOT NULL,
+                    total_supply BIGINT NOT NULL,
+                    holder_count BIGINT NOT NULL,
+                    top10_amount BIGINT NOT NULL,
+                    top10_share FLOAT NOT NULL,
+                    funded_holder_count INTEGER NOT NULL DEFAULT 0,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(mint, taken_at)
+                )
+                "#,
+                    r#"
+                CREATE TABLE IF NOT EXISTS token_holder_snapshot_holders (
+                    snapshot_id INTEGER NOT NULL REFERENCES token_holder_snapshots(id) ON DELETE CASCADE,
+                    rank INTEGER NOT NULL,
+                    token_account TEXT NOT NULL,
+                    owner TEXT NOT NULL,
+                    amount BIGINT NOT NULL,
+                    share FLOAT NOT NULL,
+                    in_funding_graph BOOLEAN NOT NULL DEFAULT FALSE,
+                    PRIMARY KEY (snapshot_id, rank)
+                )
+                "#,
+                    "CREATE INDEX IF NOT EXISTS idx_token_holder_snapshot_holders_owner ON \
+                     token_holder_snapshot_holders(owner)",
+                ],
+                down: Some(vec![
+                    "DROP TABLE IF EXISTS token_holder_snapshot_holders",
+                    "DROP TABLE IF EXISTS token_holder_snapshots",
+                ]),
+            },
+            // Migration 24: Create launch bundle detection tables
+            Migration {
+                version: 24,
+                name: String::from("create_launch_bundle_tables"),
+                sql: vec![
+                    r#"
+                CREATE TABLE IF NOT EXISTS token_launch_bundles (
+                    mint TEXT PRIMARY KEY,
+                    creator TEXT NOT NULL,
+                    create_slot BIGINT NOT NULL,
+                    window_slots BIGINT NOT NULL,
+                    early_buy_count INTEGER NOT NULL DEFAULT 0,
+                    bundler_count INTEGER DEFAULT NULL,
+                    bundled_token_amount BIGINT DEFAULT NULL,
+                    bundled_supply_share FLOAT DEFAULT NULL,
+                    analyzed_at BIGINT DEFAULT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                )
+                "#,
+                    r#"
+                CREATE TABLE IF NOT EXISTS token_early_buys (
+                    id SERIAL PRIMARY KEY,
+                    mint TEXT NOT NULL,
+                    signature TEXT NOT NULL,
+                    buyer TEXT NOT NULL,
+                    fee_payer TEXT NOT NULL,
+                    slot BIGINT NOT NULL,
+                    sol_amount BIGINT NOT NULL,
+                    token_amount BIGINT NOT NULL,
+                    UNIQUE(mint, signature, buyer)
+                )
+                "#,
+                    r#"
+                CREATE TABLE IF NOT EXISTS token_bundlers (
+                    mint TEXT NOT NULL,
+                    wallet TEXT NOT NULL,
+                    signals TEXT[] NOT NULL,
+                    sol_amount BIGINT NOT NULL,
+                    token_amount BIGINT NOT NULL,
+                    supply_share FLOAT NOT NULL,
+                    PRIMARY KEY (mint, wallet)
+                )
+                "#,
+                    "CREATE INDEX IF NOT EXISTS idx_token_bundlers_wallet ON token_bundlers(wallet)",
+                ],
+                down: Some(vec![
+                    "DROP TABLE IF EXISTS token_bundlers",
+                    "DROP TABLE IF EXISTS token_early_buys",
+                    "DROP TABLE IF EXISTS token_launch_bundles",
+                ]),
+            },
+            // Migration 25: Create creator reputation history table
+            Migration {
+                version: 25,
+                name: String::from("create_creator_reputation_history_table"),
+                sql: vec![
+                    r#"
+                CREATE TABLE IF NOT EXISTS creator_reputation_history (
+                    id SERIAL PRIMARY KEY,
+                    creator TEXT NOT NULL,
+                    mint TEXT NOT NULL,
+                    outcome TEXT NOT NULL,
+                    wallets TEXT[] NOT NULL DEFAULT '{}',
+                    token_amount BIGINT NOT NULL DEFAULT 0,
+                    sol_amount BIGINT NOT NULL DEFAULT 0,
+                    share_pct FLOAT DEFAULT NULL,
+                    price_impact_pct FLOAT DEFAULT NULL,
+                    occurred_at BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(creator, mint, outcome)
+                )
+                "#,
+                    "CREATE INDEX IF NOT EXISTS idx_creator_reputation_history_creator ON \
+                     creator_reputation_history(creator, occurred_at)",
+                ],
+                down: Some(vec!["DROP TABLE IF EXISTS creator_reputation_history"]),
+            },
+            // Migration 26: Create price alert rule and hit tables
+            Migration {
+                version: 26,
+                name: String::from("create_price_alert_tables"),
+                sql: vec![
+                    r#"
+                CREATE TABLE IF NOT EXISTS price_alert_rules (
+                    id SERIAL PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    mint TEXT DEFAULT NULL,
+                    cex_funded_only BOOLEAN NOT NULL DEFAULT FALSE,
+                    kind TEXT NOT NULL,
+                    threshold FLOAT NOT NULL,
+                    window_secs BIGINT DEFAULT NULL,
+                    cooldown_secs BIGINT NOT NULL DEFAULT 3600,
+                    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+                )
+                "#,
+                    r#"
+                CREATE TABLE IF NOT EXISTS price_alert_hits (
+                    id SERIAL PRIMARY KEY,
+                    rule_id INTEGER NOT NULL REFERENCES price_alert_rules(id) ON DELETE CASCADE,
+                    mint TEXT NOT NULL,
+                    observed FLOAT NOT NULL,
+                    price_sol FLOAT NOT NULL,
+                    market_cap_sol FLOAT NOT NULL,
+                    market_cap_usd FLOAT DEFAULT NULL,
+                    triggered_at BIGINT NOT NULL
+                )
+                "#,
+                    "CREATE INDEX IF NOT EXISTS idx_price_alert_hits_rule ON price_alert_hits(rule_id, triggered_at)",
+                    "CREATE INDEX IF NOT EXISTS idx_price_alert_hits_mint ON price_alert_hits(mint)",
+                ],
+                down: Some(vec![
+                    "DROP TABLE IF EXISTS price_alert_hits",
+                    "DROP TABLE IF EXISTS price_alert_rules",
+                ]),
+            },
+            // Migration 27: Range-partition the history tables by day so expired days can be dropped whole
+            Migration {
+                version: 27,
+                name: String::from("partition_history_tables"),
+                sql: vec![
+                    "CREATE SCHEMA IF NOT EXISTS history_archive",
+                    "ALTER TABLE token_price_history RENAME TO token_price_history_legacy",
+                    "ALTER TABLE token_price_history_legacy RENAME CONSTRAINT token_price_history_mint_timestamp_key \
+                     TO token_price_history_legacy_mint_timestamp_key",
+                    "DROP INDEX IF EXISTS idx_token_price_history_mint",
+                    "DROP INDEX IF EXISTS idx_token_price_history_timestamp",
+                    r#"
+                CREATE TABLE token_price_history (
+                    mint TEXT NOT NULL,
+                    price BIGINT NOT NULL,
+                    timestamp BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(mint, timestamp)
+                ) PARTITION BY RANGE (timestamp)
+                "#,
+                    "CREATE TABLE token_price_history_default PARTITION OF token_price_history DEFAULT",
+                    r#"
+                INSERT INTO token_price_history (mint, price, timestamp, created_at)
+                SELECT mint, price, timestamp, created_at FROM token_price_history_legacy
+                "#,
+                    "DROP TABLE token_price_history_legacy",
+                    "CREATE INDEX IF NOT EXISTS idx_token_price_history_mint ON token_price_history(mint)",
+                    "CREATE INDEX IF NOT EXISTS idx_token_price_history_timestamp ON token_price_history(timestamp)",
+                    "ALTER TABLE token_volume_history RENAME TO token_volume_history_legacy",
+                    "ALTER TABLE token_volume_history_legacy RENAME CONSTRAINT \
+                     token_volume_history_mint_timestamp_key TO token_volume_history_legacy_mint_timestamp_key",
+                    "DROP INDEX IF EXISTS idx_token_volume_history_mint",
+                    "DROP INDEX IF EXISTS idx_token_volume_history_timestamp",
+                    r#"
+                CREATE TABLE token_volume_history (
+                    mint TEXT NOT NULL,
+                    volume BIGINT NOT NULL,
+                    timestamp BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(mint, timestamp)
+                ) PARTITION BY RANGE (timestamp)
+                "#,
+                    "CREATE TABLE token_volume_history_default PARTITION OF token_volume_history DEFAULT",
+                    r#"
+                INSERT INTO token_volume_history (mint, volume, timestamp, created_at)
+                SELECT mint, volume, timestamp, created_at FROM token_volume_history_legacy
+                "#,
+                    "DROP TABLE token_volume_history_legacy",
+                    "CREATE INDEX IF NOT EXISTS idx_token_volume_history_mint ON token_volume_history(mint)",
+                    "CREATE INDEX IF NOT EXISTS idx_token_volume_history_timestamp ON token_volume_history(timestamp)",
+                    "ALTER TABLE cex_activity_history RENAME TO cex_activity_history_legacy",
+                    "ALTER TABLE cex_activity_history_legacy RENAME CONSTRAINT \
+                     cex_activity_history_cex_address_timestamp_key TO \
+                     cex_activity_history_legacy_cex_address_timestamp_key",
+                    "DROP INDEX IF EXISTS idx_cex_activity_history_cex",
+                    "DROP INDEX IF EXISTS idx_cex_activity_history_timestamp",
+                    r#"
+                CREATE TABLE cex_activity_history (
+                    cex_address TEXT NOT NULL,
+                    token_count BIGINT NOT NULL,
+                    timestamp BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(cex_address, timestamp)
+                ) PARTITION BY RANGE (timestamp)
+                "#,
+                    "CREATE TABLE cex_activity_history_default PARTITION OF cex_activity_history DEFAULT",
+                    r#"
+                INSERT INTO cex_activity_history (cex_address, token_count, timestamp, created_at)
+                SELECT cex_address, token_count, timestamp, created_at FROM cex_activity_history_legacy
+                "#,
+                    "DROP TABLE cex_activity_history_legacy",
+                    "CREATE INDEX IF NOT EXISTS idx_cex_activity_history_cex ON cex_activity_history(cex_address)",
+                    "CREATE INDEX IF NOT EXISTS idx_cex_activity_history_timestamp ON cex_activity_history(timestamp)",
+                ],
+                // Partitions archived into history_archive stay there
+                down: Some(vec![
+                    "ALTER TABLE token_price_history RENAME TO token_price_history_partitioned",
+                    "DROP INDEX IF EXISTS idx_token_price_history_mint",
+                    "DROP INDEX IF EXISTS idx_token_price_history_timestamp",
+                    r#"
+                CREATE TABLE token_price_history (
+                    id SERIAL PRIMARY KEY,
+                    mint TEXT NOT NULL,
+                    price BIGINT NOT NULL,
+                    timestamp BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(mint, timestamp)
+                )
+                "#,
+                    r#"
+                INSERT INTO token_price_history (mint, price, timestamp, created_at)
+                SELECT mint, price, timestamp, created_at FROM token_price_history_partitioned
+                "#,
+                    "DROP TABLE token_price_history_partitioned",
+                    "CREATE INDEX IF NOT EXISTS idx_token_price_history_mint ON token_price_history(mint)",
+                    "CREATE INDEX IF NOT EXISTS idx_token_price_history_timestamp ON token_price_history(timestamp)",
+                    "ALTER TABLE token_volume_history RENAME TO token_volume_history_partitioned",
+                    "DROP INDEX IF EXISTS idx_token_volume_history_mint",
+                    "DROP INDEX IF EXISTS idx_token_volume_history_timestamp",
+                    r#"
+                CREATE TABLE token_volume_history (
+                    id SERIAL PRIMARY KEY,
+                    mint TEXT NOT NULL,
+                    volume BIGINT NOT NULL,
+                    timestamp BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(mint, timestamp)
+                )
+                "#,
+                    r#"
+                INSERT INTO token_volume_history (mint, volume, timestamp, created_at)
+                SELECT mint, volume, timestamp, created_at FROM token_volume_history_partitioned
+                "#,
+                    "DROP TABLE token_volume_history_partitioned",
+                    "CREATE INDEX IF NOT EXISTS idx_token_volume_history_mint ON token_volume_history(mint)",
+                    "CREATE INDEX IF NOT EXISTS idx_token_volume_history_timestamp ON token_volume_history(timestamp)",
+                    "ALTER TABLE cex_activity_history RENAME TO cex_activity_history_partitioned",
+                    "DROP INDEX IF EXISTS idx_cex_activity_history_cex",
+                    "DROP INDEX IF EXISTS idx_cex_activity_history_timestamp",
+                    r#"
+                CREATE TABLE cex_activity_history (
+                    id SERIAL PRIMARY KEY,
+                    cex_address TEXT NOT NULL,
+                    token_count BIGINT NOT NULL,
+                    timestamp BIGINT NOT NULL,
+                    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+                    UNIQUE(cex_address, timestamp)
+                )
+                "#,
+                    r#"
+                INSERT INTO cex_activity_history (cex_address, token_count, timestamp, created_at)
+                SELECT cex_address, token_count, timestamp, created_at FROM cex_activity_history_partitioned
+                "#,
+                    "DROP TABLE cex_activity_history_partitioned",
+                    "CREATE INDEX IF NOT EXISTS idx_cex_activity_history_cex ON cex_activity_history(cex_address)",
+                    "CREATE INDEX IF NOT EXISTS idx_cex_activity_history_timestamp ON cex_activity_history(timestamp)",
+                ]),
+            },
+            // Migration 28: Track when rows last changed so exports only pick up what changed
+            Migration {
+                version: 28,
+                name: String::from("track_row_changes"),
+                sql: vec![
+                    r#"
+                CREATE OR REPLACE FUNCTION touch_changed_at() RETURNS TRIGGER AS $$
+                BEGIN
+                    NEW.changed_at = NOW();
+                    RETURN NEW;
+                END;
+                $$ LANGUAGE plpgsql
+                "#,
+                    "ALTER TABLE tokens ADD COLUMN IF NOT EXISTS changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT \
+                     NOW()",
+                    "DROP TRIGGER IF EXISTS tokens_touch_changed_at ON tokens",
+                    "CREATE TRIGGER tokens_touch_changed_at BEFORE UPDATE ON tokens FOR EACH ROW WHEN (OLD IS \
+                     DISTINCT FROM NEW) EXECUTE FUNCTION touch_changed_at()",
+                    "CREATE INDEX IF NOT EXISTS idx_tokens_changed_at ON tokens(changed_at)",
+                    "ALTER TABLE wallet_nodes ADD COLUMN IF NOT EXISTS changed_at TIMESTAMP WITH TIME ZONE NOT NULL \
+                     DEFAULT NOW()",
+                    "DROP TRIGGER IF EXISTS wallet_nodes_touch_changed_at ON wallet_nodes",
+                    "CREATE TRIGGER wallet_nodes_touch_changed_at BEFORE UPDATE ON wallet_nodes FOR EACH ROW WHEN \
+                     (OLD IS DISTINCT FROM NEW) EXECUTE FUNCTION touch_changed_at()",
+                    "CREATE INDEX IF NOT EXISTS idx_wallet_nodes_changed_at ON wallet_nodes(changed_at)",
+                    "ALTER TABLE wallet_edges ADD COLUMN IF NOT EXISTS changed_at TIMESTAMP WITH TIME ZONE NOT NULL \
+                     DEFAULT NOW()",
+                    "DROP TRIGGER IF EXISTS wallet_edges_touch_changed_at ON wallet_edges",
+                    "CREATE TRIGGER wallet_edges_touch_changed_at BEFORE UPDATE ON wallet_edges FOR EACH ROW WHEN \
+                     (OLD IS DISTINCT FROM NEW) EXECUTE FUNCTION touch_changed_at()",
+                    "CREATE INDEX IF NOT EXISTS idx_wallet_edges_changed_at ON wallet_edges(changed_at)",
+                ],
+                down: Some(vec![
+                    "DROP TRIGGER IF EXISTS tokens_touch_changed_at ON tokens",
+                    "DROP INDEX IF EXISTS idx_tokens_changed_at",
+                    "ALTER TABLE tokens DROP COLUMN IF EXISTS changed_at",
+                    "DROP TRIGGER IF EXISTS wallet_nodes_touch_changed_at ON wallet_nodes",
+                    "DROP INDEX IF EXISTS idx_wallet_nodes_changed_at",
+                    "ALTER TABLE wallet_nodes DROP COLUMN IF EXISTS changed_at",
+                    "DROP TRIGGER IF EXISTS wallet_edges_touch_changed_at ON wallet_edges",
+                    "DROP INDEX IF EXISTS idx_wallet_edges_changed_at",
+                    "ALTER TABLE wallet_edges DROP COLUMN IF EXISTS changed_at",
+                    "DROP FUNCTION IF EXISTS touch_changed_at()",
+                ]),
+            },
+            // Migration 29: Index tokens for lookups, newest-first pagination and name/symbol search
+            Migration {
+                version: 29,
+                name: String::from("create_token_query_indexes"),
+                sql: vec![
+                    "CREATE EXTENSION IF NOT EXISTS pg_trgm",
+                    "CREATE INDEX IF NOT EXISTS idx_tokens_created_at_mint ON tokens(created_at DESC, mint DESC)",
+                    "CREATE INDEX IF NOT EXISTS idx_tokens_platform_created_at ON tokens(platform, created_at DESC)",
+                    "CREATE INDEX IF NOT EXISTS idx_tokens_is_bonded_created_at ON tokens(is_bonded, created_at DESC)",
+                    "CREATE INDEX IF NOT EXISTS idx_tokens_cex_sources ON tokens USING GIN (cex_sources)",
+                    "CREATE INDEX IF NOT EXISTS idx_tokens_name_trgm ON tokens USING GIN (name gin_trgm_ops)",
+                    "CREATE INDEX IF NOT EXISTS idx_tokens_symbol_trgm ON tokens USING GIN (symbol gin_trgm_ops)",
+                ],
+                down: Some(vec![
+                    "DROP INDEX IF EXISTS idx_tokens_symbol_trgm",
+                    "DROP INDEX IF EXISTS idx_tokens_name_trgm",
+                    "DROP INDEX IF EXISTS idx_tokens_cex_sources",
+                    "DROP INDEX IF EXISTS idx_tokens_is_bonded_created_at",
+                    "DROP INDEX IF EXISTS idx_tokens_platform_created_at",
+                    "DROP INDEX IF EXISTS idx_tokens_created_at_mint",
+                    // pg_trgm stays, other objects may have come to depend on it
+                ]),
+            },
+            // Migration 30: Index CEX wallets, the start points of every funding route
+            Migration {
+                version: 30,
+                name: String::from("index_cex_wallet_nodes"),
+                sql: vec!["CREATE INDEX IF NOT EXISTS idx_wallet_nodes_cex ON wallet_nodes(id) WHERE is_cex"],
+                down: Some(vec!["DROP INDEX IF EXISTS idx_wallet_nodes_cex"]),
+            },
+            // Migration 31: Keep every transfer between a pair, including several in the same second
+            Migration {
+                version: 31,
+                name: String::from("key_wallet_transfers_by_seq"),
+                sql: vec![
+                    "ALTER TABLE wallet_transfers ADD COLUMN IF NOT EXISTS seq INTEGER NOT NULL DEFAULT 0",
+                    "ALTER TABLE wallet_transfers DROP CONSTRAINT IF EXISTS \
+                     wallet_transfers_source_pubkey_target_pubkey_mint_timestamp_key",
+                    "ALTER TABLE wallet_transfers ADD CONSTRAINT wallet_transfers_transfer_key UNIQUE (source_pubkey, \
+                     target_pubkey, mint, timestamp, seq)",
+                ],
+                down: Some(vec![
+                    "DELETE FROM wallet_transfers WHERE seq > 0",
+                    "ALTER TABLE wallet_transfers DROP CONSTRAINT IF EXISTS wallet_transfers_transfer_key",
+                    "ALTER TABLE wallet_transfers ADD CONSTRAINT \
+                     wallet_transfers_source_pubkey_target_pubkey_mint_timestamp_key UNIQUE (source_pubkey, \
+                     target_pubkey, mint, timestamp)",
+                    "ALTER TABLE wallet_transfers DROP COLUMN IF EXISTS seq",
+                ]),
+            },
+            // Migration 32: Key price and volume history by trade so trades in the same second are all kept
+            Migration {
+                version: 32,
+                name: String::from("key_price_history_by_trade"),
+                sql: vec![
+                    "ALTER TABLE token_price_history ADD COLUMN IF NOT EXISTS trade_id TEXT NOT NULL DEFAULT ''",
+                    "ALTER TABLE token_price_history DROP CONSTRAINT IF EXISTS token_price_history_mint_timestamp_key",
+                    "ALTER TABLE token_price_history ADD CONSTRAINT token_price_history_trade_key UNIQUE (mint, \
+                     timestamp, trade_id)",
+                    "ALTER TABLE token_volume_history ADD COLUMN IF NOT EXISTS trade_id TEXT NOT NULL DEFAULT ''",
+                    "ALTER TABLE token_volume_history DROP CONSTRAINT IF EXISTS \
+                     token_volume_history_mint_timestamp_key",
+                    "ALTER TABLE token_volume_history ADD CONSTRAINT token_volume_history_trade_key UNIQUE (mint, \
+                     timestamp, trade_id)",
+                ],
+                down: Some(vec![
+                    "DELETE FROM token_price_history p USING token_price_history keep WHERE keep.mint = p.mint AND \
+                     keep.timestamp = p.timestamp AND keep.trade_id > p.trade_id",
+                    "ALTER TABLE token_price_history DROP CONSTRAINT IF EXISTS token_price_history_trade_key",
+                    "ALTER TABLE token_price_history ADD CONSTRAINT token_price_history_mint_timestamp_key UNIQUE \
+                     (mint, timestamp)",
+                    "ALTER TABLE token_price_history DROP COLUMN IF EXISTS trade_id",
+                    "DELETE FROM token_volume_history v USING token_volume_history keep WHERE keep.mint = v.mint AND \
+                     keep.timestamp = v.timestamp AND keep.trade_id > v.trade_id",
+                    "ALTER TABLE token_volume_history DROP CONSTRAINT IF EXISTS token_volume_history_trade_key",
+                    "ALTER TABLE token_volume_history ADD CONSTRAINT token_volume_history_mint_timestamp_key UNIQUE \
+                     (mint, timestamp)",
+                    "ALTER TABLE token_volume_history DROP COLUMN IF EXISTS trade_id",
+                ]),
+            },
+            // Migration 33: Track when price and volume history rows last changed, like migration 28
+            Migration {
+                version: 33,
+                name: String::from("track_history_changes"),
+                sql: vec![
+                    "ALTER TABLE token_price_history ADD COLUMN IF NOT EXISTS changed_at TIMESTAMP WITH TIME ZONE NOT \
+                     NULL DEFAULT NOW()",
+                    "DROP TRIGGER IF EXISTS token_price_history_touch_changed_at ON token_price_history",
+                    "CREATE TRIGGER token_price_history_touch_changed_at BEFORE UPDATE ON token_price_history FOR \
+                     EACH ROW WHEN (OLD IS DISTINCT FROM NEW) EXECUTE FUNCTION touch_changed_at()",
+                    "CREATE INDEX IF NOT EXISTS idx_token_price_history_changed_at ON token_price_history(changed_at)",
+                    "ALTER TABLE token_volume_history ADD COLUMN IF NOT EXISTS changed_at TIMESTAMP WITH TIME ZONE \
+                     NOT NULL DEFAULT NOW()",
+                    "DROP TRIGGER IF EXISTS token_volume_history_touch_changed_at ON token_volume_history",
+                    "CREATE TRIGGER token_volume_history_touch_changed_at BEFORE UPDATE ON token_volume_history FOR \
+                     EACH ROW WHEN (OLD IS DISTINCT FROM NEW) EXECUTE FUNCTION touch_changed_at()",
+                    "CREATE INDEX IF NOT EXISTS idx_token_volume_history_changed_at ON \
+                     token_volume_history(changed_at)",
+                ],
+                down: Some(vec![
+                    "DROP TRIGGER IF EXISTS token_price_history_touch_changed_at ON token_price_history",
+                    "DROP INDEX IF EXISTS idx_token_price_history_changed_at",
+                    "ALTER TABLE token_price_history DROP COLUMN IF EXISTS changed_at",
+                    "DROP TRIGGER IF EXISTS token_volume_history_touch_changed_at ON token_volume_history",
+                    "DROP INDEX IF EXISTS idx_token_volume_history_changed_at",
+                    "ALTER TABLE token_volume_history DROP COLUMN IF EXISTS changed_at",
+                ]),
+            },
+            // Migration 34: Identify each transfer by signature and instruction so copies stored under several
+            // mints collapse to one, and distinct transfers with the same amount and second don't
+            Migration {
+                version: 34,
+                name: String::from("identify_wallet_transfers"),
+                sql: vec!["ALTER TABLE wallet_transfers ADD COLUMN IF NOT EXISTS transfer_id TEXT"],
+                down: Some(vec!["ALTER TABLE wallet_transfers DROP COLUMN IF EXISTS transfer_id"]),
             },
         ]
     }
@@ -1,3 +1,5 @@
+pub mod backend;
+pub mod memory;
 pub mod migration;
 pub mod postgres;
 pub mod redis;
@@ -5,6 +7,7 @@ pub mod redis;
 use std::sync::Arc;
 
 use anyhow::Result;
+pub use backend::StorageBackend;
 use postgres::PostgresClient;
 use redis::RedisClient;
 use tracing::error;
@@ -12,14 +15,19 @@ use tracing::info;
 use tracing::instrument;
 
 use crate::config::Config;
+use crate::err_with_loc;
+use crate::error::postgres::PostgresClientError;
 use crate::storage::migration::Migrator;
 use crate::storage::postgres::make_postgres_client;
 use crate::storage::redis::make_redis_client;
 
 #[derive(Debug, Clone)]
 pub struct StorageEngine {
-    pub postgres: Arc<PostgresClient>,
-    pub redis: Arc<RedisClient>,
+    // Engine-level jobs only Postgres can run (migrations, analytics rollups, partitions), unset when
+    // the engine is built from a backend alone
+    postgres: Option<Arc<PostgresClient>>,
+    // What handlers go through, so they run on Postgres and Redis or fully in-process
+    pub backend: StorageBackend,
 }
 
 impl StorageEngine {
@@ -27,22 +35,37 @@ impl StorageEngine {
         postgres: Arc<PostgresClient>,
         redis: Arc<RedisClient>,
     ) -> Self {
+        let backend = StorageBackend::new(&postgres, redis);
         Self {
-            postgres,
-            redis,
+            postgres: Some(postgres),
+            backend,
         }
     }
 
+    pub fn from_backend(backend: StorageBackend) -> Self {
+        Self {
+            postgres: None,
+            backend,
+        }
+    }
+
+    pub fn postgres(&self) -> Result<&Arc<PostgresClient>> {
+        self.postgres.as_ref().ok_or_else(|| {
+            error!("postgres_client_not_configured");
+            err_with_loc!(PostgresClientError::Other(String::from("postgres_client_not_configured")))
+        })
+    }
+
     // Run migrations on the storage engine
     pub async fn run_migrations(&self) -> Result<()> {
-        let migrator = Migrator::new(self.postgres.pool.clone());
+        let migrator = Migrator::new(self.postgres()?.pool.clone());
         migrator.run_migrations().await?;
         Ok(())
     }
 
     // Check if the database schema is at the expected version
     pub async fn check_schema_version(&self) -> Result<bool> {
-        let migrator = Migrator::new(self.postgres.pool.clone());
+        let migrator = Migrator::new(self.postgres()?.pool.clone());
         migrator.check_schema_version().await
     }
 }
@@ -59,12 +82,11 @@ pub async fn make_storage_engine(
 
     let storage = StorageEngine::new(postgres, redis);
 
-    // Check schema version instead of running migrations
+    // Check schema version instead of running migrations, and refuse to run against a schema this build doesn't match
     let schema_valid = storage.check_schema_version().await?;
     if !schema_valid {
         error!("Database schema version mismatch. Please run migrations before starting services.");
-        // You could choose to panic here or continue with a warning
-        // panic!("Database schema version mismatch. Please run migrations before starting services.");
+        return Err(err_with_loc!(PostgresClientError::Other(String::from("schema_version_mismatch"))));
     }
 
     info!("schema_version::checked");
@@ -85,5 +107,9 @@ pub async fn run_database_migrations(
     migrator.run_migrations().await?;
     info!("Database migrations completed successfully");
 
+    // Services start writing right away, so have today's history partitions in place
+    postgres.partition.maintain_tables(&config.storage_postgres.partitions).await;
+    info!("history_partitions::maintained");
+
     Ok(())
 }
@@ -0,0 +1,193 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use solana_pubkey::Pubkey;
+use tracing::error;
+
+use super::PostgresPool;
+use crate::err_with_loc;
+use crate::error::Result;
+use crate::error::postgres::PostgresClientError;
+use crate::model::alert::AlertCondition;
+use crate::model::alert::AlertHit;
+use crate::model::alert::AlertRule;
+use crate::storage::postgres::PostgresStorage;
+
+#[derive(Debug, Clone)]
+pub struct AlertDb {
+    pub pool: Arc<PostgresPool>,
+}
+
+#[async_trait::async_trait]
+impl PostgresStorage for AlertDb {
+    fn new(pool: Arc<PostgresPool>) -> Self {
+        Self {
+            pool,
+        }
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute("SELECT 1", &[]).await.map_err(|e| {
+            error!("failed_to_health_check: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_health_check: {}", e)))
+        })?;
+        Ok(())
+    }
+
+    // No need to initialize tables here as this is now handled by migrations
+    async fn initialize(&self) -> Result<()> {
+        // Just do a health check to ensure the database is available
+        self.health_check().await
+    }
+}
+
+impl AlertDb {
+    pub async fn create_alert_rule(
+        &self,
+        rule: &AlertRule,
+    ) -> Result<i32> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let row = conn
+            .query_one(
+                "INSERT INTO price_alert_rules (
+                    name, mint, cex_funded_only, kind, threshold, window_secs, cooldown_secs, enabled
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+                 RETURNING id",
+                &[
+                    &rule.name,
+                    &rule.mint.map(|mint| mint.to_string()),
+                    &rule.cex_funded_only,
+                    &rule.condition.as_str(),
+                    &rule.condition.threshold(),
+                    &rule.condition.window_secs(),
+                    &rule.cooldown_secs,
+                    &rule.enabled,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_create_alert_rule::{}::{}", rule.name, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_create_alert_rule::{}::{}",
+                    rule.name, e
+                )))
+            })?;
+
+        Ok(row.get("id"))
+    }
+
+    pub async fn set_alert_rule_enabled(
+        &self,
+        rule_id: i32,
+        enabled: bool,
+    ) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute("UPDATE price_alert_rules SET enabled = $1 WHERE id = $2", &[&enabled, &rule_id])
+            .await
+            .map_err(|e| {
+                error!("failed_to_set_alert_rule_enabled::{}::{}", rule_id, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_set_alert_rule_enabled::{}::{}",
+                    rule_id, e
+                )))
+            })?;
+
+        Ok(())
+    }
+
+    // Rules with an unknown kind or an invalid mint are skipped rather than failing the whole load
+    pub async fn get_enabled_alert_rules(&self) -> Result<Vec<AlertRule>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT id, name, mint, cex_funded_only, kind, threshold, window_secs, cooldown_secs
+                 FROM price_alert_rules WHERE enabled = TRUE ORDER BY id",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_enabled_alert_rules: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_enabled_alert_rules: {}", e)))
+            })?;
+
+        let mut rules = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let id: i32 = row.get("id");
+            let kind: &str = row.get("kind");
+            let Some(condition) = AlertCondition::from_parts(kind, row.get("threshold"), row.get("window_secs")) else {
+                error!("invalid_alert_rule_condition::{}::{}", id, kind);
+                continue;
+            };
+            let mint = match row.get::<_, Option<&str>>("mint").map(Pubkey::from_str).transpose() {
+                Ok(mint) => mint,
+                Err(e) => {
+                    error!("invalid_alert_rule_mint::{}::{}", id, e);
+                    continue;
+                },
+            };
+            rules.push(AlertRule {
+                id,
+                name: row.get("name"),
+                mint,
+                cex_funded_only: row.get("cex_funded_only"),
+                condition,
+                cooldown_secs: row.get("cooldown_secs"),
+                enabled: true,
+            });
+        }
+
+        Ok(rules)
+    }
+
+    pub async fn record_alert_hit(
+        &self,
+        hit: &AlertHit,
+    ) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute(
+            "INSERT INTO price_alert_hits (
+                rule_id, mint, observed, price_sol, market_cap_sol, market_cap_usd, triggered_at
+             ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+            &[
+                &hit.rule_id,
+                &hit.mint.to_string(),
+                &hit.observed,
+                &hit.price_sol,
+                &hit.market_cap_sol,
+                &hit.market_cap_usd,
+                &hit.triggered_at,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_record_alert_hit::{}::{}", hit.rule_id, e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_record_alert_hit::{}::{}",
+                hit.rule_id, e
+            )))
+        })?;
+
+        Ok(())
+    }
+}
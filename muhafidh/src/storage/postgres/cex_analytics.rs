@@ -0,0 +1,291 @@
+use std::str::FromStr;
+use std::sync::Arc;
+
+use solana_pubkey::Pubkey;
+use tracing::error;
+
+use super::PostgresPool;
+use super::model::CexLaunchStats;
+use super::model::TokenLaunchOutcome;
+use crate::err_with_loc;
+use crate::error::Result;
+use crate::error::postgres::PostgresClientError;
+use crate::model::backtest::BacktestSample;
+use crate::storage::postgres::PostgresStorage;
+
+#[derive(Debug, Clone)]
+pub struct CexAnalyticsDb {
+    pub pool: Arc<PostgresPool>,
+}
+
+#[async_trait::async_trait]
+impl PostgresStorage for CexAnalyticsDb {
+    fn new(pool: Arc<PostgresPool>) -> Self {
+        Self {
+            pool,
+        }
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute("SELECT 1", &[]).await.map_err(|e| {
+            error!("failed_to_health_check: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_health_check: {}", e)))
+        })?;
+        Ok(())
+    }
+
+    // No need to initialize tables here as this is now handled by migrations
+    async fn initialize(&self) -> Result<()> {
+        // Just do a health check to ensure the database is available
+        self.health_check().await
+    }
+}
+
+impl CexAnalyticsDb {
+    fn parse_pubkey(value: &str) -> Result<Pubkey> {
+        Pubkey::from_str(value).map_err(|e| {
+            error!("invalid_pubkey_in_cex_analytics::{}::{}", value, e);
+            err_with_loc!(PostgresClientError::QueryError(format!("invalid_pubkey_in_cex_analytics::{}::{}", value, e)))
+        })
+    }
+
+    // CEX-funded mints that received new price history since their outcome was last attributed
+    pub async fn get_pending_outcome_mints(
+        &self,
+        limit: i64,
+    ) -> Result<Vec<Pubkey>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT DISTINCT r.token_mint
+                FROM cex_token_relations r
+                LEFT JOIN cex_token_ath a ON a.cex_address = r.cex_address AND a.token_mint = r.token_mint
+                WHERE EXISTS (
+                    SELECT 1 FROM token_price_history p
+                    WHERE p.mint = r.token_mint
+                    AND p.created_at > COALESCE(a.updated_at, 'epoch'::TIMESTAMPTZ)
+                )
+                LIMIT $1",
+                &[&limit],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_pending_outcome_mints: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_pending_outcome_mints: {}", e)))
+            })?;
+
+        rows.iter().map(|row| Self::parse_pubkey(row.get::<_, &str>(0))).collect()
+    }
+
+    // Exchange wallets recorded as the funding source of a mint
+    pub async fn get_funding_cexes(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Vec<Pubkey>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query("SELECT cex_address FROM cex_token_relations WHERE token_mint = $1", &[&mint.to_string()])
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_funding_cexes::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_funding_cexes::{}::{}", mint, e)))
+            })?;
+
+        rows.iter().map(|row| Self::parse_pubkey(row.get::<_, &str>(0))).collect()
+    }
+
+    // Launch price is the first recorded price, ATH the highest (earliest on ties)
+    pub async fn compute_launch_outcome(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<TokenLaunchOutcome>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let row = conn
+            .query_opt(
+                "WITH launch AS (
+                    SELECT price, timestamp FROM token_price_history WHERE mint = $1 ORDER BY timestamp ASC LIMIT 1
+                ), peak AS (
+                    SELECT price, timestamp FROM token_price_history WHERE mint = $1
+                    ORDER BY price DESC, timestamp ASC LIMIT 1
+                )
+                SELECT
+                    launch.price AS launch_price,
+                    COALESCE((SELECT created_at FROM tokens WHERE mint = $1), launch.timestamp) AS launched_at,
+                    peak.price AS ath_price,
+                    peak.timestamp AS ath_at
+                FROM launch, peak",
+                &[&mint.to_string()],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_compute_launch_outcome::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_compute_launch_outcome::{}::{}",
+                    mint, e
+                )))
+            })?;
+
+        Ok(row.map(|row| TokenLaunchOutcome {
+            mint: *mint,
+            launch_price: row.get::<_, i64>("launch_price").max(0) as u64,
+            launched_at: row.get::<_, i64>("launched_at").max(0) as u64,
+            ath_price: row.get::<_, i64>("ath_price").max(0) as u64,
+            ath_at: row.get::<_, i64>("ath_at").max(0) as u64,
+        }))
+    }
+
+    // Per-exchange launch count, bonded rate, median ATH multiple and median time-to-ATH
+    pub async fn get_cex_launch_stats(&self) -> Result<Vec<CexLaunchStats>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT
+                    m.address,
+                    m.name,
+                    COUNT(r.token_mint) AS launch_count,
+                    COUNT(r.token_mint) FILTER (WHERE t.is_bonded) AS bonded_count,
+                    COUNT(a.token_mint) AS ath_tokens,
+                    percentile_cont(0.5) WITHIN GROUP (ORDER BY a.ath_multiple) AS median_ath_multiple,
+                    percentile_cont(0.5) WITHIN GROUP (ORDER BY a.time_to_ath_secs) AS median_time_to_ath_secs
+                FROM cex_metrics m
+                JOIN cex_token_relations r ON r.cex_address = m.address
+                LEFT JOIN tokens t ON t.mint = r.token_mint
+                LEFT JOIN cex_token_ath a ON a.cex_address = r.cex_address AND a.token_mint = r.token_mint
+                GROUP BY m.address, m.name
+                ORDER BY launch_count DESC",
+                &[],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_cex_launch_stats: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_cex_launch_stats: {}", e)))
+            })?;
+
+        let mut stats = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let launch_count = row.get::<_, i64>("launch_count").max(0) as u64;
+            let bonded_count = row.get::<_, i64>("bonded_count").max(0) as u64;
+            stats.push(CexLaunchStats {
+                cex_address: Self::parse_pubkey(row.get::<_, &str>("address"))?,
+                cex_name: row.get("name"),
+                launch_count,
+                bonded_count,
+                bonded_rate: if launch_count > 0 {
+                    bonded_count as f64 / launch_count as f64
+                } else {
+                    0.0
+                },
+                ath_tokens: row.get::<_, i64>("ath_tokens").max(0) as u64,
+                median_ath_multiple: row.get("median_ath_multiple"),
+                median_time_to_ath_secs: row.get("median_time_to_ath_secs"),
+            });
+        }
+
+        Ok(stats)
+    }
+
+    // Launches created in [created_from, created_to) with their funding sources, bundle share and
+    // price outcomes; funding sources are the recorded CEX relations plus the CEX wallets found in
+    // the mint's funding graph, the 24h price is the last one recorded within a day of creation
+    pub async fn get_backtest_samples(
+        &self,
+        created_from: u64,
+        created_to: u64,
+        platform: Option<&str>,
+    ) -> Result<Vec<BacktestSample>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT
+                    t.mint,
+                    t.creator,
+                    t.platform,
+                    t.created_at,
+                    t.is_bonded,
+                    COALESCE(cex.names, '{}') AS funding_cexes,
+                    b.bundled_supply_share,
+                    launch.price AS launch_price,
+                    peak.price AS ath_price,
+                    peak.timestamp AS ath_at,
+                    day.price AS price_24h
+                FROM tokens t
+                LEFT JOIN token_launch_bundles b ON b.mint = t.mint
+                LEFT JOIN LATERAL (
+                    SELECT array_agg(DISTINCT funding.name) AS names FROM (
+                        SELECT COALESCE(m.name, r.cex_address) AS name
+                        FROM cex_token_relations r
+                        LEFT JOIN cex_metrics m ON m.address = r.cex_address
+                        WHERE r.token_mint = t.mint
+                        UNION
+                        SELECT COALESCE(n.cex_name, n.pubkey)
+                        FROM wallet_edges e
+                        JOIN wallet_nodes n ON n.pubkey = e.source_pubkey AND n.is_cex
+                        WHERE e.mint = t.mint
+                    ) funding
+                ) cex ON TRUE
+                LEFT JOIN LATERAL (
+                    SELECT price FROM token_price_history WHERE mint = t.mint ORDER BY timestamp ASC LIMIT 1
+                ) launch ON TRUE
+                LEFT JOIN LATERAL (
+                    SELECT price, timestamp FROM token_price_history WHERE mint = t.mint
+                    ORDER BY price DESC, timestamp ASC LIMIT 1
+                ) peak ON TRUE
+                LEFT JOIN LATERAL (
+                    SELECT price FROM token_price_history WHERE mint = t.mint AND timestamp <= t.created_at + 86400
+                    ORDER BY timestamp DESC LIMIT 1
+                ) day ON TRUE
+                WHERE t.created_at >= $1 AND t.created_at < $2 AND ($3::TEXT IS NULL OR t.platform = $3)
+                ORDER BY t.created_at",
+                &[&(created_from as i64), &(created_to as i64), &platform],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_backtest_samples: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_backtest_samples: {}", e)))
+            })?;
+
+        let mut samples = Vec::with_capacity(rows.len());
+        for row in &rows {
+            samples.push(BacktestSample {
+                mint: Self::parse_pubkey(row.get::<_, &str>("mint"))?,
+                creator: Self::parse_pubkey(row.get::<_, &str>("creator"))?,
+                platform: row.get("platform"),
+                created_at: row.get::<_, i64>("created_at").max(0) as u64,
+                is_bonded: row.get("is_bonded"),
+                funding_cexes: row.get("funding_cexes"),
+                bundled_supply_share: row.get("bundled_supply_share"),
+                launch_price: row.get::<_, Option<i64>>("launch_price").map(|price| price.max(0) as u64),
+                ath_price: row.get::<_, Option<i64>>("ath_price").map(|price| price.max(0) as u64),
+                ath_at: row.get::<_, Option<i64>>("ath_at").map(|at| at.max(0) as u64),
+                price_24h: row.get::<_, Option<i64>>("price_24h").map(|price| price.max(0) as u64),
+            });
+        }
+
+        Ok(samples)
+    }
+}
@@ -1,17 +1,89 @@
+use std::str::FromStr;
 use std::sync::Arc;
 
 use solana_pubkey::Pubkey;
+use tokio_postgres::Row;
 use tracing::debug;
 use tracing::error;
 
 use super::PostgresPool;
+use super::model::TokenCursor;
+use super::model::TokenFilter;
+use super::model::TokenLaunchOutcome;
 use super::model::TokenMetadataDto;
+use super::model::TokenPage;
 use crate::err_with_loc;
 use crate::error::Result;
 use crate::error::postgres::PostgresClientError;
 use crate::model::token::TokenMetadata;
 use crate::storage::postgres::PostgresStorage;
 
+// Columns read back into a `TokenMetadata`, in the order `token_from_row` expects
+const TOKEN_COLUMNS: &str = "mint, name, symbol, uri, creator, platform, created_at, cex_sources, cex_updated_at, \
+                             updated_at, associated_bonding_curve, is_bonded, bonded_at, all_time_high_price, \
+                             all_time_high_price_at";
+
+// Rows with an unparseable mint or creator are logged and skipped
+fn token_from_row(row: &Row) -> Option<TokenMetadata> {
+    let mint: &str = row.get("mint");
+    let mint = match Pubkey::from_str(mint) {
+        Ok(mint) => mint,
+        Err(e) => {
+            error!("invalid_mint_in_tokens::{}::{}", mint, e);
+            return None;
+        },
+    };
+    let creator: &str = row.get("creator");
+    let creator = match Pubkey::from_str(creator) {
+        Ok(creator) => creator,
+        Err(e) => {
+            error!("invalid_creator_in_tokens::{}::{}::{}", mint, creator, e);
+            return None;
+        },
+    };
+
+    let as_u64 = |value: i64| value.max(0) as u64;
+    let cex_sources = row
+        .get::<_, Option<Vec<String>>>("cex_sources")
+        .map(|sources| sources.iter().filter_map(|source| Pubkey::from_str(source).ok()).collect());
+
+    Some(TokenMetadata {
+        mint,
+        // Only the associated bonding curve is persisted
+        bonding_curve: None,
+        name: row.get("name"),
+        symbol: row.get("symbol"),
+        uri: row.get("uri"),
+        creator,
+        platform: row.get("platform"),
+        created_at: as_u64(row.get("created_at")),
+        cex_sources,
+        cex_updated_at: row.get::<_, Option<i64>>("cex_updated_at").map(as_u64),
+        updated_at: row.get::<_, Option<i64>>("updated_at").map(as_u64),
+        associated_bonding_curve: row
+            .get::<_, Option<&str>>("associated_bonding_curve")
+            .and_then(|curve| Pubkey::from_str(curve).ok()),
+        is_bonded: row.get("is_bonded"),
+        bonded_at: row.get::<_, Option<i64>>("bonded_at").map(as_u64).filter(|at| *at > 0),
+        all_time_high_price: as_u64(row.get("all_time_high_price")),
+        all_time_high_price_at: as_u64(row.get("all_time_high_price_at")),
+    })
+}
+
+// Escape LIKE wildcards so the search term is matched literally, anywhere in the text
+fn contains_pattern(query: &str) -> String {
+    let mut pattern = String::with_capacity(query.len() + 2);
+    pattern.push('%');
+    for c in query.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+    pattern
+}
+
 #[derive(Debug, Clone)]
 pub struct TokenMetadataDb {
     pub pool: Arc<PostgresPool>,
@@ -72,7 +144,7 @@ impl TokenMetadataDb {
                 &(dto.updated_at.unwrap_or(0) as i64),
                 &dto.associated_bonding_curve.map(|p| p.to_string()),
                 &dto.is_bonded,
-                &(dto.bonded_at.unwrap_or(0) as i64),
+                &dto.bonded_at.map(|at| at as i64),
                 &(dto.all_time_high_price as i64),
                 &(dto.all_time_high_price_at as i64),
             ],
@@ -120,13 +192,15 @@ impl TokenMetadataDb {
         Ok(())
     }
 
+    // Link a mint to the CEX that funded its creator and count it towards the CEX metrics.
+    // Returns false when the link already existed, e.g. on a retry, in which case nothing is counted again
     pub async fn record_cex_activity(
         &self,
         cex_name: &str,
         cex_address: &Pubkey,
         mint: &Pubkey,
-    ) -> Result<()> {
-        let conn = self.pool.get().await.map_err(|e| {
+    ) -> Result<bool> {
+        let mut conn = self.pool.get().await.map_err(|e| {
             error!("failed_to_get_client_pool_connection: {}", e);
             err_with_loc!(PostgresClientError::PoolError(e))
         })?;
@@ -134,8 +208,31 @@ impl TokenMetadataDb {
         // Sanitize the CEX name to prevent UTF-8 encoding errors
         let sanitized_cex_name = Self::sanitize_utf8(cex_name);
 
-        // First, ensure the CEX exists in the table
-        conn.execute(
+        let tx = conn.transaction().await.map_err(|e| {
+            error!("failed_to_start_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
+        })?;
+
+        // Record the specific token-CEX relationship
+        let recorded =
+            tx.execute(
+                "INSERT INTO cex_token_relations (
+          cex_address, token_mint, created_at
+      ) VALUES ($1, $2, NOW())
+      ON CONFLICT (cex_address, token_mint) DO NOTHING",
+                &[&cex_address.to_string(), &mint.to_string()],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_record_cex_token_relation: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_record_cex_token_relation: {}", e)))
+            })? > 0;
+        if !recorded {
+            return Ok(false);
+        }
+
+        // Then count it towards the CEX, creating the CEX on its first token
+        tx.execute(
             "INSERT INTO cex_metrics (
           name, address, total_tokens, last_token_at
       ) VALUES ($1, $2, 1, NOW())
@@ -150,29 +247,20 @@ impl TokenMetadataDb {
             err_with_loc!(PostgresClientError::QueryError(format!("failed_to_update_cex_metrics: {}", e)))
         })?;
 
-        // Record the specific token-CEX relationship
-        conn.execute(
-            "INSERT INTO cex_token_relations (
-          cex_address, token_mint, created_at
-      ) VALUES ($1, $2, NOW())
-      ON CONFLICT (cex_address, token_mint) DO NOTHING",
-            &[&cex_address.to_string(), &mint.to_string()],
-        )
-        .await
-        .map_err(|e| {
-            error!("failed_to_record_cex_token_relation: {}", e);
-            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_record_cex_token_relation: {}", e)))
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
         })?;
 
         // debug!("recorded_cex_activity::{}::{}", sanitized_cex_name, mint);
-        Ok(())
+        Ok(true)
     }
 
     pub async fn update_cex_token_ath(
         &self,
         cex_address: &Pubkey,
         mint: &Pubkey,
-        price: u64,
+        outcome: &TokenLaunchOutcome,
     ) -> Result<()> {
         let conn = self.pool.get().await.map_err(|e| {
             error!("failed_to_get_client_pool_connection: {}", e);
@@ -196,12 +284,28 @@ impl TokenMetadataDb {
         // Record the specific token ATH
         conn.execute(
             "INSERT INTO cex_token_ath (
-          cex_address, token_mint, ath_price, ath_at
-      ) VALUES ($1, $2, $3, NOW())
+          cex_address, token_mint, launch_price, ath_price, ath_at, ath_multiple, time_to_ath_secs, updated_at
+      ) VALUES ($1, $2, $3, $4, to_timestamp($5), $6, $7, NOW())
       ON CONFLICT (cex_address, token_mint) DO UPDATE SET
+          launch_price = EXCLUDED.launch_price,
           ath_price = GREATEST(cex_token_ath.ath_price, EXCLUDED.ath_price),
-          ath_at = CASE WHEN cex_token_ath.ath_price < EXCLUDED.ath_price THEN NOW() ELSE cex_token_ath.ath_at END",
-            &[&cex_address.to_string(), &mint.to_string(), &(price as i64)],
+          ath_at = CASE WHEN cex_token_ath.ath_price < EXCLUDED.ath_price THEN EXCLUDED.ath_at ELSE \
+             cex_token_ath.ath_at END,
+          ath_multiple = CASE WHEN cex_token_ath.ath_price < EXCLUDED.ath_price OR cex_token_ath.ath_multiple IS NULL
+              THEN EXCLUDED.ath_multiple ELSE cex_token_ath.ath_multiple END,
+          time_to_ath_secs = CASE WHEN cex_token_ath.ath_price < EXCLUDED.ath_price OR cex_token_ath.time_to_ath_secs \
+             IS NULL
+              THEN EXCLUDED.time_to_ath_secs ELSE cex_token_ath.time_to_ath_secs END,
+          updated_at = NOW()",
+            &[
+                &cex_address.to_string(),
+                &mint.to_string(),
+                &(outcome.launch_price as i64),
+                &(outcome.ath_price as i64),
+                &(outcome.ath_at as f64),
+                &outcome.ath_multiple(),
+                &(outcome.time_to_ath_secs() as i64),
+            ],
         )
         .await
         .map_err(|e| {
@@ -212,6 +316,288 @@ impl TokenMetadataDb {
         debug!("updated_cex_token_ath::{}::{}", cex_address, mint);
         Ok(())
     }
+
+    // Raise the stored all-time high, never lowers it
+    pub async fn update_token_ath(
+        &self,
+        mint: &Pubkey,
+        ath_price: u64,
+        ath_at: u64,
+    ) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute(
+            "UPDATE tokens
+             SET all_time_high_price = $2, all_time_high_price_at = $3
+             WHERE mint = $1 AND all_time_high_price < $2",
+            &[&mint.to_string(), &(ath_price as i64), &(ath_at as i64)],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_update_token_ath: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_update_token_ath: {}", e)))
+        })?;
+
+        Ok(())
+    }
+
+    // Flag a token as migrated off its bonding curve, keeps the first migration time. Rows written before
+    // unbonded tokens got a NULL `bonded_at` hold 0 instead
+    pub async fn mark_token_bonded(
+        &self,
+        mint: &Pubkey,
+        bonded_at: u64,
+    ) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute(
+            "UPDATE tokens
+             SET is_bonded = TRUE, bonded_at = COALESCE(NULLIF(bonded_at, 0), $2)
+             WHERE mint = $1",
+            &[&mint.to_string(), &(bonded_at as i64)],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_mark_token_bonded: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_mark_token_bonded: {}", e)))
+        })?;
+
+        Ok(())
+    }
+
+    // Mints that migrated off their bonding curve after `bonded_after`, with their migration time
+    pub async fn get_bonded_tokens_since(
+        &self,
+        platform: &str,
+        bonded_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT mint, bonded_at FROM tokens
+                WHERE platform = $1 AND is_bonded = TRUE AND bonded_at >= $2",
+                &[&platform, &(bonded_after as i64)],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_bonded_tokens_since: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_bonded_tokens_since: {}", e)))
+            })?;
+
+        let mut tokens = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let mint: &str = row.get("mint");
+            match Pubkey::from_str(mint) {
+                Ok(mint) => tokens.push((mint, row.get::<_, i64>("bonded_at").max(0) as u64)),
+                Err(e) => error!("invalid_mint_in_tokens::{}::{}", mint, e),
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    // Mints created after `created_after` whose creator was traced back to a CEX
+    pub async fn get_cex_funded_tokens_since(
+        &self,
+        created_after: u64,
+    ) -> Result<Vec<Pubkey>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT mint FROM tokens
+                WHERE cardinality(cex_sources) > 0 AND created_at >= $1",
+                &[&(created_after as i64)],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_cex_funded_tokens_since: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_cex_funded_tokens_since: {}", e)))
+            })?;
+
+        let mut tokens = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let mint: &str = row.get("mint");
+            match Pubkey::from_str(mint) {
+                Ok(mint) => tokens.push(mint),
+                Err(e) => error!("invalid_mint_in_tokens::{}::{}", mint, e),
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    // Mints still on their bonding curve that were created after `created_after`, with their creation time
+    pub async fn get_unbonded_tokens_since(
+        &self,
+        platform: &str,
+        created_after: u64,
+    ) -> Result<Vec<(Pubkey, u64)>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT mint, created_at FROM tokens
+                WHERE platform = $1 AND is_bonded = FALSE AND created_at >= $2",
+                &[&platform, &(created_after as i64)],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_unbonded_tokens_since: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_unbonded_tokens_since: {}", e)))
+            })?;
+
+        let mut tokens = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let mint: &str = row.get("mint");
+            match Pubkey::from_str(mint) {
+                Ok(mint) => tokens.push((mint, row.get::<_, i64>("created_at").max(0) as u64)),
+                Err(e) => error!("invalid_mint_in_tokens::{}::{}", mint, e),
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    // Token with the given mint, `None` when it isn't stored
+    pub async fn get_token_by_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<TokenMetadata>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let sql = format!("SELECT {} FROM tokens WHERE mint = $1", TOKEN_COLUMNS);
+        let row = conn.query_opt(sql.as_str(), &[&mint.to_string()]).await.map_err(|e| {
+            error!("failed_to_get_token_by_mint: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_token_by_mint: {}", e)))
+        })?;
+
+        Ok(row.as_ref().and_then(token_from_row))
+    }
+
+    // Newest tokens matching `filter`, up to `limit` per page. Pass the previous page's `next_cursor`
+    // to continue after it; tokens inserted meanwhile don't shift the pages
+    pub async fn list_tokens(
+        &self,
+        filter: &TokenFilter,
+        cursor: Option<&TokenCursor>,
+        limit: u32,
+    ) -> Result<TokenPage> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let creator = filter.creator.map(|creator| creator.to_string());
+        let cex_source = filter.cex_source.map(|cex_source| cex_source.to_string());
+        let created_after = filter.created_after.map(|created_after| created_after as i64);
+        let created_before = filter.created_before.map(|created_before| created_before as i64);
+        let cursor_created_at = cursor.map(|cursor| cursor.created_at as i64);
+        let cursor_mint = cursor.map(|cursor| cursor.mint.as_str());
+        // One extra row tells whether another page follows
+        let fetch = limit as i64 + 1;
+
+        let sql = format!(
+            "SELECT {} FROM tokens
+             WHERE ($1::TEXT IS NULL OR platform = $1)
+               AND ($2::TEXT IS NULL OR creator = $2)
+               AND ($3::TEXT IS NULL OR cex_sources @> ARRAY[$3::TEXT])
+               AND ($4::BOOLEAN IS NULL OR is_bonded = $4)
+               AND ($5::BIGINT IS NULL OR created_at >= $5)
+               AND ($6::BIGINT IS NULL OR created_at < $6)
+               AND ($7::BIGINT IS NULL OR (created_at, mint) < ($7, $8::TEXT))
+             ORDER BY created_at DESC, mint DESC
+             LIMIT $9",
+            TOKEN_COLUMNS
+        );
+        let rows = conn
+            .query(sql.as_str(), &[
+                &filter.platform,
+                &creator,
+                &cex_source,
+                &filter.is_bonded,
+                &created_after,
+                &created_before,
+                &cursor_created_at,
+                &cursor_mint,
+                &fetch,
+            ])
+            .await
+            .map_err(|e| {
+                error!("failed_to_list_tokens: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_list_tokens: {}", e)))
+            })?;
+
+        let has_more = rows.len() > limit as usize;
+        let rows = &rows[..rows.len().min(limit as usize)];
+        // Taken from the raw rows so a skipped invalid row can't end the pagination early
+        let next_cursor = match rows.last() {
+            Some(row) if has_more => Some(TokenCursor {
+                created_at: row.get::<_, i64>("created_at").max(0) as u64,
+                mint: row.get("mint"),
+            }),
+            _ => None,
+        };
+
+        Ok(TokenPage {
+            tokens: rows.iter().filter_map(token_from_row).collect(),
+            next_cursor,
+        })
+    }
+
+    // Tokens whose name or symbol contains `query`, ignoring case. Exact symbol matches come first,
+    // then the newest tokens
+    pub async fn search_tokens(
+        &self,
+        query: &str,
+        limit: u32,
+    ) -> Result<Vec<TokenMetadata>> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let sql = format!(
+            "SELECT {} FROM tokens
+             WHERE name ILIKE $1 OR symbol ILIKE $1
+             ORDER BY lower(symbol) = lower($2) DESC, created_at DESC, mint DESC
+             LIMIT $3",
+            TOKEN_COLUMNS
+        );
+        let rows = conn
+            .query(sql.as_str(), &[&contains_pattern(query), &query, &(limit as i64)])
+            .await
+            .map_err(|e| {
+                error!("failed_to_search_tokens: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_search_tokens: {}", e)))
+            })?;
+
+        Ok(rows.iter().filter_map(token_from_row).collect())
+    }
 }
 
 #[async_trait::async_trait]
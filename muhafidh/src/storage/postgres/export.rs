@@ -0,0 +1,180 @@
+use std::sync::Arc;
+
+use chrono::DateTime;
+use chrono::Utc;
+use futures::TryStreamExt;
+use tokio_postgres::Row;
+use tokio_postgres::types::ToSql;
+use tracing::error;
+
+use crate::err_with_loc;
+use crate::error::Result;
+use crate::error::postgres::PostgresClientError;
+use crate::model::export::ColumnType;
+use crate::model::export::ExportDataset;
+use crate::model::export::ExportValue;
+use crate::storage::postgres::PostgresPool;
+use crate::storage::postgres::PostgresStorage;
+
+// How a dataset is read: selected columns (in `ExportDataset::columns` order), source, the unix seconds
+// it is partitioned and ranged by, and the TIMESTAMPTZ compared against the incremental watermark
+struct DatasetQuery {
+    select: &'static str,
+    from: &'static str,
+    time: &'static str,
+    changed: &'static str,
+}
+
+fn dataset_query(dataset: ExportDataset) -> DatasetQuery {
+    match dataset {
+        ExportDataset::Tokens => DatasetQuery {
+            select: "mint, name, symbol, uri, creator, platform, created_at, array_to_string(cex_sources, ','), \
+                     cex_updated_at, updated_at, associated_bonding_curve, is_bonded, bonded_at, all_time_high_price, \
+                     all_time_high_price_at, EXTRACT(EPOCH FROM changed_at)::BIGINT",
+            from: "tokens",
+            time: "created_at",
+            changed: "changed_at",
+        },
+        ExportDataset::CexRelations => DatasetQuery {
+            select: "r.cex_address, m.name, r.token_mint, EXTRACT(EPOCH FROM r.created_at)::BIGINT",
+            from: "cex_token_relations r LEFT JOIN cex_metrics m ON m.address = r.cex_address",
+            time: "COALESCE(EXTRACT(EPOCH FROM r.created_at)::BIGINT, 0)",
+            changed: "r.created_at",
+        },
+        ExportDataset::WalletNodes => DatasetQuery {
+            select: "pubkey, is_cex, cex_name, total_received, total_balance, EXTRACT(EPOCH FROM created_at)::BIGINT, \
+                     EXTRACT(EPOCH FROM changed_at)::BIGINT",
+            from: "wallet_nodes",
+            time: "COALESCE(EXTRACT(EPOCH FROM created_at)::BIGINT, 0)",
+            changed: "changed_at",
+        },
+        ExportDataset::WalletEdges => DatasetQuery {
+            select: "source_pubkey, target_pubkey, mint, amount, transfer_count, first_timestamp, last_timestamp, \
+                     EXTRACT(EPOCH FROM changed_at)::BIGINT",
+            from: "wallet_edges",
+            // Transfer times are unix milliseconds
+            time: "COALESCE(last_timestamp, timestamp) / 1000",
+            changed: "changed_at",
+        },
+        ExportDataset::PriceHistory => DatasetQuery {
+            select: "p.mint, p.trade_id, p.price, v.volume, p.timestamp, EXTRACT(EPOCH FROM GREATEST(p.changed_at, \
+                     v.changed_at))::BIGINT",
+            from: "token_price_history p LEFT JOIN token_volume_history v ON v.mint = p.mint AND v.timestamp = \
+                   p.timestamp AND v.trade_id = p.trade_id",
+            time: "p.timestamp",
+            // A volume rewritten on its own changes the exported row too, GREATEST skips the missing side
+            changed: "GREATEST(p.changed_at, v.changed_at)",
+        },
+    }
+}
+
+fn export_row(
+    dataset: ExportDataset,
+    row: &Row,
+) -> Vec<ExportValue> {
+    dataset
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(i, column)| match column.kind {
+            ColumnType::Text => ExportValue::Text(row.get(i)),
+            ColumnType::Int => ExportValue::Int(row.get(i)),
+            ColumnType::Float => ExportValue::Float(row.get(i)),
+            ColumnType::Bool => ExportValue::Bool(row.get(i)),
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportDb {
+    pub pool: Arc<PostgresPool>,
+}
+
+#[async_trait::async_trait]
+impl PostgresStorage for ExportDb {
+    fn new(pool: Arc<PostgresPool>) -> Self {
+        Self {
+            pool,
+        }
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute("SELECT 1", &[]).await.map_err(|e| {
+            error!("failed_to_health_check: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_health_check: {}", e)))
+        })?;
+        Ok(())
+    }
+
+    // No need to initialize tables here as this is now handled by migrations
+    async fn initialize(&self) -> Result<()> {
+        // Just do a health check to ensure the database is available
+        self.health_check().await
+    }
+}
+
+impl ExportDb {
+    // Stream the rows of a dataset with their time in [from, to) (unix seconds), ordered by day.
+    // With `changed_since` only rows written or updated since then are read. Returns the row count
+    pub async fn export_dataset<F>(
+        &self,
+        dataset: ExportDataset,
+        from: i64,
+        to: i64,
+        changed_since: Option<DateTime<Utc>>,
+        mut on_row: F,
+    ) -> Result<u64>
+    where
+        F: FnMut(i64, Vec<ExportValue>) -> Result<()> + Send, {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let query = dataset_query(dataset);
+        let sql = format!(
+            "SELECT {select}, {time} AS export_time
+             FROM {from}
+             WHERE {time} >= $1 AND {time} < $2 AND ($3::TIMESTAMPTZ IS NULL OR {changed} >= $3)
+             ORDER BY export_time",
+            select = query.select,
+            time = query.time,
+            from = query.from,
+            changed = query.changed,
+        );
+        let params: [&(dyn ToSql + Sync); 3] = [&from, &to, &changed_since];
+
+        // Rows are handed over as they arrive instead of being collected first
+        let rows = conn.query_raw(sql.as_str(), params).await.map_err(|e| {
+            error!("failed_to_export_dataset::{}: {}", dataset.as_str(), e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_export_dataset::{}: {}",
+                dataset.as_str(),
+                e
+            )))
+        })?;
+        futures::pin_mut!(rows);
+
+        let time_column = dataset.columns().len();
+        let mut exported = 0;
+        while let Some(row) = rows.try_next().await.map_err(|e| {
+            error!("failed_to_read_export_row::{}: {}", dataset.as_str(), e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_read_export_row::{}: {}",
+                dataset.as_str(),
+                e
+            )))
+        })? {
+            let day = row.get::<_, i64>(time_column).div_euclid(86_400);
+            on_row(day, export_row(dataset, &row))?;
+            exported += 1;
+        }
+
+        Ok(exported)
+    }
+}
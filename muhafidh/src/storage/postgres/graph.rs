@@ -1,6 +1,10 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Instant;
 
 use solana_pubkey::Pubkey;
+use tokio_postgres::Row;
 use tracing::debug;
 use tracing::error;
 
@@ -8,8 +12,16 @@ use crate::err_with_loc;
 use crate::error::Result;
 use crate::error::postgres::PostgresClientError;
 use crate::model::creator::graph::CreatorConnectionGraph;
+use crate::model::creator::graph::TransferRecord;
 use crate::storage::postgres::PostgresPool;
 use crate::storage::postgres::PostgresStorage;
+use crate::storage::postgres::model::EdgeCost;
+use crate::storage::postgres::model::FundingPath;
+use crate::storage::postgres::model::PathHop;
+use crate::storage::postgres::model::WalletNeighbor;
+
+// Upper bound on the partial paths `paths_between` expands before giving up on finding more
+const MAX_EXPLORED_PATHS: i64 = 100_000;
 
 #[derive(Debug, Clone)]
 pub struct GraphDb {
@@ -55,12 +67,86 @@ impl GraphDb {
         String::from_utf8_lossy(&bytes).to_string()
     }
 
-    // Store the connection graph in pgrouting
+    // Store the connection graph in pgrouting. Nodes, edges and transfers are each written with one
+    // set-based statement over `unnest`ed arrays, so a graph costs three round trips regardless of size
     pub async fn store_connection_graph(
         &self,
         mint: &Pubkey,
         connection_graph: &CreatorConnectionGraph,
     ) -> Result<()> {
+        let started = Instant::now();
+        let mint = mint.to_string();
+
+        // A multi-row upsert can't touch the same row twice, so duplicates are collapsed up front
+        // with the last occurrence winning, as row-by-row upserts would have left it. The maps are
+        // ordered by each table's conflict key, so concurrent writers lock shared rows in the same
+        // order instead of deadlocking
+        let mut nodes: BTreeMap<String, (bool, Option<String>)> = BTreeMap::new();
+        for node in connection_graph.get_nodes() {
+            let sanitized_cex_name = if node.is_cex {
+                crate::model::cex::Cex::get_exchange_name(node.detail.address)
+                    .map(|name| Self::sanitize_utf8(&name.to_string()))
+            } else {
+                None
+            };
+            nodes.insert(node.detail.address.to_string(), (node.is_cex, sanitized_cex_name));
+        }
+
+        let mut edges: BTreeMap<(String, String), (f64, i64, i64, i64)> = BTreeMap::new();
+        // Several transfers can share a second, so each keeps its position among them as `seq`
+        let mut transfers: BTreeMap<(String, String, i64), Vec<(f64, String)>> = BTreeMap::new();
+        for edge in connection_graph.get_edges() {
+            let source = edge.from.to_string();
+            let target = edge.to.to_string();
+            for transfer in &edge.transfers {
+                transfers
+                    .entry((source.clone(), target.clone(), transfer.timestamp))
+                    .or_default()
+                    .push((transfer.amount, transfer.transfer_id.clone()));
+            }
+            edges.insert(
+                (source, target),
+                (edge.amount, edge.transfer_count as i64, edge.first_timestamp, edge.last_timestamp),
+            );
+        }
+
+        let (node_pubkeys, (node_is_cex, node_cex_names)): (Vec<String>, (Vec<bool>, Vec<Option<String>>)) =
+            nodes.into_iter().unzip();
+
+        let edge_count = edges.len();
+        let mut edge_sources = Vec::with_capacity(edge_count);
+        let mut edge_targets = Vec::with_capacity(edge_count);
+        let mut edge_amounts = Vec::with_capacity(edge_count);
+        let mut edge_transfer_counts = Vec::with_capacity(edge_count);
+        let mut edge_first_timestamps = Vec::with_capacity(edge_count);
+        let mut edge_last_timestamps = Vec::with_capacity(edge_count);
+        for ((source, target), (amount, transfer_count, first_timestamp, last_timestamp)) in edges {
+            edge_sources.push(source);
+            edge_targets.push(target);
+            edge_amounts.push(amount);
+            edge_transfer_counts.push(transfer_count);
+            edge_first_timestamps.push(first_timestamp);
+            edge_last_timestamps.push(last_timestamp);
+        }
+
+        let transfer_count = transfers.values().map(Vec::len).sum();
+        let mut transfer_sources = Vec::with_capacity(transfer_count);
+        let mut transfer_targets = Vec::with_capacity(transfer_count);
+        let mut transfer_timestamps = Vec::with_capacity(transfer_count);
+        let mut transfer_seqs = Vec::with_capacity(transfer_count);
+        let mut transfer_amounts = Vec::with_capacity(transfer_count);
+        let mut transfer_ids = Vec::with_capacity(transfer_count);
+        for ((source, target, timestamp), records) in transfers {
+            for (seq, (amount, transfer_id)) in records.into_iter().enumerate() {
+                transfer_sources.push(source.clone());
+                transfer_targets.push(target.clone());
+                transfer_timestamps.push(timestamp);
+                transfer_seqs.push(seq as i32);
+                transfer_amounts.push(amount);
+                transfer_ids.push(transfer_id);
+            }
+        }
+
         let mut conn = self.pool.get().await.map_err(|e| {
             error!("failed_to_get_client_pool_connection: {}", e);
             err_with_loc!(PostgresClientError::PoolError(e))
@@ -72,92 +158,468 @@ impl GraphDb {
             err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
         })?;
 
-        // First, extract all nodes from the graph and insert them
-        let nodes = connection_graph.get_nodes();
-        let node_count = nodes.len(); // Store length before iteration
+        let nodes_started = Instant::now();
+        tx.execute(
+            "INSERT INTO wallet_nodes (pubkey, is_cex, cex_name)
+             SELECT * FROM unnest($1::text[], $2::boolean[], $3::text[])
+             ON CONFLICT (pubkey) DO UPDATE SET is_cex = EXCLUDED.is_cex,
+               cex_name = EXCLUDED.cex_name",
+            &[&node_pubkeys, &node_is_cex, &node_cex_names],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_insert_nodes: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_insert_nodes: {}", e)))
+        })?;
+        let nodes_ms = nodes_started.elapsed().as_millis();
 
-        for node in &nodes {
-            // Use reference to avoid moving the Vec
-            let sanitized_cex_name = if node.is_cex {
-                match crate::model::cex::Cex::get_exchange_name(node.detail.address) {
-                    Some(name) => Some(Self::sanitize_utf8(&name.to_string())),
-                    None => None,
-                }
-            } else {
-                None
-            };
+        // Node ids are resolved by joining on pubkey; an edge whose endpoint is missing would be dropped
+        // by the join, so the written count is checked to fail the transaction like a missing id lookup did
+        let edges_started = Instant::now();
+        let written = tx
+            .execute(
+                "INSERT INTO wallet_edges (
+                   source_id, target_id, source_pubkey, target_pubkey,
+                   cost, amount, timestamp, mint, transfer_count, first_timestamp, last_timestamp
+                 )
+                 SELECT s.id, t.id, e.source_pubkey, e.target_pubkey,
+                   1.0, e.amount, e.last_timestamp, $7, e.transfer_count, e.first_timestamp, e.last_timestamp
+                 FROM unnest($1::text[], $2::text[], $3::float8[], $4::bigint[], $5::bigint[], $6::bigint[])
+                   AS e(source_pubkey, target_pubkey, amount, transfer_count, first_timestamp, last_timestamp),
+                   wallet_nodes s, wallet_nodes t
+                 WHERE s.pubkey = e.source_pubkey AND t.pubkey = e.target_pubkey
+                 ORDER BY e.source_pubkey, e.target_pubkey
+                 ON CONFLICT (source_pubkey, target_pubkey, mint) DO UPDATE SET
+                   amount = EXCLUDED.amount,
+                   transfer_count = EXCLUDED.transfer_count,
+                   first_timestamp = LEAST(wallet_edges.first_timestamp, EXCLUDED.first_timestamp),
+                   last_timestamp = GREATEST(wallet_edges.last_timestamp, EXCLUDED.last_timestamp),
+                   timestamp = GREATEST(wallet_edges.timestamp, EXCLUDED.timestamp)",
+                &[
+                    &edge_sources,
+                    &edge_targets,
+                    &edge_amounts,
+                    &edge_transfer_counts,
+                    &edge_first_timestamps,
+                    &edge_last_timestamps,
+                    &mint,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_insert_edges: {}", e);
+                err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_insert_edges: {}", e)))
+            })?;
+        if written as usize != edge_count {
+            error!("edge_endpoints_missing::mint::{}::expected::{}::written::{}", mint, edge_count, written);
+            return Err(err_with_loc!(PostgresClientError::QueryError(format!(
+                "edge_endpoints_missing::expected::{}::written::{}",
+                edge_count, written
+            ))));
+        }
+        let edges_ms = edges_started.elapsed().as_millis();
+
+        // Keep the individual transfers available for on-demand inspection
+        let transfers_started = Instant::now();
+        tx.execute(
+            "INSERT INTO wallet_transfers (source_pubkey, target_pubkey, mint, amount, timestamp, seq, transfer_id)
+             SELECT e.source_pubkey, e.target_pubkey, $7, e.amount, e.timestamp, e.seq, e.transfer_id
+             FROM unnest($1::text[], $2::text[], $3::float8[], $4::bigint[], $5::integer[], $6::text[])
+               AS e(source_pubkey, target_pubkey, amount, timestamp, seq, transfer_id)
+             ON CONFLICT (source_pubkey, target_pubkey, mint, timestamp, seq) DO UPDATE SET
+               amount = EXCLUDED.amount,
+               transfer_id = EXCLUDED.transfer_id",
+            &[
+                &transfer_sources,
+                &transfer_targets,
+                &transfer_amounts,
+                &transfer_timestamps,
+                &transfer_seqs,
+                &transfer_ids,
+                &mint,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_insert_transfers: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_insert_transfers: {}", e)))
+        })?;
+        let transfers_ms = transfers_started.elapsed().as_millis();
+
+        // Commit the transaction
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        })?;
+
+        debug!(
+            "connection_graph_stored::mint::{}::nodes::{}::edges::{}::transfers::{}::nodes_ms::{}::edges_ms::{}::transfers_ms::{}::total_ms::{}",
+            mint,
+            node_pubkeys.len(),
+            edge_count,
+            transfer_count,
+            nodes_ms,
+            edges_ms,
+            transfers_ms,
+            started.elapsed().as_millis()
+        );
+        Ok(())
+    }
+
+    fn parse_pubkey(value: &str) -> Result<Pubkey> {
+        Pubkey::from_str(value).map_err(|e| {
+            error!("invalid_pubkey_in_graph::{}::{}", value, e);
+            err_with_loc!(PostgresClientError::QueryError(format!("invalid_pubkey_in_graph::{}::{}", value, e)))
+        })
+    }
+
+    // Rows are expected as (pubkey, is_cex, cex_name, amount, timestamp, agg_cost) ordered by path_seq
+    fn rows_to_path(rows: &[Row]) -> Result<Option<FundingPath>> {
+        if rows.is_empty() {
+            return Ok(None);
+        }
+
+        let mut hops = Vec::with_capacity(rows.len());
+        for row in rows {
+            hops.push(PathHop {
+                address: Self::parse_pubkey(row.get::<_, &str>("pubkey"))?,
+                is_cex: row.get("is_cex"),
+                cex_name: row.get("cex_name"),
+                amount: row.get("amount"),
+                timestamp: row.get("timestamp"),
+                agg_cost: row.get("agg_cost"),
+            });
+        }
+
+        let total_cost = hops.last().map(|hop| hop.agg_cost).unwrap_or_default();
+        Ok(Some(FundingPath {
+            hops,
+            total_cost,
+        }))
+    }
+
+    async fn get_node_id(
+        &self,
+        address: &Pubkey,
+    ) -> Result<Option<i64>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
 
-            tx.execute(
-                "INSERT INTO wallet_nodes (pubkey, is_cex, cex_name) VALUES ($1, $2, $3)
-         ON CONFLICT (pubkey) DO UPDATE SET is_cex = EXCLUDED.is_cex,
-           cex_name = EXCLUDED.cex_name",
-                &[&node.detail.address.to_string(), &node.is_cex, &sanitized_cex_name],
+        let row = conn
+            .query_opt("SELECT id::BIGINT FROM wallet_nodes WHERE pubkey = $1", &[&address.to_string()])
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_node_id: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_node_id: {}", e)))
+            })?;
+
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    /// Rebuild the funding graph stored for the given mints. Passing several mints yields their merged subgraph.
+    pub async fn load_connection_graph(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<CreatorConnectionGraph> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let mints: Vec<String> = mints.iter().map(|mint| mint.to_string()).collect();
+
+        // The same transfer can be stored once per mint, keep a single copy of it by its id. Rows written
+        // before transfers had one fall back to their second and position within it
+        let rows = conn
+            .query(
+                "SELECT * FROM (
+                    SELECT DISTINCT ON (w.source_pubkey, w.target_pubkey, COALESCE(w.transfer_id, w.timestamp || ':' \
+                 || w.seq))
+                        w.source_pubkey, w.target_pubkey, w.amount, w.timestamp, w.seq,
+                        COALESCE(w.transfer_id, w.timestamp || ':' || w.seq) AS transfer_id,
+                        s.is_cex AS source_is_cex, s.total_balance AS source_balance,
+                        t.is_cex AS target_is_cex, t.total_balance AS target_balance
+                    FROM wallet_transfers w
+                    JOIN wallet_nodes s ON s.pubkey = w.source_pubkey
+                    JOIN wallet_nodes t ON t.pubkey = w.target_pubkey
+                    WHERE w.mint = ANY($1)
+                    ORDER BY w.source_pubkey, w.target_pubkey, COALESCE(w.transfer_id, w.timestamp || ':' || w.seq)
+                ) transfers
+                ORDER BY timestamp, seq",
+                &[&mints],
             )
             .await
             .map_err(|e| {
-                error!("failed_to_insert_node: {}", e);
-                err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_insert_node: {}", e)))
+                error!("failed_to_load_connection_graph: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_load_connection_graph: {}", e)))
             })?;
+
+        let mut graph = CreatorConnectionGraph::new();
+        for row in &rows {
+            let source = Self::parse_pubkey(row.get::<_, &str>("source_pubkey"))?;
+            let target = Self::parse_pubkey(row.get::<_, &str>("target_pubkey"))?;
+            let source_idx = graph.add_node(
+                source,
+                row.get::<_, Option<f64>>("source_balance").unwrap_or_default(),
+                row.get("source_is_cex"),
+            );
+            let target_idx = graph.add_node(
+                target,
+                row.get::<_, Option<f64>>("target_balance").unwrap_or_default(),
+                row.get("target_is_cex"),
+            );
+            graph.add_edge(source_idx, target_idx, row.get("amount"), row.get("timestamp"), row.get("transfer_id"));
         }
 
-        // Then extract all edges and insert them
-        let edges = connection_graph.get_edges();
-        let edge_count = edges.len(); // Store length before iteration
-
-        for edge in &edges {
-            // Use reference to avoid moving the Vec
-            // First get the node IDs
-            let source_id: i32 = tx
-                .query_one("SELECT id FROM wallet_nodes WHERE pubkey = $1", &[&edge.from.to_string()])
-                .await
-                .map_err(|e| {
-                    error!("failed_to_get_source_node_id: {}", e);
-                    err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_source_node_id: {}", e)))
-                })?
-                .get(0);
-
-            let target_id: i32 = tx
-                .query_one("SELECT id FROM wallet_nodes WHERE pubkey = $1", &[&edge.to.to_string()])
-                .await
-                .map_err(|e| {
-                    error!("failed_to_get_target_node_id: {}", e);
-                    err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_target_node_id: {}", e)))
-                })?
-                .get(0);
-
-            // Insert the edge
-            tx.execute(
-                "INSERT INTO wallet_edges (
-           source_id, target_id, source_pubkey, target_pubkey,
-           cost, amount, timestamp, mint
-         ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-         ON CONFLICT (source_pubkey, target_pubkey, mint, timestamp) DO UPDATE SET
-           amount = EXCLUDED.amount",
+        Ok(graph)
+    }
+
+    /// Individual transfers behind the aggregated edge `source -> target` for a mint, oldest first.
+    pub async fn get_transfers(
+        &self,
+        source: &Pubkey,
+        target: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<Vec<TransferRecord>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT amount, timestamp, COALESCE(transfer_id, timestamp || ':' || seq) AS transfer_id
+                FROM wallet_transfers
+                WHERE source_pubkey = $1 AND target_pubkey = $2 AND mint = $3
+                ORDER BY timestamp ASC, seq ASC",
+                &[&source.to_string(), &target.to_string(), &mint.to_string()],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_transfers: {}", e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_transfers: {}", e)))
+            })?;
+
+        Ok(rows
+            .iter()
+            .map(|row| TransferRecord {
+                transfer_id: row.get("transfer_id"),
+                amount: row.get("amount"),
+                timestamp: row.get("timestamp"),
+            })
+            .collect())
+    }
+
+    /// Shortest funding path from the nearest CEX node down to `wallet`.
+    /// Returns `None` when the wallet is unknown or no CEX can reach it.
+    pub async fn shortest_path_to_cex(
+        &self,
+        wallet: &Pubkey,
+        cost: EdgeCost,
+    ) -> Result<Option<FundingPath>> {
+        let Some(wallet_id) = self.get_node_id(wallet).await? else {
+            return Ok(None);
+        };
+
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        // Funding flows from the CEX towards the wallet, so route from every CEX node to the wallet and keep the
+        // cheapest route. CEX ids are read from the partial `is_cex` index in the same statement, and the end of each
+        // route is found by `edge = -1`, which unlike `end_vid` (pgRouting 3.6+) every 3.x release reports
+        let rows = conn
+            .query(
+                "WITH route AS (
+                    SELECT * FROM pgr_dijkstra(
+                        $1,
+                        ARRAY(SELECT id::BIGINT FROM wallet_nodes WHERE is_cex),
+                        $2::BIGINT,
+                        true
+                    )
+                ), best AS (
+                    SELECT start_vid FROM route WHERE edge = -1 ORDER BY agg_cost ASC LIMIT 1
+                )
+                SELECT n.pubkey, n.is_cex, n.cex_name, e.amount, e.timestamp, r.agg_cost
+                FROM route r
+                JOIN best b ON r.start_vid = b.start_vid
+                JOIN wallet_nodes n ON n.id = r.node
+                LEFT JOIN wallet_edges e ON e.id = r.edge
+                ORDER BY r.path_seq",
+                &[&cost.edges_sql(), &wallet_id],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_query_shortest_path_to_cex::{}::{}", wallet, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_query_shortest_path_to_cex::{}::{}",
+                    wallet, e
+                )))
+            })?;
+
+        Self::rows_to_path(&rows)
+    }
+
+    /// Cheapest directed path from `from` to `to` using bidirectional Dijkstra
+    pub async fn shortest_path_between(
+        &self,
+        from: &Pubkey,
+        to: &Pubkey,
+        cost: EdgeCost,
+    ) -> Result<Option<FundingPath>> {
+        let (Some(from_id), Some(to_id)) = (self.get_node_id(from).await?, self.get_node_id(to).await?) else {
+            return Ok(None);
+        };
+
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT n.pubkey, n.is_cex, n.cex_name, e.amount, e.timestamp, r.agg_cost
+                FROM pgr_bdDijkstra($1, $2::BIGINT, $3::BIGINT, true) r
+                JOIN wallet_nodes n ON n.id = r.node
+                LEFT JOIN wallet_edges e ON e.id = r.edge
+                ORDER BY r.path_seq",
+                &[&cost.edges_sql(), &from_id, &to_id],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_query_shortest_path_between::{}::{}::{}", from, to, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_query_shortest_path_between::{}::{}::{}",
+                    from, to, e
+                )))
+            })?;
+
+        Self::rows_to_path(&rows)
+    }
+
+    /// Loop-free paths from `from` to `to` with at most `max_hops` transfers, fewest hops first, up to `max_paths`.
+    /// When `directed` is false, transfers may be walked against the funding direction.
+    pub async fn paths_between(
+        &self,
+        from: &Pubkey,
+        to: &Pubkey,
+        max_hops: u32,
+        max_paths: u32,
+        directed: bool,
+    ) -> Result<Vec<Vec<Pubkey>>> {
+        let (Some(from_id), Some(to_id)) = (self.get_node_id(from).await?, self.get_node_id(to).await?) else {
+            return Ok(vec![]);
+        };
+
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        // The walk never revisits a wallet already on its path and is expanded one hop at a time, so the first
+        // paths reaching `to` are the shortest. Postgres only evaluates as much of the recursive CTE as the LIMITs
+        // fetch, which bounds both the partial paths explored and the paths returned
+        let rows = conn
+            .query(
+                "WITH RECURSIVE links AS (
+                    SELECT DISTINCT source_id::BIGINT AS src, target_id::BIGINT AS dst FROM wallet_edges
+                    WHERE source_id IS NOT NULL AND target_id IS NOT NULL
+                    UNION
+                    SELECT DISTINCT target_id::BIGINT, source_id::BIGINT FROM wallet_edges
+                    WHERE NOT $4 AND source_id IS NOT NULL AND target_id IS NOT NULL
+                ), walk AS (
+                    SELECT ARRAY[$1::BIGINT] AS path, $1::BIGINT AS tip, 0 AS hops
+                    UNION ALL
+                    SELECT w.path || l.dst, l.dst, w.hops + 1
+                    FROM walk w
+                    JOIN links l ON l.src = w.tip
+                    WHERE w.hops < $3 AND w.tip <> $2 AND NOT l.dst = ANY(w.path)
+                ), explored AS (
+                    SELECT * FROM walk LIMIT $6
+                ), found AS (
+                    SELECT * FROM explored WHERE tip = $2 LIMIT $5
+                )
+                SELECT ARRAY(
+                    SELECT n.pubkey FROM unnest(f.path) WITH ORDINALITY AS p(id, ord)
+                    JOIN wallet_nodes n ON n.id = p.id
+                    ORDER BY p.ord
+                ) AS pubkeys
+                FROM found f
+                ORDER BY f.hops",
                 &[
-                    &source_id,
-                    &target_id,
-                    &edge.from.to_string(),
-                    &edge.to.to_string(),
-                    &(1.0f64),                // Default cost - explicit f64 type annotation
-                    &(edge.amount as f64),    // Explicit conversion to f64
-                    &(edge.timestamp as i64), // Explicit conversion to i64
-                    &mint.to_string(),
+                    &from_id,
+                    &to_id,
+                    &(max_hops as i32),
+                    &directed,
+                    &(max_paths as i64),
+                    &MAX_EXPLORED_PATHS,
                 ],
             )
             .await
             .map_err(|e| {
-                error!("failed_to_insert_edge: {}", e);
-                err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_insert_edge: {}", e)))
+                error!("failed_to_query_paths_between::{}::{}::{}", from, to, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_query_paths_between::{}::{}::{}",
+                    from, to, e
+                )))
             })?;
+
+        let mut paths = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let pubkeys: Vec<String> = row.get("pubkeys");
+            paths.push(pubkeys.iter().map(|p| Self::parse_pubkey(p)).collect::<Result<Vec<Pubkey>>>()?);
         }
 
-        // Commit the transaction
-        tx.commit().await.map_err(|e| {
-            error!("failed_to_commit_transaction: {}", e);
-            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        Ok(paths)
+    }
+
+    /// Every wallet within `max_hops` transfers of `wallet`, ignoring transfer direction
+    pub async fn k_hop_neighborhood(
+        &self,
+        wallet: &Pubkey,
+        max_hops: u32,
+    ) -> Result<Vec<WalletNeighbor>> {
+        let Some(wallet_id) = self.get_node_id(wallet).await? else {
+            return Ok(vec![]);
+        };
+
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
         })?;
 
-        // debug!("Stored connection graph for mint {} with {} nodes and {} edges", mint, node_count, edge_count);
-        Ok(())
+        // With unit costs the driving distance is exactly the hop count
+        let rows = conn
+            .query(
+                "SELECT n.pubkey, n.is_cex, n.cex_name, d.agg_cost
+                FROM pgr_drivingDistance($1, $2::BIGINT, $3::FLOAT8, false) d
+                JOIN wallet_nodes n ON n.id = d.node
+                WHERE d.node <> $2
+                ORDER BY d.agg_cost, n.pubkey",
+                &[&EdgeCost::Hops.edges_sql(), &wallet_id, &(max_hops as f64)],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_query_k_hop_neighborhood::{}::{}", wallet, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_query_k_hop_neighborhood::{}::{}",
+                    wallet, e
+                )))
+            })?;
+
+        let mut neighbors = Vec::with_capacity(rows.len());
+        for row in &rows {
+            neighbors.push(WalletNeighbor {
+                address: Self::parse_pubkey(row.get::<_, &str>("pubkey"))?,
+                is_cex: row.get("is_cex"),
+                cex_name: row.get("cex_name"),
+                hops: row.get::<_, f64>("agg_cost").round() as u32,
+            });
+        }
+
+        Ok(neighbors)
     }
 }
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use solana_pubkey::Pubkey;
+use tracing::error;
+
+use super::PostgresPool;
+use super::model::CreatorReputationEvent;
+use crate::err_with_loc;
+use crate::error::Result;
+use crate::error::postgres::PostgresClientError;
+use crate::model::bundle::BundleAnalysis;
+use crate::model::bundle::EarlyBuy;
+use crate::model::bundle::LaunchWindow;
+use crate::storage::postgres::PostgresStorage;
+
+#[derive(Debug, Clone)]
+pub struct LaunchDb {
+    pub pool: Arc<PostgresPool>,
+}
+
+#[async_trait::async_trait]
+impl PostgresStorage for LaunchDb {
+    fn new(pool: Arc<PostgresPool>) -> Self {
+        Self {
+            pool,
+        }
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute("SELECT 1", &[]).await.map_err(|e| {
+            error!("failed_to_health_check: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_health_check: {}", e)))
+        })?;
+        Ok(())
+    }
+
+    // No need to initialize tables here as this is now handled by migrations
+    async fn initialize(&self) -> Result<()> {
+        // Just do a health check to ensure the database is available
+        self.health_check().await
+    }
+}
+
+impl LaunchDb {
+    fn parse_pubkey(value: &str) -> Result<Pubkey> {
+        Pubkey::from_str(value).map_err(|e| {
+            error!("invalid_pubkey_in_launch::{}::{}", value, e);
+            err_with_loc!(PostgresClientError::QueryError(format!("invalid_pubkey_in_launch::{}::{}", value, e)))
+        })
+    }
+
+    // Store the buys collected during a launch's bundle window
+    pub async fn store_launch_window(
+        &self,
+        window: &LaunchWindow,
+    ) -> Result<()> {
+        let mut conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let tx = conn.transaction().await.map_err(|e| {
+            error!("failed_to_start_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
+        })?;
+
+        tx.execute(
+            "INSERT INTO token_launch_bundles (mint, creator, create_slot, window_slots, early_buy_count)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (mint) DO UPDATE SET
+             early_buy_count = EXCLUDED.early_buy_count",
+            &[
+                &window.mint.to_string(),
+                &window.creator.to_string(),
+                &(window.create_slot as i64),
+                &(window.window_slots as i64),
+                &(window.buys.len() as i32),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_insert_launch_window::{}::{}", window.mint, e);
+            err_with_loc!(PostgresClientError::TransactionError(format!(
+                "failed_to_insert_launch_window::{}::{}",
+                window.mint, e
+            )))
+        })?;
+
+        for buy in &window.buys {
+            tx.execute(
+                "INSERT INTO token_early_buys (mint, signature, buyer, fee_payer, slot, sol_amount, token_amount)
+                 VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (mint, signature, buyer) DO NOTHING",
+                &[
+                    &window.mint.to_string(),
+                    &buy.signature,
+                    &buy.buyer.to_string(),
+                    &buy.fee_payer.to_string(),
+                    &(buy.slot as i64),
+                    &(buy.sol_amount as i64),
+                    &(buy.token_amount as i64),
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_insert_early_buy::{}::{}", window.mint, e);
+                err_with_loc!(PostgresClientError::TransactionError(format!(
+                    "failed_to_insert_early_buy::{}::{}",
+                    window.mint, e
+                )))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        })?;
+
+        Ok(())
+    }
+
+    // Launch window of a mint with its early buys in slot order, `None` if it was never recorded
+    pub async fn get_launch_window(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<LaunchWindow>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let Some(row) = conn
+            .query_opt("SELECT creator, create_slot, window_slots FROM token_launch_bundles WHERE mint = $1", &[
+                &mint.to_string()
+            ])
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_launch_window::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_launch_window::{}::{}", mint, e)))
+            })?
+        else {
+            return Ok(None);
+        };
+
+        let mut window = LaunchWindow::new(
+            *mint,
+            Self::parse_pubkey(row.get::<_, &str>("creator"))?,
+            row.get::<_, i64>("create_slot").max(0) as u64,
+            row.get::<_, i64>("window_slots").max(0) as u64,
+        );
+
+        let rows = conn
+            .query(
+                "SELECT signature, buyer, fee_payer, slot, sol_amount, token_amount FROM token_early_buys
+                 WHERE mint = $1
+                 ORDER BY slot ASC, id ASC",
+                &[&mint.to_string()],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_early_buys::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_early_buys::{}::{}", mint, e)))
+            })?;
+
+        for row in &rows {
+            window.buys.push(EarlyBuy {
+                signature: row.get("signature"),
+                buyer: Self::parse_pubkey(row.get::<_, &str>("buyer"))?,
+                fee_payer: Self::parse_pubkey(row.get::<_, &str>("fee_payer"))?,
+                slot: row.get::<_, i64>("slot").max(0) as u64,
+                sol_amount: row.get::<_, i64>("sol_amount").max(0) as u64,
+                token_amount: row.get::<_, i64>("token_amount").max(0) as u64,
+            });
+        }
+
+        Ok(Some(window))
+    }
+
+    // Record the bundle findings of a launch, replacing earlier ones
+    pub async fn store_bundle_analysis(
+        &self,
+        analysis: &BundleAnalysis,
+        analyzed_at: u64,
+    ) -> Result<()> {
+        let mut conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let tx = conn.transaction().await.map_err(|e| {
+            error!("failed_to_start_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
+        })?;
+
+        let mint = analysis.mint.to_string();
+        tx.execute(
+            "UPDATE token_launch_bundles SET
+             bundler_count = $2,
+             bundled_token_amount = $3,
+             bundled_supply_share = $4,
+             analyzed_at = $5
+             WHERE mint = $1",
+            &[
+                &mint,
+                &(analysis.bundlers.len() as i32),
+                &(analysis.bundled_token_amount as i64),
+                &analysis.bundled_supply_share,
+                &(analyzed_at as i64),
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_update_launch_bundle::{}::{}", mint, e);
+            err_with_loc!(PostgresClientError::TransactionError(format!(
+                "failed_to_update_launch_bundle::{}::{}",
+                mint, e
+            )))
+        })?;
+
+        tx.execute("DELETE FROM token_bundlers WHERE mint = $1", &[&mint])
+            .await
+            .map_err(|e| {
+                error!("failed_to_clear_token_bundlers::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::TransactionError(format!(
+                    "failed_to_clear_token_bundlers::{}::{}",
+                    mint, e
+                )))
+            })?;
+
+        for bundler in &analysis.bundlers {
+            let signals: Vec<&str> = bundler.signals.iter().map(|signal| signal.as_str()).collect();
+            tx.execute(
+                "INSERT INTO token_bundlers (mint, wallet, signals, sol_amount, token_amount, supply_share)
+                 VALUES ($1, $2, $3, $4, $5, $6)",
+                &[
+                    &mint,
+                    &bundler.wallet.to_string(),
+                    &signals,
+                    &(bundler.sol_amount as i64),
+                    &(bundler.token_amount as i64),
+                    &bundler.supply_share,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_insert_token_bundler::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::TransactionError(format!(
+                    "failed_to_insert_token_bundler::{}::{}",
+                    mint, e
+                )))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        })?;
+
+        Ok(())
+    }
+
+    // Wallets clustered with each mint's creator (funding graph and suspected bundlers) in one round trip
+    pub async fn get_cluster_wallets(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<HashMap<Pubkey, Vec<Pubkey>>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let mints: Vec<String> = mints.iter().map(|mint| mint.to_string()).collect();
+        let rows = conn
+            .query(
+                "SELECT mint, wallet FROM token_bundlers WHERE mint = ANY($1)
+                 UNION
+                 SELECT mint, source_pubkey FROM wallet_edges WHERE mint = ANY($1)
+                 UNION
+                 SELECT mint, target_pubkey FROM wallet_edges WHERE mint = ANY($1)",
+                &[&mints],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_cluster_wallets::mints::{}::{}", mints.len(), e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_get_cluster_wallets::mints::{}::{}",
+                    mints.len(),
+                    e
+                )))
+            })?;
+
+        let mut clusters: HashMap<Pubkey, Vec<Pubkey>> = HashMap::new();
+        for row in rows {
+            let mint = Self::parse_pubkey(row.get::<_, &str>(0))?;
+            let wallet = Self::parse_pubkey(row.get::<_, &str>(1))?;
+            clusters.entry(mint).or_default().push(wallet);
+        }
+        Ok(clusters)
+    }
+
+    // Record a launch outcome on the creator's reputation history, once per creator, mint and outcome
+    pub async fn record_creator_outcome(
+        &self,
+        event: &CreatorReputationEvent,
+    ) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let wallets: Vec<String> = event.wallets.iter().map(|wallet| wallet.to_string()).collect();
+        conn.execute(
+            "INSERT INTO creator_reputation_history (
+                creator, mint, outcome, wallets, token_amount, sol_amount, share_pct, price_impact_pct, occurred_at
+             ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             ON CONFLICT (creator, mint, outcome) DO NOTHING",
+            &[
+                &event.creator.to_string(),
+                &event.mint.to_string(),
+                &event.outcome,
+                &wallets,
+                &(event.token_amount as i64),
+                &(event.sol_amount as i64),
+                &event.share_pct,
+                &event.price_impact_pct,
+                &event.occurred_at,
+            ],
+        )
+        .await
+        .map_err(|e| {
+            error!("failed_to_record_creator_outcome::{}::{}", event.mint, e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_record_creator_outcome::{}::{}",
+                event.mint, e
+            )))
+        })?;
+
+        Ok(())
+    }
+}
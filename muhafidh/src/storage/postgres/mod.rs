@@ -1,10 +1,16 @@
+pub mod alert;
+pub mod cex_analytics;
 pub mod db;
+pub mod export;
 pub mod graph;
+pub mod launch;
 pub mod model;
+pub mod partition;
 pub mod time_series;
 
 use std::fs::File;
 use std::io::Read;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -15,15 +21,24 @@ use native_tls::Identity;
 use native_tls::TlsConnector;
 use postgres_native_tls::MakeTlsConnector;
 use tokio_postgres::Config;
+use tokio_postgres::config::SslMode;
+use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::instrument;
 
 use crate::config::StoragePostgresConfig;
+use crate::config::TlsConfig;
+use crate::config::TlsMode;
 use crate::err_with_loc;
 use crate::error::postgres::PostgresClientError;
+use crate::storage::postgres::alert::AlertDb;
+use crate::storage::postgres::cex_analytics::CexAnalyticsDb;
 use crate::storage::postgres::db::TokenMetadataDb;
+use crate::storage::postgres::export::ExportDb;
 use crate::storage::postgres::graph::GraphDb;
+use crate::storage::postgres::launch::LaunchDb;
+use crate::storage::postgres::partition::PartitionDb;
 use crate::storage::postgres::time_series::TimeSeriesDb;
 
 pub type PostgresPool = Pool<PostgresConnectionManager<MakeTlsConnector>>;
@@ -34,6 +49,11 @@ pub struct PostgresClient {
     pub db: TokenMetadataDb,
     pub time_series: TimeSeriesDb,
     pub graph: GraphDb,
+    pub cex_analytics: CexAnalyticsDb,
+    pub launch: LaunchDb,
+    pub alert: AlertDb,
+    pub partition: PartitionDb,
+    pub export: ExportDb,
 }
 
 #[async_trait::async_trait]
@@ -43,61 +63,97 @@ pub trait PostgresStorage: Send + Sync {
     async fn initialize(&self) -> Result<()>;
 }
 
-#[instrument(level = "debug", skip(config))]
-pub async fn make_postgres_client(
-    engine_name: &str,
-    config: &StoragePostgresConfig,
-) -> Result<Arc<PostgresClient>> {
-    let mut db_config = Config::new();
-    db_config
-        .user(&config.user)
-        .password(&config.password)
-        .host(&config.host)
-        .port(config.port)
-        .dbname(&config.db_name);
-
-    let mut ca_file = File::open(config.tls.ca_path.clone()).map_err(|e| {
-        error!("failed_to_open_root_ca_file: {}", e);
-        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_open_root_ca_file: {}", e)))
+fn read_tls_file(
+    path: &str,
+    kind: &str,
+) -> Result<Vec<u8>> {
+    let mut file = File::open(path).map_err(|e| {
+        error!("failed_to_open_{}_file: {}", kind, e);
+        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_open_{}_file: {}", kind, e)))
     })?;
 
-    let mut ca_data = vec![];
-    ca_file.read_to_end(&mut ca_data).map_err(|e| {
-        error!("failed_to_read_root_ca_file: {}", e);
-        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_read_root_ca_file: {}", e)))
+    let mut data = vec![];
+    file.read_to_end(&mut data).map_err(|e| {
+        error!("failed_to_read_{}_file: {}", kind, e);
+        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_read_{}_file: {}", kind, e)))
     })?;
 
-    let certificate = Certificate::from_pem(&ca_data).map_err(|e| {
-        error!("failed_to_parse_root_ca_file: {}", e);
-        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_parse_root_ca_file: {}", e)))
-    })?;
-
-    let mut identity_file = File::open(config.tls.client_identity_path.clone()).map_err(|e| {
-        error!("failed_to_open_identity_file: {}", e);
-        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_open_identity_file: {}", e)))
-    })?;
+    Ok(data)
+}
 
-    let mut identity_data = vec![];
-    identity_file.read_to_end(&mut identity_data).map_err(|e| {
-        error!("failed_to_read_identity_file: {}", e);
-        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_read_identity_file: {}", e)))
-    })?;
+// Build the connector for the configured mode. Disable still needs a connector for the pool type,
+// it is only used when the url itself asks for `sslmode=require`, checking against system roots
+fn make_tls_connector(tls: &TlsConfig) -> Result<MakeTlsConnector> {
+    let mut builder = TlsConnector::builder();
 
-    let identity = Identity::from_pkcs12(&identity_data, "").map_err(|e: native_tls::Error| {
-        error!("invalid_identity_file: {}", e);
-        err_with_loc!(PostgresClientError::TlsError(format!("invalid_identity_file: {}", e)))
+    if tls.mode != TlsMode::Disable && !tls.ca_path.is_empty() {
+        let ca_data = read_tls_file(&tls.ca_path, "root_ca")?;
+        let certificate = Certificate::from_pem(&ca_data).map_err(|e| {
+            error!("failed_to_parse_root_ca_file: {}", e);
+            err_with_loc!(PostgresClientError::TlsError(format!("failed_to_parse_root_ca_file: {}", e)))
+        })?;
+        builder.add_root_certificate(certificate);
+    }
+
+    if tls.mode == TlsMode::Mutual && tls.client_identity_path.is_empty() {
+        error!("client_identity_path_required_for_mutual_tls");
+        return Err(err_with_loc!(PostgresClientError::TlsError(String::from(
+            "client_identity_path_required_for_mutual_tls"
+        ))));
+    }
+
+    if tls.mode != TlsMode::Disable && !tls.client_identity_path.is_empty() {
+        let identity_data = read_tls_file(&tls.client_identity_path, "identity")?;
+        let identity =
+            Identity::from_pkcs12(&identity_data, &tls.client_identity_password).map_err(|e: native_tls::Error| {
+                error!("invalid_identity_file: {}", e);
+                err_with_loc!(PostgresClientError::TlsError(format!("invalid_identity_file: {}", e)))
+            })?;
+        builder.identity(identity);
+    }
+
+    let tls = builder.build().map_err(|e| {
+        error!("failed_to_build_tls_connector: {}", e);
+        err_with_loc!(PostgresClientError::TlsError(format!("failed_to_build_tls_connector: {}", e)))
     })?;
 
-    let tls = TlsConnector::builder()
-        .add_root_certificate(certificate)
-        .identity(identity)
-        .build()
-        .map_err(|e| {
-            error!("failed_to_build_tls_connector: {}", e);
-            err_with_loc!(PostgresClientError::TlsError(format!("failed_to_build_tls_connector: {}", e)))
-        })?;
+    Ok(MakeTlsConnector::new(tls))
+}
 
-    let connector = MakeTlsConnector::new(tls);
+#[instrument(level = "debug", skip(config))]
+pub async fn make_postgres_client(
+    engine_name: &str,
+    config: &StoragePostgresConfig,
+) -> Result<Arc<PostgresClient>> {
+    let tls_config = &config.tls;
+    let mut db_config = match &config.url {
+        Some(url) => Config::from_str(url).map_err(|e| {
+            error!("invalid_postgres_url: {}", e);
+            err_with_loc!(PostgresClientError::ConnectionError(e))
+        })?,
+        None => {
+            let mut db_config = Config::new();
+            db_config
+                .user(&config.user)
+                .password(&config.password)
+                .host(&config.host)
+                .port(config.port)
+                .dbname(&config.db_name);
+            db_config
+        },
+    };
+
+    // TLS is required whenever it is configured, never preferred. The `sslmode` of a url can only
+    // tighten that: `require` is kept even with TLS disabled, weaker modes are overridden
+    let url_requires_tls = config.url.is_some() && db_config.get_ssl_mode() == SslMode::Require;
+    if tls_config.mode != TlsMode::Disable || url_requires_tls {
+        db_config.ssl_mode(SslMode::Require);
+    } else {
+        db_config.ssl_mode(SslMode::Disable);
+    }
+    debug!("{}::postgres_tls_mode::{:?}::ssl_mode::{:?}", engine_name, tls_config.mode, db_config.get_ssl_mode());
+
+    let connector = make_tls_connector(tls_config)?;
 
     let mgr = PostgresConnectionManager::new(db_config, connector);
 
@@ -111,11 +167,21 @@ pub async fn make_postgres_client(
     let token_metadata_db = TokenMetadataDb::new(pool.clone());
     let time_series_db = TimeSeriesDb::new(pool.clone());
     let graph_db = GraphDb::new(pool.clone());
+    let cex_analytics_db = CexAnalyticsDb::new(pool.clone());
+    let launch_db = LaunchDb::new(pool.clone());
+    let alert_db = AlertDb::new(pool.clone());
+    let partition_db = PartitionDb::new(pool.clone());
+    let export_db = ExportDb::new(pool.clone());
 
     // Initialize database schema
     token_metadata_db.initialize().await?;
     time_series_db.initialize().await?;
     graph_db.initialize().await?;
+    cex_analytics_db.initialize().await?;
+    launch_db.initialize().await?;
+    alert_db.initialize().await?;
+    partition_db.initialize().await?;
+    export_db.initialize().await?;
 
     info!("{}::postgres_client::connection_established", engine_name);
 
@@ -124,5 +190,10 @@ pub async fn make_postgres_client(
         db: token_metadata_db,
         time_series: time_series_db,
         graph: graph_db,
+        cex_analytics: cex_analytics_db,
+        launch: launch_db,
+        alert: alert_db,
+        partition: partition_db,
+        export: export_db,
     }))
 }
@@ -1,3 +1,11 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde::Serialize;
+use solana_pubkey::Pubkey;
+
+use crate::error::Error;
+use crate::error::anyhow;
 use crate::model::token::TokenMetadata;
 
 #[derive(Debug, Clone)]
@@ -56,3 +64,438 @@ impl From<TokenMetadata> for TokenMetadataDto {
         }
     }
 }
+
+/// Filters for token queries, every unset field matches all tokens
+#[derive(Debug, Clone, Default)]
+pub struct TokenFilter {
+    pub platform: Option<String>,
+    pub creator: Option<Pubkey>,
+    /// Only tokens whose creator was traced back to this CEX wallet
+    pub cex_source: Option<Pubkey>,
+    pub is_bonded: Option<bool>,
+    /// Only tokens created at or after this unix timestamp
+    pub created_after: Option<u64>,
+    /// Only tokens created before this unix timestamp
+    pub created_before: Option<u64>,
+}
+
+/// Position after the last token of a page, tokens are ordered newest first and ties broken by mint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenCursor {
+    pub created_at: u64,
+    // Mint as stored, so a row whose mint doesn't parse still moves the cursor past it
+    pub mint: String,
+}
+
+/// One page of tokens, `next_cursor` is `None` on the last page
+#[derive(Debug, Clone)]
+pub struct TokenPage {
+    pub tokens: Vec<TokenMetadata>,
+    pub next_cursor: Option<TokenCursor>,
+}
+
+/// How edge weights are derived when routing over `wallet_edges`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgeCost {
+    /// Every transfer costs 1, so the cheapest path is the one with the fewest hops
+    #[default]
+    Hops,
+    /// Larger transfers are cheaper to traverse than dust
+    Amount,
+    /// Recent transfers are cheaper to traverse than old ones (cost grows by 1 per hour of age)
+    Recency,
+}
+
+impl EdgeCost {
+    fn cost_expr(&self) -> &'static str {
+        match self {
+            EdgeCost::Hops => "1.0::FLOAT8",
+            EdgeCost::Amount => "(1.0 / (1.0 + GREATEST(amount, 0.0)))::FLOAT8",
+            EdgeCost::Recency => {
+                "(1.0 + GREATEST(EXTRACT(EPOCH FROM NOW()) * 1000.0 - timestamp, 0.0) / 3600000.0)::FLOAT8"
+            },
+        }
+    }
+
+    /// Build the edges query consumed by the pgRouting functions
+    pub fn edges_sql(&self) -> String {
+        format!(
+            "SELECT id, source_id AS source, target_id AS target, {} AS cost FROM wallet_edges WHERE source_id IS NOT \
+             NULL AND target_id IS NOT NULL",
+            self.cost_expr()
+        )
+    }
+}
+
+/// A single wallet on a routed path, together with the transfer used to leave it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathHop {
+    pub address: Pubkey,
+    pub is_cex: bool,
+    pub cex_name: Option<String>,
+    /// Amount of the outgoing transfer, `None` on the last hop
+    pub amount: Option<f64>,
+    /// Timestamp of the outgoing transfer, `None` on the last hop
+    pub timestamp: Option<i64>,
+    /// Accumulated cost from the start of the path up to this wallet
+    pub agg_cost: f64,
+}
+
+/// An ordered path through the funding graph, from funder to funded wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingPath {
+    pub hops: Vec<PathHop>,
+    pub total_cost: f64,
+}
+
+impl FundingPath {
+    pub fn hop_count(&self) -> usize {
+        self.hops.len().saturating_sub(1)
+    }
+
+    pub fn source(&self) -> Option<&PathHop> {
+        self.hops.first()
+    }
+}
+
+/// A wallet reached from a starting wallet within a bounded number of hops
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletNeighbor {
+    pub address: Pubkey,
+    pub is_cex: bool,
+    pub cex_name: Option<String>,
+    pub hops: u32,
+}
+
+/// How a token performed after launch, derived from its price history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenLaunchOutcome {
+    pub mint: Pubkey,
+    /// First recorded price of the token
+    pub launch_price: u64,
+    pub launched_at: u64,
+    pub ath_price: u64,
+    pub ath_at: u64,
+}
+
+impl TokenLaunchOutcome {
+    /// ATH relative to the launch price, `None` when the launch price is unknown
+    pub fn ath_multiple(&self) -> Option<f64> {
+        if self.launch_price == 0 {
+            return None;
+        }
+        Some(self.ath_price as f64 / self.launch_price as f64)
+    }
+
+    pub fn time_to_ath_secs(&self) -> u64 {
+        self.ath_at.saturating_sub(self.launched_at)
+    }
+}
+
+/// Launch outcome statistics for every token funded by one exchange wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CexLaunchStats {
+    pub cex_address: Pubkey,
+    pub cex_name: String,
+    pub launch_count: u64,
+    pub bonded_count: u64,
+    pub bonded_rate: f64,
+    /// Number of launches with a recorded ATH
+    pub ath_tokens: u64,
+    pub median_ath_multiple: Option<f64>,
+    pub median_time_to_ath_secs: Option<f64>,
+}
+
+/// Bucket size of a CEX activity curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActivityBucket {
+    #[serde(rename = "hour")]
+    Hour,
+    #[serde(rename = "day")]
+    Day,
+    #[serde(rename = "week")]
+    Week,
+}
+
+impl ActivityBucket {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityBucket::Hour => "hour",
+            ActivityBucket::Day => "day",
+            ActivityBucket::Week => "week",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            ActivityBucket::Hour => 3_600,
+            ActivityBucket::Day => 86_400,
+            ActivityBucket::Week => 604_800,
+        }
+    }
+
+    /// Start of the bucket containing `timestamp` (unix seconds, UTC). Weeks start on Monday.
+    pub fn bucket_start(
+        &self,
+        timestamp: i64,
+    ) -> i64 {
+        match self {
+            // The unix epoch is a Thursday, shift by 3 days so buckets start on Monday
+            ActivityBucket::Week => {
+                let monday_offset = 3 * ActivityBucket::Day.seconds();
+                (timestamp + monday_offset).div_euclid(self.seconds()) * self.seconds() - monday_offset
+            },
+            _ => timestamp.div_euclid(self.seconds()) * self.seconds(),
+        }
+    }
+}
+
+/// Number of CEX-funded launches an exchange wallet produced in one bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CexActivityPoint {
+    pub cex_address: String,
+    pub bucket_start: i64,
+    pub token_count: u64,
+}
+
+/// Width of an OHLCV candle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    M1,
+    #[serde(rename = "5m")]
+    M5,
+    #[serde(rename = "1h")]
+    H1,
+    #[serde(rename = "1d")]
+    D1,
+}
+
+impl FromStr for CandleInterval {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1m" => Ok(CandleInterval::M1),
+            "5m" => Ok(CandleInterval::M5),
+            "1h" => Ok(CandleInterval::H1),
+            "1d" => Ok(CandleInterval::D1),
+            _ => Err(anyhow!("unsupported_candle_interval::{}", s)),
+        }
+    }
+}
+
+impl CandleInterval {
+    pub const ALL: [CandleInterval; 4] =
+        [CandleInterval::M1, CandleInterval::M5, CandleInterval::H1, CandleInterval::D1];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::M1 => "1m",
+            CandleInterval::M5 => "5m",
+            CandleInterval::H1 => "1h",
+            CandleInterval::D1 => "1d",
+        }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        match self {
+            CandleInterval::M1 => 60,
+            CandleInterval::M5 => 300,
+            CandleInterval::H1 => 3_600,
+            CandleInterval::D1 => 86_400,
+        }
+    }
+
+    /// Start of the candle containing `timestamp` (unix seconds, UTC)
+    pub fn bucket_start(
+        &self,
+        timestamp: i64,
+    ) -> i64 {
+        timestamp.div_euclid(self.seconds()) * self.seconds()
+    }
+}
+
+/// OHLCV candle of one mint. Prices use the `token_price_history` scale, volume is in lamports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candle {
+    pub mint: Pubkey,
+    pub interval: CandleInterval,
+    pub bucket_start: i64,
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    pub volume: u64,
+    pub trade_count: u64,
+    /// Timestamps of the trades that set `open` and `close`
+    pub first_trade_at: i64,
+    pub last_trade_at: i64,
+}
+
+impl Candle {
+    pub fn new(
+        mint: Pubkey,
+        interval: CandleInterval,
+        price: u64,
+        volume: u64,
+        timestamp: i64,
+    ) -> Self {
+        Self {
+            mint,
+            interval,
+            bucket_start: interval.bucket_start(timestamp),
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume,
+            trade_count: 1,
+            first_trade_at: timestamp,
+            last_trade_at: timestamp,
+        }
+    }
+
+    /// Fold a trade in, in any order: an earlier trade re-opens the candle, a later one closes it
+    pub fn apply_trade(
+        &mut self,
+        price: u64,
+        volume: u64,
+        timestamp: i64,
+    ) {
+        if timestamp < self.first_trade_at {
+            self.open = price;
+            self.first_trade_at = timestamp;
+        }
+        if timestamp >= self.last_trade_at {
+            self.close = price;
+            self.last_trade_at = timestamp;
+        }
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.volume = self.volume.saturating_add(volume);
+        self.trade_count += 1;
+    }
+
+    /// Fold in a finer candle of the same mint, in any order
+    pub fn absorb(
+        &mut self,
+        other: &Candle,
+    ) {
+        if other.first_trade_at < self.first_trade_at {
+            self.open = other.open;
+            self.first_trade_at = other.first_trade_at;
+        }
+        if other.last_trade_at >= self.last_trade_at {
+            self.close = other.close;
+            self.last_trade_at = other.last_trade_at;
+        }
+        self.high = self.high.max(other.high);
+        self.low = self.low.min(other.low);
+        self.volume = self.volume.saturating_add(other.volume);
+        self.trade_count += other.trade_count;
+    }
+}
+
+/// One of the largest holders of a mint at snapshot time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenHolder {
+    pub rank: u32,
+    pub token_account: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    // Fraction of the total supply, 0.0 - 1.0
+    pub share: f64,
+    // Owner also appears in the creator's funding graph
+    pub in_funding_graph: bool,
+}
+
+/// Supply concentration of a mint at a point in time. Curve and pool accounts are excluded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderSnapshot {
+    pub mint: Pubkey,
+    pub taken_at: i64,
+    pub total_supply: u64,
+    pub holder_count: u64,
+    pub top10_amount: u64,
+    pub top10_share: f64,
+    pub holders: Vec<TokenHolder>,
+}
+
+impl HolderSnapshot {
+    pub fn funded_holders(&self) -> impl Iterator<Item = &TokenHolder> {
+        self.holders.iter().filter(|holder| holder.in_funding_graph)
+    }
+}
+
+/// Outcome of a launch recorded against its creator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreatorReputationEvent {
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub outcome: String,
+    // Creator and clustered wallets involved
+    pub wallets: Vec<Pubkey>,
+    pub token_amount: u64,
+    pub sol_amount: u64,
+    pub share_pct: Option<f64>,
+    pub price_impact_pct: Option<f64>,
+    pub occurred_at: i64,
+}
+
+/// History table range-partitioned by day on its `timestamp` (unix seconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionedTable {
+    TokenPriceHistory,
+    TokenVolumeHistory,
+    CexActivityHistory,
+}
+
+impl PartitionedTable {
+    pub const ALL: [PartitionedTable; 3] = [
+        PartitionedTable::TokenPriceHistory,
+        PartitionedTable::TokenVolumeHistory,
+        PartitionedTable::CexActivityHistory,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PartitionedTable::TokenPriceHistory => "token_price_history",
+            PartitionedTable::TokenVolumeHistory => "token_volume_history",
+            PartitionedTable::CexActivityHistory => "cex_activity_history",
+        }
+    }
+
+    /// Catch-all partition for rows outside every daily partition
+    pub fn default_partition(&self) -> String {
+        format!("{}_default", self.as_str())
+    }
+
+    /// Daily partition holding `day` (days since the unix epoch), e.g. `token_price_history_p20250403`
+    pub fn partition_name(
+        &self,
+        day: i64,
+    ) -> String {
+        let date = chrono::DateTime::from_timestamp(day * 86_400, 0).unwrap_or_default();
+        format!("{}_p{}", self.as_str(), date.format("%Y%m%d"))
+    }
+
+    /// Day of a daily partition of this table, None for any other name
+    pub fn partition_day(
+        &self,
+        partition: &str,
+    ) -> Option<i64> {
+        let date = partition.strip_prefix(self.as_str())?.strip_prefix("_p")?;
+        let date = chrono::NaiveDate::parse_from_str(date, "%Y%m%d").ok()?;
+        Some(date.and_hms_opt(0, 0, 0)?.and_utc().timestamp().div_euclid(86_400))
+    }
+}
+
+/// What one maintenance pass did to a partitioned table
+#[derive(Debug, Clone, Default)]
+pub struct PartitionMaintenance {
+    pub created: usize,
+    pub retired: usize,
+    // Expired rows deleted from the default partition
+    pub purged_rows: u64,
+}
@@ -0,0 +1,456 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+
+use crate::config::PartitionConfig;
+use crate::config::PartitionRetentionConfig;
+use crate::err_with_loc;
+use crate::error::Result;
+use crate::error::postgres::PostgresClientError;
+use crate::handler::shutdown::ShutdownSignal;
+use crate::storage::postgres::PostgresPool;
+use crate::storage::postgres::PostgresStorage;
+use crate::storage::postgres::model::PartitionMaintenance;
+use crate::storage::postgres::model::PartitionedTable;
+
+/// Schema expired partitions are moved to when they are archived
+pub const ARCHIVE_SCHEMA: &str = "history_archive";
+
+const DAY_SECS: i64 = 86_400;
+
+#[derive(Debug, Clone)]
+pub struct PartitionDb {
+    pub pool: Arc<PostgresPool>,
+}
+
+#[async_trait::async_trait]
+impl PostgresStorage for PartitionDb {
+    fn new(pool: Arc<PostgresPool>) -> Self {
+        Self {
+            pool,
+        }
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        conn.execute("SELECT 1", &[]).await.map_err(|e| {
+            error!("failed_to_health_check: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_health_check: {}", e)))
+        })?;
+        Ok(())
+    }
+
+    // No need to initialize tables here as this is now handled by migrations
+    async fn initialize(&self) -> Result<()> {
+        // Just do a health check to ensure the database is available
+        self.health_check().await
+    }
+}
+
+impl PartitionDb {
+    // Every service writing history runs this, passes of several instances are serialized per table
+    pub fn spawn_maintenance(
+        &self,
+        config: PartitionConfig,
+        shutdown: ShutdownSignal,
+    ) -> JoinHandle<()> {
+        let partition_db = self.clone();
+        tokio::spawn(async move {
+            debug!("history_partition_maintenance_task::started");
+            let mut interval = tokio::time::interval(Duration::from_secs(config.maintenance_interval_secs.max(1)));
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        partition_db.maintain_tables(&config).await;
+                    },
+                    _ = shutdown.wait_for_shutdown() => {
+                        debug!("history_partition_maintenance_task::received_shutdown_signal");
+                        break;
+                    },
+                }
+            }
+        })
+    }
+
+    // Maintain every partitioned table with its own retention. A failing table is logged and skipped
+    pub async fn maintain_tables(
+        &self,
+        config: &PartitionConfig,
+    ) {
+        for table in PartitionedTable::ALL {
+            let retention = match table {
+                PartitionedTable::TokenPriceHistory => &config.token_price_history,
+                PartitionedTable::TokenVolumeHistory => &config.token_volume_history,
+                PartitionedTable::CexActivityHistory => &config.cex_activity_history,
+            };
+
+            match self.maintain_table(table, config.premake_days, retention).await {
+                Ok(maintenance) => {
+                    debug!(
+                        "partition_maintenance::{}::created::{}::retired::{}::purged_rows::{}",
+                        table.as_str(),
+                        maintenance.created,
+                        maintenance.retired,
+                        maintenance.purged_rows
+                    );
+                },
+                Err(e) => {
+                    error!("failed_to_maintain_partitions::{}::error::{}", table.as_str(), e);
+                },
+            }
+        }
+    }
+
+    // One maintenance pass: create upcoming partitions, move stray rows out of the default partition
+    // and retire partitions past retention
+    pub async fn maintain_table(
+        &self,
+        table: PartitionedTable,
+        premake_days: u64,
+        retention: &PartitionRetentionConfig,
+    ) -> Result<PartitionMaintenance> {
+        let mut maintenance = PartitionMaintenance::default();
+        let today = chrono::Utc::now().timestamp().div_euclid(DAY_SECS);
+        // Partitions of days before this are expired
+        let cutoff_day = (retention.retention_days > 0).then(|| today - retention.retention_days as i64);
+
+        // Expired rows that are going to be dropped anyway aren't worth a partition of their own
+        if let Some(cutoff_day) = cutoff_day {
+            if !retention.archive {
+                maintenance.purged_rows = self.purge_default_partition(table, cutoff_day * DAY_SECS).await?;
+            }
+        }
+
+        let mut days: BTreeSet<i64> = (today..=today + premake_days as i64).collect();
+        days.extend(self.get_default_partition_days(table).await?);
+        for day in days {
+            if self.create_partition(table, day).await? {
+                maintenance.created += 1;
+            }
+        }
+
+        if let Some(cutoff_day) = cutoff_day {
+            for (partition, day) in self.get_partitions(table).await? {
+                if day < cutoff_day {
+                    self.retire_partition(table, &partition, retention.archive).await?;
+                    maintenance.retired += 1;
+                }
+            }
+        }
+
+        Ok(maintenance)
+    }
+
+    // Create the partition of one day, moving its rows out of the default partition.
+    // Returns false when it is already attached.
+    //
+    // Attaching checks the new partition against its bounds and the default partition for rows of the
+    // range, scanning both under a lock that blocks writes unless CHECK constraints already prove it.
+    // The new partition is created with its bounds as a CHECK. For future days, which no write can
+    // target meanwhile, the default partition gets a CHECK excluding the range, validated outside the
+    // blocking lock, so the attach itself scans nothing. Today and earlier days only come up on a first
+    // pass or for stray rows, the default partition is scanned for those.
+    pub async fn create_partition(
+        &self,
+        table: PartitionedTable,
+        day: i64,
+    ) -> Result<bool> {
+        let partition = table.partition_name(day);
+        let default_partition = table.default_partition();
+        let start = day * DAY_SECS;
+        let end = start + DAY_SECS;
+        let today = chrono::Utc::now().timestamp().div_euclid(DAY_SECS);
+        let range_check = format!("{}_range", partition);
+        let default_check = format!("{}_excluded", partition);
+        let exclude_from_default = day > today;
+
+        let mut statements = vec![
+            format!(
+                "CREATE TABLE IF NOT EXISTS {} (LIKE {} INCLUDING DEFAULTS,
+                 CONSTRAINT {} CHECK (timestamp IS NOT NULL AND timestamp >= {} AND timestamp < {}))",
+                partition,
+                table.as_str(),
+                range_check,
+                start,
+                end
+            ),
+            format!(
+                "WITH moved AS (DELETE FROM {} WHERE timestamp >= {} AND timestamp < {} RETURNING *)
+                 INSERT INTO {} SELECT * FROM moved",
+                default_partition, start, end, partition
+            ),
+        ];
+        // The partition bound takes over from both checks once attached
+        let attach = [
+            format!(
+                "ALTER TABLE {} ATTACH PARTITION {} FOR VALUES FROM ({}) TO ({})",
+                table.as_str(),
+                partition,
+                start,
+                end
+            ),
+            format!("ALTER TABLE {} DROP CONSTRAINT IF EXISTS {}", partition, range_check),
+            format!("ALTER TABLE {} DROP CONSTRAINT IF EXISTS {}", default_partition, default_check),
+        ];
+
+        if !exclude_from_default {
+            statements.extend(attach);
+            if !self.run_locked(table, &partition, &statements).await? {
+                return Ok(false);
+            }
+            debug!("partition_created::{}", partition);
+            return Ok(true);
+        }
+
+        statements.extend([
+            format!("ALTER TABLE {} DROP CONSTRAINT IF EXISTS {}", default_partition, default_check),
+            format!(
+                "ALTER TABLE {} ADD CONSTRAINT {} CHECK (timestamp < {} OR timestamp >= {}) NOT VALID",
+                default_partition, default_check, start, end
+            ),
+        ]);
+        if !self.run_locked(table, &partition, &statements).await? {
+            return Ok(false);
+        }
+
+        // Validating only takes a lock that lets writes through
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+        let sql = format!("ALTER TABLE {} VALIDATE CONSTRAINT {}", default_partition, default_check);
+        conn.execute(sql.as_str(), &[]).await.map_err(|e| {
+            error!("failed_to_validate_default_partition_check::{}: {}", partition, e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_validate_default_partition_check::{}: {}",
+                partition, e
+            )))
+        })?;
+        drop(conn);
+
+        if !self.run_locked(table, &partition, &attach).await? {
+            return Ok(false);
+        }
+
+        debug!("partition_created::{}", partition);
+        Ok(true)
+    }
+
+    // Run statements in one transaction under the table's maintenance lock, unless the partition is
+    // attached by then. A partition created but not attached yet (e.g. after a crash in between) is
+    // picked up again by the next pass
+    async fn run_locked(
+        &self,
+        table: PartitionedTable,
+        partition: &str,
+        statements: &[String],
+    ) -> Result<bool> {
+        let mut conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let tx = conn.transaction().await.map_err(|e| {
+            error!("failed_to_start_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
+        })?;
+
+        // Serializes maintenance of a table across instances
+        tx.execute("SELECT pg_advisory_xact_lock(hashtext($1))", &[&table.as_str()])
+            .await
+            .map_err(|e| {
+                error!("failed_to_lock_partitioned_table::{}: {}", table.as_str(), e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_lock_partitioned_table::{}: {}",
+                    table.as_str(),
+                    e
+                )))
+            })?;
+
+        let attached: bool = tx
+            .query_one("SELECT EXISTS (SELECT 1 FROM pg_inherits WHERE inhrelid = to_regclass($1::TEXT))", &[
+                &partition,
+            ])
+            .await
+            .map_err(|e| {
+                error!("failed_to_check_partition::{}: {}", partition, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_check_partition::{}: {}",
+                    partition, e
+                )))
+            })?
+            .get(0);
+        if attached {
+            return Ok(false);
+        }
+
+        for sql in statements {
+            tx.execute(sql.as_str(), &[]).await.map_err(|e| {
+                error!("failed_to_create_partition::{}: {}", partition, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_create_partition::{}: {}",
+                    partition, e
+                )))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        })?;
+
+        Ok(true)
+    }
+
+    // Drop an expired partition, or detach it into the archive schema
+    pub async fn retire_partition(
+        &self,
+        table: PartitionedTable,
+        partition: &str,
+        archive: bool,
+    ) -> Result<()> {
+        let mut conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let tx = conn.transaction().await.map_err(|e| {
+            error!("failed_to_start_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
+        })?;
+
+        tx.execute("SELECT pg_advisory_xact_lock(hashtext($1))", &[&table.as_str()])
+            .await
+            .map_err(|e| {
+                error!("failed_to_lock_partitioned_table::{}: {}", table.as_str(), e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_lock_partitioned_table::{}: {}",
+                    table.as_str(),
+                    e
+                )))
+            })?;
+
+        let statements = if archive {
+            vec![
+                format!("ALTER TABLE {} DETACH PARTITION {}", table.as_str(), partition),
+                format!("ALTER TABLE {} SET SCHEMA {}", partition, ARCHIVE_SCHEMA),
+            ]
+        } else {
+            vec![format!("DROP TABLE IF EXISTS {}", partition)]
+        };
+        for sql in &statements {
+            tx.execute(sql.as_str(), &[]).await.map_err(|e| {
+                error!("failed_to_retire_partition::{}: {}", partition, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_retire_partition::{}: {}",
+                    partition, e
+                )))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        })?;
+
+        info!("partition_retired::{}::archived::{}", partition, archive);
+        Ok(())
+    }
+
+    // Daily partitions attached to a table, with their day
+    pub async fn get_partitions(
+        &self,
+        table: PartitionedTable,
+    ) -> Result<Vec<(String, i64)>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT child.relname::TEXT
+                 FROM pg_inherits i
+                 JOIN pg_class child ON child.oid = i.inhrelid
+                 JOIN pg_class parent ON parent.oid = i.inhparent
+                 WHERE parent.relname = $1",
+                &[&table.as_str()],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_partitions::{}: {}", table.as_str(), e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_get_partitions::{}: {}",
+                    table.as_str(),
+                    e
+                )))
+            })?;
+
+        let partitions = rows
+            .iter()
+            .filter_map(|row| {
+                let partition: String = row.get(0);
+                let day = table.partition_day(&partition)?;
+                Some((partition, day))
+            })
+            .collect();
+
+        Ok(partitions)
+    }
+
+    // Days with rows in the default partition, i.e. rows no daily partition covered when written
+    async fn get_default_partition_days(
+        &self,
+        table: PartitionedTable,
+    ) -> Result<Vec<i64>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let sql = format!("SELECT DISTINCT floor(timestamp / 86400.0)::BIGINT FROM {}", table.default_partition());
+        let rows = conn.query(sql.as_str(), &[]).await.map_err(|e| {
+            error!("failed_to_get_default_partition_days::{}: {}", table.as_str(), e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_get_default_partition_days::{}: {}",
+                table.as_str(),
+                e
+            )))
+        })?;
+
+        Ok(rows.iter().map(|row| row.get::<_, i64>(0)).collect())
+    }
+
+    // Delete rows older than `cutoff` (unix seconds) from the default partition
+    async fn purge_default_partition(
+        &self,
+        table: PartitionedTable,
+        cutoff: i64,
+    ) -> Result<u64> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let sql = format!("DELETE FROM {} WHERE timestamp < $1", table.default_partition());
+        let purged = conn.execute(sql.as_str(), &[&cutoff]).await.map_err(|e| {
+            error!("failed_to_purge_default_partition::{}: {}", table.as_str(), e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_purge_default_partition::{}: {}",
+                table.as_str(),
+                e
+            )))
+        })?;
+
+        Ok(purged)
+    }
+}
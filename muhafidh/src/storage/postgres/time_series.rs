@@ -21,8 +21,11 @@
 //     }
 // }
 
+use std::str::FromStr;
 use std::sync::Arc;
 
+use solana_pubkey::Pubkey;
+use tokio_postgres::Row;
 use tracing::error;
 
 use crate::err_with_loc;
@@ -30,6 +33,12 @@ use crate::error::Result;
 use crate::error::postgres::PostgresClientError;
 use crate::storage::postgres::PostgresPool;
 use crate::storage::postgres::PostgresStorage;
+use crate::storage::postgres::model::ActivityBucket;
+use crate::storage::postgres::model::Candle;
+use crate::storage::postgres::model::CandleInterval;
+use crate::storage::postgres::model::CexActivityPoint;
+use crate::storage::postgres::model::HolderSnapshot;
+use crate::storage::postgres::model::TokenHolder;
 
 #[derive(Debug, Clone)]
 pub struct TimeSeriesDb {
@@ -65,12 +74,13 @@ impl PostgresStorage for TimeSeriesDb {
 }
 
 impl TimeSeriesDb {
-    // Add a token price record
+    // Add a token price record, one row per trade so trades in the same second don't overwrite each other
     pub async fn add_token_price(
         &self,
         mint: &str,
         price: u64,
         timestamp: i64,
+        trade_id: &str,
     ) -> Result<()> {
         let conn = self.pool.get().await.map_err(|e| {
             error!("failed_to_get_client_pool_connection: {}", e);
@@ -78,11 +88,11 @@ impl TimeSeriesDb {
         })?;
 
         conn.execute(
-            "INSERT INTO token_price_history (mint, price, timestamp)
-                 VALUES ($1, $2, $3)
-                 ON CONFLICT (mint, timestamp) DO UPDATE SET
+            "INSERT INTO token_price_history (mint, price, timestamp, trade_id)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (mint, timestamp, trade_id) DO UPDATE SET
                  price = EXCLUDED.price",
-            &[&mint, &(price as i64), &timestamp],
+            &[&mint, &(price as i64), &timestamp, &trade_id],
         )
         .await
         .map_err(|e| {
@@ -93,12 +103,13 @@ impl TimeSeriesDb {
         Ok(())
     }
 
-    // Add a token volume record
+    // Add a token volume record, one row per trade so trades in the same second don't overwrite each other
     pub async fn add_token_volume(
         &self,
         mint: &str,
         volume: u64,
         timestamp: i64,
+        trade_id: &str,
     ) -> Result<()> {
         let conn = self.pool.get().await.map_err(|e| {
             error!("failed_to_get_client_pool_connection: {}", e);
@@ -106,11 +117,11 @@ impl TimeSeriesDb {
         })?;
 
         conn.execute(
-            "INSERT INTO token_volume_history (mint, volume, timestamp)
-                 VALUES ($1, $2, $3)
-                 ON CONFLICT (mint, timestamp) DO UPDATE SET
+            "INSERT INTO token_volume_history (mint, volume, timestamp, trade_id)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (mint, timestamp, trade_id) DO UPDATE SET
                  volume = EXCLUDED.volume",
-            &[&mint, &(volume as i64), &timestamp],
+            &[&mint, &(volume as i64), &timestamp, &trade_id],
         )
         .await
         .map_err(|e| {
@@ -148,4 +159,562 @@ impl TimeSeriesDb {
 
         Ok(())
     }
+
+    // Record one CEX-funded launch into its hourly activity bucket
+    pub async fn record_cex_launch(
+        &self,
+        cex_address: &str,
+        launched_at: i64,
+    ) -> Result<()> {
+        self.add_cex_activity(cex_address, 1, ActivityBucket::Hour.bucket_start(launched_at))
+            .await
+    }
+
+    // Re-aggregate hourly CEX activity since `since` into daily or weekly rollups
+    pub async fn rollup_cex_activity(
+        &self,
+        bucket: ActivityBucket,
+        since: i64,
+    ) -> Result<u64> {
+        if bucket == ActivityBucket::Hour {
+            return Ok(0);
+        }
+
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        // Recompute whole buckets so a partially rolled up bucket gets corrected
+        let since = bucket.bucket_start(since);
+        let bucket_expr = match bucket {
+            ActivityBucket::Week => {
+                "(EXTRACT(EPOCH FROM date_trunc('week', to_timestamp(timestamp) AT TIME ZONE 'UTC')))::BIGINT"
+            },
+            _ => "(timestamp / 86400) * 86400",
+        };
+
+        let sql = format!(
+            "INSERT INTO cex_activity_rollups (cex_address, granularity, bucket_start, token_count, updated_at)
+             SELECT cex_address, $1::TEXT, {bucket_expr} AS bucket_start, SUM(token_count)::BIGINT, NOW()
+             FROM cex_activity_history
+             WHERE timestamp >= $2
+             GROUP BY cex_address, bucket_start
+             ON CONFLICT (cex_address, granularity, bucket_start) DO UPDATE SET
+             token_count = EXCLUDED.token_count,
+             updated_at = NOW()"
+        );
+
+        let rolled_up = conn.execute(sql.as_str(), &[&bucket.as_str(), &since]).await.map_err(|e| {
+            error!("failed_to_rollup_cex_activity::{}::{}", bucket.as_str(), e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_rollup_cex_activity::{}::{}",
+                bucket.as_str(),
+                e
+            )))
+        })?;
+
+        Ok(rolled_up)
+    }
+
+    // Activity curve of one exchange wallet between `from` and `to` (unix seconds, inclusive)
+    pub async fn get_cex_activity_curve(
+        &self,
+        cex_address: &str,
+        bucket: ActivityBucket,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<CexActivityPoint>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = match bucket {
+            ActivityBucket::Hour => {
+                conn.query(
+                    "SELECT cex_address, timestamp AS bucket_start, token_count
+                     FROM cex_activity_history
+                     WHERE cex_address = $1 AND timestamp >= $2 AND timestamp <= $3
+                     ORDER BY timestamp ASC",
+                    &[&cex_address, &bucket.bucket_start(from), &to],
+                )
+                .await
+            },
+            _ => {
+                conn.query(
+                    "SELECT cex_address, bucket_start, token_count
+                     FROM cex_activity_rollups
+                     WHERE cex_address = $1 AND granularity = $2 AND bucket_start >= $3 AND bucket_start <= $4
+                     ORDER BY bucket_start ASC",
+                    &[&cex_address, &bucket.as_str(), &bucket.bucket_start(from), &to],
+                )
+                .await
+            },
+        }
+        .map_err(|e| {
+            error!("failed_to_get_cex_activity_curve::{}::{}", cex_address, e);
+            err_with_loc!(PostgresClientError::QueryError(format!(
+                "failed_to_get_cex_activity_curve::{}::{}",
+                cex_address, e
+            )))
+        })?;
+
+        Ok(rows
+            .iter()
+            .map(|row| CexActivityPoint {
+                cex_address: row.get("cex_address"),
+                bucket_start: row.get("bucket_start"),
+                token_count: row.get::<_, i64>("token_count").max(0) as u64,
+            })
+            .collect())
+    }
+
+    // Activity curves of every exchange wallet that was active between `from` and `to`
+    pub async fn get_all_cex_activity_curves(
+        &self,
+        bucket: ActivityBucket,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<CexActivityPoint>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = match bucket {
+            ActivityBucket::Hour => {
+                conn.query(
+                    "SELECT cex_address, timestamp AS bucket_start, token_count
+                     FROM cex_activity_history
+                     WHERE timestamp >= $1 AND timestamp <= $2
+                     ORDER BY cex_address, timestamp ASC",
+                    &[&bucket.bucket_start(from), &to],
+                )
+                .await
+            },
+            _ => {
+                conn.query(
+                    "SELECT cex_address, bucket_start, token_count
+                     FROM cex_activity_rollups
+                     WHERE granularity = $1 AND bucket_start >= $2 AND bucket_start <= $3
+                     ORDER BY cex_address, bucket_start ASC",
+                    &[&bucket.as_str(), &bucket.bucket_start(from), &to],
+                )
+                .await
+            },
+        }
+        .map_err(|e| {
+            error!("failed_to_get_all_cex_activity_curves: {}", e);
+            err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_all_cex_activity_curves: {}", e)))
+        })?;
+
+        Ok(rows
+            .iter()
+            .map(|row| CexActivityPoint {
+                cex_address: row.get("cex_address"),
+                bucket_start: row.get("bucket_start"),
+                token_count: row.get::<_, i64>("token_count").max(0) as u64,
+            })
+            .collect())
+    }
+
+    // Recompute every candle of a mint whose bucket overlaps `from..=to` from raw price and volume
+    // history, replacing whatever was stored. Buckets are rebuilt whole, so running it again or for a
+    // late trade gives the same result. Curve prices come per slot and volumes per trade, so volume is
+    // summed per bucket rather than matched to a price row
+    pub async fn rebuild_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rebuilt = conn
+            .execute(
+                "WITH prices AS (
+                    SELECT timestamp, trade_id, price, (timestamp / $2) * $2 AS bucket_start
+                    FROM token_price_history
+                    WHERE mint = $1 AND timestamp >= $3 AND timestamp < $4
+                ),
+                volumes AS (
+                    SELECT (timestamp / $2) * $2 AS bucket_start, SUM(volume)::BIGINT AS volume, COUNT(*) AS \
+                 trade_count
+                    FROM token_volume_history
+                    WHERE mint = $1 AND timestamp >= $3 AND timestamp < $4
+                    GROUP BY 1
+                )
+                INSERT INTO token_candles (
+                    mint, interval, bucket_start, open, high, low, close, volume, trade_count,
+                    first_trade_at, last_trade_at, updated_at
+                )
+                SELECT
+                    $1,
+                    $5::TEXT,
+                    p.bucket_start,
+                    (ARRAY_AGG(p.price ORDER BY p.timestamp ASC, p.trade_id ASC))[1],
+                    MAX(p.price),
+                    MIN(p.price),
+                    (ARRAY_AGG(p.price ORDER BY p.timestamp DESC, p.trade_id DESC))[1],
+                    COALESCE(MAX(v.volume), 0),
+                    COALESCE(MAX(v.trade_count), COUNT(*)),
+                    MIN(p.timestamp),
+                    MAX(p.timestamp),
+                    NOW()
+                FROM prices p
+                LEFT JOIN volumes v ON v.bucket_start = p.bucket_start
+                GROUP BY p.bucket_start
+                ON CONFLICT (mint, interval, bucket_start) DO UPDATE SET
+                open = EXCLUDED.open,
+                high = EXCLUDED.high,
+                low = EXCLUDED.low,
+                close = EXCLUDED.close,
+                volume = EXCLUDED.volume,
+                trade_count = EXCLUDED.trade_count,
+                first_trade_at = EXCLUDED.first_trade_at,
+                last_trade_at = EXCLUDED.last_trade_at,
+                updated_at = NOW()",
+                &[
+                    &mint,
+                    &interval.seconds(),
+                    &interval.bucket_start(from),
+                    &(interval.bucket_start(to) + interval.seconds()),
+                    &interval.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_rebuild_candles::{}::{}::{}", mint, interval.as_str(), e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_rebuild_candles::{}::{}::{}",
+                    mint,
+                    interval.as_str(),
+                    e
+                )))
+            })?;
+
+        Ok(rebuilt)
+    }
+
+    // Recompute the `interval` candles of a mint overlapping `from..=to` from its 1m candles, so coarser
+    // intervals don't scan raw history again. Whole buckets are rolled up, like `rebuild_candles`
+    pub async fn rollup_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<u64> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rolled = conn
+            .execute(
+                "INSERT INTO token_candles (
+                    mint, interval, bucket_start, open, high, low, close, volume, trade_count,
+                    first_trade_at, last_trade_at, updated_at
+                )
+                SELECT
+                    $1,
+                    $5::TEXT,
+                    (bucket_start / $2) * $2 AS rollup_start,
+                    (ARRAY_AGG(open ORDER BY first_trade_at ASC))[1],
+                    MAX(high),
+                    MIN(low),
+                    (ARRAY_AGG(close ORDER BY last_trade_at DESC))[1],
+                    SUM(volume)::BIGINT,
+                    SUM(trade_count)::BIGINT,
+                    MIN(first_trade_at),
+                    MAX(last_trade_at),
+                    NOW()
+                FROM token_candles
+                WHERE mint = $1 AND interval = $6 AND bucket_start >= $3 AND bucket_start < $4
+                GROUP BY rollup_start
+                ON CONFLICT (mint, interval, bucket_start) DO UPDATE SET
+                open = EXCLUDED.open,
+                high = EXCLUDED.high,
+                low = EXCLUDED.low,
+                close = EXCLUDED.close,
+                volume = EXCLUDED.volume,
+                trade_count = EXCLUDED.trade_count,
+                first_trade_at = EXCLUDED.first_trade_at,
+                last_trade_at = EXCLUDED.last_trade_at,
+                updated_at = NOW()",
+                &[
+                    &mint,
+                    &interval.seconds(),
+                    &interval.bucket_start(from),
+                    &(interval.bucket_start(to) + interval.seconds()),
+                    &interval.as_str(),
+                    &CandleInterval::M1.as_str(),
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_rollup_candles::{}::{}::{}", mint, interval.as_str(), e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_rollup_candles::{}::{}::{}",
+                    mint,
+                    interval.as_str(),
+                    e
+                )))
+            })?;
+
+        Ok(rolled)
+    }
+
+    fn row_to_candle(row: &Row) -> Result<Candle> {
+        let mint: &str = row.get("mint");
+        let interval: &str = row.get("interval");
+        Ok(Candle {
+            mint: Pubkey::from_str(mint).map_err(|e| {
+                error!("invalid_mint_in_candles::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!("invalid_mint_in_candles::{}::{}", mint, e)))
+            })?,
+            interval: interval.parse::<CandleInterval>().map_err(|e| {
+                err_with_loc!(PostgresClientError::QueryError(format!("invalid_candle_interval::{}", e)))
+            })?,
+            bucket_start: row.get("bucket_start"),
+            open: row.get::<_, i64>("open").max(0) as u64,
+            high: row.get::<_, i64>("high").max(0) as u64,
+            low: row.get::<_, i64>("low").max(0) as u64,
+            close: row.get::<_, i64>("close").max(0) as u64,
+            volume: row.get::<_, i64>("volume").max(0) as u64,
+            trade_count: row.get::<_, i64>("trade_count").max(0) as u64,
+            first_trade_at: row.get("first_trade_at"),
+            last_trade_at: row.get("last_trade_at"),
+        })
+    }
+
+    // Candles of a mint whose bucket starts between `from` and `to` (unix seconds, inclusive)
+    pub async fn get_candles(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<Candle>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT mint, interval, bucket_start, open, high, low, close, volume, trade_count,
+                    first_trade_at, last_trade_at
+                 FROM token_candles
+                 WHERE mint = $1 AND interval = $2 AND bucket_start >= $3 AND bucket_start <= $4
+                 ORDER BY bucket_start ASC",
+                &[&mint, &interval.as_str(), &interval.bucket_start(from), &to],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_candles::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_candles::{}::{}", mint, e)))
+            })?;
+
+        rows.iter().map(Self::row_to_candle).collect()
+    }
+
+    // Most recent candle of a mint, if any
+    pub async fn get_latest_candle(
+        &self,
+        mint: &str,
+        interval: CandleInterval,
+    ) -> Result<Option<Candle>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let row = conn
+            .query_opt(
+                "SELECT mint, interval, bucket_start, open, high, low, close, volume, trade_count,
+                    first_trade_at, last_trade_at
+                 FROM token_candles
+                 WHERE mint = $1 AND interval = $2
+                 ORDER BY bucket_start DESC
+                 LIMIT 1",
+                &[&mint, &interval.as_str()],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_latest_candle::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!("failed_to_get_latest_candle::{}::{}", mint, e)))
+            })?;
+
+        row.as_ref().map(Self::row_to_candle).transpose()
+    }
+
+    // Store a holder snapshot together with its largest holders
+    pub async fn insert_holder_snapshot(
+        &self,
+        snapshot: &HolderSnapshot,
+    ) -> Result<()> {
+        let mut conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let tx = conn.transaction().await.map_err(|e| {
+            error!("failed_to_start_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_start_transaction: {}", e)))
+        })?;
+
+        let funded_holder_count = snapshot.funded_holders().count() as i32;
+        let snapshot_id: i32 = tx
+            .query_one(
+                "INSERT INTO token_holder_snapshots (
+                    mint, taken_at, total_supply, holder_count, top10_amount, top10_share, funded_holder_count
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                 ON CONFLICT (mint, taken_at) DO UPDATE SET
+                 total_supply = EXCLUDED.total_supply,
+                 holder_count = EXCLUDED.holder_count,
+                 top10_amount = EXCLUDED.top10_amount,
+                 top10_share = EXCLUDED.top10_share,
+                 funded_holder_count = EXCLUDED.funded_holder_count
+                 RETURNING id",
+                &[
+                    &snapshot.mint.to_string(),
+                    &snapshot.taken_at,
+                    &(snapshot.total_supply as i64),
+                    &(snapshot.holder_count as i64),
+                    &(snapshot.top10_amount as i64),
+                    &snapshot.top10_share,
+                    &funded_holder_count,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_insert_holder_snapshot::{}::{}", snapshot.mint, e);
+                err_with_loc!(PostgresClientError::TransactionError(format!(
+                    "failed_to_insert_holder_snapshot::{}::{}",
+                    snapshot.mint, e
+                )))
+            })?
+            .get(0);
+
+        tx.execute("DELETE FROM token_holder_snapshot_holders WHERE snapshot_id = $1", &[&snapshot_id])
+            .await
+            .map_err(|e| {
+                error!("failed_to_clear_holder_snapshot_holders::{}::{}", snapshot.mint, e);
+                err_with_loc!(PostgresClientError::TransactionError(format!(
+                    "failed_to_clear_holder_snapshot_holders::{}::{}",
+                    snapshot.mint, e
+                )))
+            })?;
+
+        for holder in &snapshot.holders {
+            tx.execute(
+                "INSERT INTO token_holder_snapshot_holders (
+                    snapshot_id, rank, token_account, owner, amount, share, in_funding_graph
+                 ) VALUES ($1, $2, $3, $4, $5, $6, $7)",
+                &[
+                    &snapshot_id,
+                    &(holder.rank as i32),
+                    &holder.token_account.to_string(),
+                    &holder.owner.to_string(),
+                    &(holder.amount as i64),
+                    &holder.share,
+                    &holder.in_funding_graph,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_insert_holder_snapshot_holder::{}::{}", snapshot.mint, e);
+                err_with_loc!(PostgresClientError::TransactionError(format!(
+                    "failed_to_insert_holder_snapshot_holder::{}::{}",
+                    snapshot.mint, e
+                )))
+            })?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            error!("failed_to_commit_transaction: {}", e);
+            err_with_loc!(PostgresClientError::TransactionError(format!("failed_to_commit_transaction: {}", e)))
+        })?;
+
+        Ok(())
+    }
+
+    fn parse_pubkey(value: &str) -> Result<Pubkey> {
+        Pubkey::from_str(value).map_err(|e| {
+            error!("invalid_pubkey_in_time_series::{}::{}", value, e);
+            err_with_loc!(PostgresClientError::QueryError(format!("invalid_pubkey_in_time_series::{}::{}", value, e)))
+        })
+    }
+
+    // Holder snapshots of a mint taken between `from` and `to` (unix seconds, inclusive), oldest first
+    pub async fn get_holder_snapshots(
+        &self,
+        mint: &Pubkey,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<HolderSnapshot>> {
+        let conn = self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_client_pool_connection: {}", e);
+            err_with_loc!(PostgresClientError::PoolError(e))
+        })?;
+
+        let rows = conn
+            .query(
+                "SELECT s.id, s.taken_at, s.total_supply, s.holder_count, s.top10_amount, s.top10_share,
+                    h.rank, h.token_account, h.owner, h.amount, h.share, h.in_funding_graph
+                 FROM token_holder_snapshots s
+                 LEFT JOIN token_holder_snapshot_holders h ON h.snapshot_id = s.id
+                 WHERE s.mint = $1 AND s.taken_at >= $2 AND s.taken_at <= $3
+                 ORDER BY s.taken_at ASC, h.rank ASC",
+                &[&mint.to_string(), &from, &to],
+            )
+            .await
+            .map_err(|e| {
+                error!("failed_to_get_holder_snapshots::{}::{}", mint, e);
+                err_with_loc!(PostgresClientError::QueryError(format!(
+                    "failed_to_get_holder_snapshots::{}::{}",
+                    mint, e
+                )))
+            })?;
+
+        let mut snapshots: Vec<HolderSnapshot> = Vec::new();
+        let mut current_id = None;
+        for row in &rows {
+            let id: i32 = row.get("id");
+            if current_id != Some(id) {
+                current_id = Some(id);
+                snapshots.push(HolderSnapshot {
+                    mint: *mint,
+                    taken_at: row.get("taken_at"),
+                    total_supply: row.get::<_, i64>("total_supply").max(0) as u64,
+                    holder_count: row.get::<_, i64>("holder_count").max(0) as u64,
+                    top10_amount: row.get::<_, i64>("top10_amount").max(0) as u64,
+                    top10_share: row.get("top10_share"),
+                    holders: Vec::new(),
+                });
+            }
+
+            let Some(rank) = row.get::<_, Option<i32>>("rank") else {
+                continue;
+            };
+            if let Some(snapshot) = snapshots.last_mut() {
+                snapshot.holders.push(TokenHolder {
+                    rank: rank.max(0) as u32,
+                    token_account: Self::parse_pubkey(row.get::<_, &str>("token_account"))?,
+                    owner: Self::parse_pubkey(row.get::<_, &str>("owner"))?,
+                    amount: row.get::<_, i64>("amount").max(0) as u64,
+                    share: row.get("share"),
+                    in_funding_graph: row.get("in_funding_graph"),
+                });
+            }
+        }
+
+        Ok(snapshots)
+    }
 }
@@ -0,0 +1,342 @@
+use bb8::PooledConnection;
+use bb8_redis::RedisConnectionManager;
+use bb8_redis::redis;
+use solana_pubkey::Pubkey;
+use tracing::debug;
+use tracing::error;
+use tracing::warn;
+
+use crate::RedisClientError;
+use crate::Result;
+use crate::config::RedisCoordinationConfig;
+use crate::err_with_loc;
+use crate::storage::redis::RedisPool;
+
+// KEYS: mint lock, worker lock set. ARGV: worker, ttl (ms), mint, acquired_at
+const LOCK_SCRIPT: &str = r#"
+if redis.call('SET', KEYS[1], ARGV[1], 'NX', 'PX', ARGV[2]) then
+    redis.call('ZADD', KEYS[2], ARGV[4], ARGV[3])
+    return 1
+end
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return 1
+end
+return 0
+"#;
+
+// KEYS: mint lock, worker lock set. ARGV: worker, ttl (ms), mint
+const RENEW_SCRIPT: &str = r#"
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    redis.call('PEXPIRE', KEYS[1], ARGV[2])
+    return 1
+end
+redis.call('ZREM', KEYS[2], ARGV[3])
+return 0
+"#;
+
+// KEYS: mint lock, worker lock set. ARGV: worker, mint
+const RELEASE_SCRIPT: &str = r#"
+redis.call('ZREM', KEYS[2], ARGV[2])
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+    return redis.call('DEL', KEYS[1])
+end
+return 0
+"#;
+
+/// Coordinates instances of one service: a registry of live instances kept fresh by heartbeats,
+/// and per-mint locks so only one instance analyzes a given mint at a time
+#[derive(Debug, Clone)]
+pub struct TokenMetadataCoordinator {
+    pub pool: RedisPool,
+    pub config: RedisCoordinationConfig,
+    // Service the registry is scoped to, e.g. `baseer`
+    pub engine: String,
+    // Owner recorded on locks and in the registry, unique per process
+    pub worker: String,
+}
+
+impl TokenMetadataCoordinator {
+    pub fn new(
+        pool: RedisPool,
+        config: RedisCoordinationConfig,
+        engine_name: &str,
+        worker: String,
+    ) -> Self {
+        Self {
+            pool,
+            config,
+            engine: engine_name.to_string(),
+            worker,
+        }
+    }
+
+    pub async fn get_connection(&self) -> Result<PooledConnection<'_, RedisConnectionManager>> {
+        self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_redis_connection: {}", e);
+            err_with_loc!(RedisClientError::GetConnectionError(e))
+        })
+    }
+
+    fn now_ms() -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+
+    fn mint_lock_key(mint: &str) -> String {
+        format!("mint_lock:{}", mint)
+    }
+
+    // Sorted set of mint -> acquired_at (ms) for the locks a worker holds
+    fn worker_locks_key(worker: &str) -> String {
+        format!("mint_worker_locks:{}", worker)
+    }
+
+    // Sorted set of worker -> last heartbeat (ms)
+    fn instances_key(&self) -> String {
+        format!("instances:{}", self.engine)
+    }
+
+    /// Take the analysis lock for a mint. Returns false when another instance holds it;
+    /// a lock this instance already holds counts as taken
+    pub async fn try_lock_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+        let mint = mint.to_string();
+
+        let locked: i64 = redis::Script::new(LOCK_SCRIPT)
+            .key(Self::mint_lock_key(&mint))
+            .key(Self::worker_locks_key(&self.worker))
+            .arg(&self.worker)
+            .arg(self.config.mint_lock_ttl_ms)
+            .arg(&mint)
+            .arg(Self::now_ms())
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_lock_mint_failed::{}: {}", mint, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        Ok(locked == 1)
+    }
+
+    /// Release a mint lock, only if this instance still holds it
+    pub async fn release_mint_lock(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let mint = mint.to_string();
+
+        let _: i64 = redis::Script::new(RELEASE_SCRIPT)
+            .key(Self::mint_lock_key(&mint))
+            .key(Self::worker_locks_key(&self.worker))
+            .arg(&self.worker)
+            .arg(&mint)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_release_mint_lock_failed::{}: {}", mint, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        debug!("mint_lock_released::{}", mint);
+        Ok(())
+    }
+
+    /// Extend the locks this instance holds; locks older than `mint_lock_max_hold_secs` are left to lapse
+    pub async fn renew_mint_locks(&self) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        let worker_locks = Self::worker_locks_key(&self.worker);
+        let cutoff = Self::now_ms() - (self.config.mint_lock_max_hold_secs * 1000) as i64;
+
+        let stale: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(&worker_locks)
+            .arg("-inf")
+            .arg(format!("({}", cutoff))
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_stale_mint_locks_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+        for mint in &stale {
+            warn!("mint_lock_max_hold_reached::{}", mint);
+            let _: i64 = redis::cmd("ZREM")
+                .arg(&worker_locks)
+                .arg(mint)
+                .query_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("redis_drop_stale_mint_lock_failed::{}: {}", mint, e);
+                    err_with_loc!(RedisClientError::RedisError(e))
+                })?;
+        }
+
+        let held: Vec<String> = redis::cmd("ZRANGE")
+            .arg(&worker_locks)
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_held_mint_locks_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        let mut renewed = 0;
+        for mint in held {
+            let extended: i64 = redis::Script::new(RENEW_SCRIPT)
+                .key(Self::mint_lock_key(&mint))
+                .key(&worker_locks)
+                .arg(&self.worker)
+                .arg(self.config.mint_lock_ttl_ms)
+                .arg(&mint)
+                .invoke_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("redis_renew_mint_lock_failed::{}: {}", mint, e);
+                    err_with_loc!(RedisClientError::RedisError(e))
+                })?;
+            if extended == 1 {
+                renewed += 1;
+            } else {
+                warn!("mint_lock_lost::{}", mint);
+            }
+        }
+
+        Ok(renewed)
+    }
+
+    /// Announce this instance in the registry
+    pub async fn heartbeat_instance(&self) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let _: i64 = redis::cmd("ZADD")
+            .arg(self.instances_key())
+            .arg(Self::now_ms())
+            .arg(&self.worker)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_instance_heartbeat_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        Ok(())
+    }
+
+    /// Leave the registry and free held locks, so peers don't wait out the timeout
+    pub async fn deregister_instance(&self) -> Result<()> {
+        let released = self.release_instance_mint_locks(&self.worker).await?;
+        let mut conn = self.get_connection().await?;
+
+        let _: i64 = redis::cmd("ZREM")
+            .arg(self.instances_key())
+            .arg(&self.worker)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_instance_deregister_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        debug!("instance_deregistered::{}::released_locks::{}", self.worker, released);
+        Ok(())
+    }
+
+    /// Instances that heartbeated within `instance_timeout_secs`
+    pub async fn get_live_instances(&self) -> Result<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        let cutoff = Self::now_ms() - (self.config.instance_timeout_secs * 1000) as i64;
+
+        let instances: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(self.instances_key())
+            .arg(cutoff)
+            .arg("+inf")
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_live_instances_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        Ok(instances)
+    }
+
+    /// Remove instances that stopped heartbeating from the registry. Each dead instance is returned
+    /// to exactly one caller, which is then responsible for rebalancing its work
+    pub async fn take_dead_instances(&self) -> Result<Vec<String>> {
+        let mut conn = self.get_connection().await?;
+        let cutoff = Self::now_ms() - (self.config.instance_timeout_secs * 1000) as i64;
+
+        let expired: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(self.instances_key())
+            .arg("-inf")
+            .arg(format!("({}", cutoff))
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_dead_instances_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        let mut dead = Vec::with_capacity(expired.len());
+        for worker in expired {
+            // Only the instance whose ZREM succeeds takes over, the others see 0
+            let removed: i64 = redis::cmd("ZREM")
+                .arg(self.instances_key())
+                .arg(&worker)
+                .query_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("redis_remove_dead_instance_failed::{}: {}", worker, e);
+                    err_with_loc!(RedisClientError::RedisError(e))
+                })?;
+            if removed == 1 {
+                dead.push(worker);
+            }
+        }
+
+        Ok(dead)
+    }
+
+    /// Free every mint lock a worker still holds, so the mints can be picked up again right away
+    pub async fn release_instance_mint_locks(
+        &self,
+        worker: &str,
+    ) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+        let worker_locks = Self::worker_locks_key(worker);
+
+        let held: Vec<String> = redis::cmd("ZRANGE")
+            .arg(&worker_locks)
+            .arg(0)
+            .arg(-1)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_instance_mint_locks_failed::{}: {}", worker, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        let mut released = 0;
+        for mint in held {
+            let deleted: i64 = redis::Script::new(RELEASE_SCRIPT)
+                .key(Self::mint_lock_key(&mint))
+                .key(&worker_locks)
+                .arg(worker)
+                .arg(&mint)
+                .invoke_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("redis_release_instance_mint_lock_failed::{}::{}: {}", worker, mint, e);
+                    err_with_loc!(RedisClientError::RedisError(e))
+                })?;
+            released += deleted as usize;
+        }
+
+        Ok(released)
+    }
+}
@@ -1,12 +1,16 @@
+pub mod coordination;
 pub mod kv;
 pub mod model;
 pub mod queue;
+pub mod stream;
 use std::sync::Arc;
 
 use bb8::Pool;
 use bb8_redis::RedisConnectionManager;
+pub use coordination::TokenMetadataCoordinator;
 pub use kv::TokenMetadataKv;
 pub use queue::TokenMetadataQueue;
+pub use stream::TokenMetadataStream;
 use tokio::sync::RwLock;
 use tracing::info;
 use tracing::instrument;
@@ -16,10 +20,18 @@ use crate::error::Result;
 
 pub type RedisPool = Pool<RedisConnectionManager>;
 
+/// Identifies this process as a stream consumer and lease owner, unique across hosts and restarts
+pub fn worker_id(engine_name: &str) -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| String::from("local"));
+    format!("{}-{}-{}", engine_name, host, std::process::id())
+}
+
 #[derive(Debug, Clone)]
 pub struct RedisClient {
     pub kv: Arc<TokenMetadataKv>,
     pub queue: Arc<TokenMetadataQueue>,
+    pub stream: Arc<TokenMetadataStream>,
+    pub coordinator: Arc<TokenMetadataCoordinator>,
 }
 
 #[instrument(level = "debug", skip(config))]
@@ -36,10 +48,19 @@ pub async fn make_redis_client(
     info!("redis::connection_established");
 
     let kv = Arc::new(TokenMetadataKv::new(pool.clone()));
-    let queue = Arc::new(TokenMetadataQueue::new(pool, pubsub));
+    let stream = Arc::new(TokenMetadataStream::new(pool.clone(), config.streams.clone(), engine_name));
+    let coordinator = Arc::new(TokenMetadataCoordinator::new(
+        pool.clone(),
+        config.coordination.clone(),
+        engine_name,
+        worker_id(engine_name),
+    ));
+    let queue = Arc::new(TokenMetadataQueue::new(pool, pubsub, config.leases.clone(), worker_id(engine_name)));
 
     Ok(Arc::new(RedisClient {
         kv,
         queue,
+        stream,
+        coordinator,
     }))
 }
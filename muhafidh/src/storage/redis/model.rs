@@ -1,6 +1,7 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::model::bundle::BundleAnalysis;
 use crate::model::creator::graph::CreatorConnectionGraph;
 use crate::model::dev::DevName;
 use crate::model::token::TokenMetadata;
@@ -44,6 +45,17 @@ pub struct TokenAnalyzedCache {
     pub updated_at: u64,
     pub node_count: usize,
     pub edge_count: usize,
+    // Total SOL sent straight to the creator across the funding graph
+    #[serde(default)]
+    pub funding_amount_sol: f64,
+    #[serde(default)]
+    pub funding_amount_usd: Option<f64>,
+    // SOL/USD price the USD amounts were derived from
+    #[serde(default)]
+    pub sol_usd_price: Option<f64>,
+    // Early buys of the launch checked for bundling
+    #[serde(default)]
+    pub bundle: Option<BundleAnalysis>,
     pub graph: CreatorConnectionGraph,
 }
 
@@ -59,5 +71,60 @@ pub struct MaxDepthReachedCache {
     pub updated_at: u64,
     pub node_count: usize,
     pub edge_count: usize,
+    #[serde(default)]
+    pub bundle: Option<BundleAnalysis>,
     pub graph: CreatorConnectionGraph,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenNewAthCache {
+    pub mint: String,
+    pub previous_ath: u64,
+    pub ath: u64,
+    pub ath_sol: f64,
+    pub rise_pct: f64,
+    pub market_cap_sol: f64,
+    pub market_cap_usd: Option<f64>,
+    pub at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DevDumpDetectedCache {
+    pub mint: String,
+    pub creator: String,
+    // Creator and clustered wallets that sold
+    pub sellers: Vec<String>,
+    pub bought_amount: u64,
+    pub sold_amount: u64,
+    pub sold_pct: f64,
+    pub sol_received: u64,
+    pub price_before: u64,
+    pub price_after: u64,
+    pub price_impact_pct: Option<f64>,
+    pub launched_at: i64,
+    pub detected_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceAlertTriggeredCache {
+    pub rule_id: i32,
+    pub rule_name: String,
+    pub mint: String,
+    pub kind: String,
+    pub threshold: f64,
+    pub observed: f64,
+    pub price_sol: f64,
+    pub market_cap_sol: f64,
+    pub market_cap_usd: Option<f64>,
+    pub triggered_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InFlightAccount {
+    pub lease_id: String,
+    pub queue: String,
+    pub worker: String,
+    pub mint: Option<String>,
+    pub leased_at: i64,
+    pub expires_at: i64,
+}
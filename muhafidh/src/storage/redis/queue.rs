@@ -1,5 +1,8 @@
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 
 use bb8::PooledConnection;
 use bb8_redis::RedisConnectionManager;
@@ -10,17 +13,63 @@ use serde_json;
 use tokio::sync::RwLock;
 use tracing::debug;
 use tracing::error;
+use tracing::warn;
 
 use crate::RedisClientError;
 use crate::Result;
+use crate::config::RedisLeaseConfig;
 use crate::err_with_loc;
 use crate::model::creator::metadata::CreatorMetadata;
 use crate::storage::redis::RedisPool;
+use crate::storage::redis::model::InFlightAccount;
+
+// Sorted set of lease id -> expiry (ms); lease details live in `account_lease:{id}`
+const ACCOUNT_LEASES: &str = "account_leases";
+
+// KEYS: queue, leases, lease hash, worker lease set. ARGV: expires_at, lease id, worker, leased_at
+const LEASE_SCRIPT: &str = r#"
+local item = redis.call('LPOP', KEYS[1])
+if not item then
+    return false
+end
+redis.call('ZADD', KEYS[2], ARGV[1], ARGV[2])
+redis.call('HSET', KEYS[3], 'queue', KEYS[1], 'payload', item, 'worker', ARGV[3], 'leased_at', ARGV[4])
+redis.call('SADD', KEYS[4], ARGV[2])
+return item
+"#;
+
+// KEYS: leases, lease hash, worker lease set. ARGV: lease id, now
+const REAP_SCRIPT: &str = r#"
+local expires_at = redis.call('ZSCORE', KEYS[1], ARGV[1])
+if not expires_at or tonumber(expires_at) > tonumber(ARGV[2]) then
+    return 0
+end
+redis.call('ZREM', KEYS[1], ARGV[1])
+local lease = redis.call('HMGET', KEYS[2], 'queue', 'payload')
+if lease[1] and lease[2] then
+    redis.call('LPUSH', lease[1], lease[2])
+end
+redis.call('SREM', KEYS[3], ARGV[1])
+redis.call('DEL', KEYS[2])
+return 1
+"#;
+
+/// An account taken off a work queue, owned by this worker until released or expired
+#[derive(Debug, Clone)]
+pub struct AccountLease {
+    pub id: String,
+    // Queue the account is returned to if the lease expires
+    pub queue: String,
+}
 
 #[derive(Clone)]
 pub struct TokenMetadataQueue {
     pub pool: RedisPool,
     pub pubsub: Arc<RwLock<PubSub>>,
+    pub lease_config: RedisLeaseConfig,
+    // Owner recorded on leases, unique per process
+    pub worker: String,
+    lease_counter: Arc<AtomicU64>,
 }
 
 impl fmt::Debug for TokenMetadataQueue {
@@ -31,6 +80,7 @@ impl fmt::Debug for TokenMetadataQueue {
         f.debug_struct("TokenMetadataQueue")
             .field("pool", &self.pool)
             .field("pubsub", &format_args!("Arc<RwLock<PubSub@{:p}>>", Arc::as_ptr(&self.pubsub)))
+            .field("worker", &self.worker)
             .finish()
     }
 }
@@ -39,10 +89,15 @@ impl TokenMetadataQueue {
     pub fn new(
         pool: RedisPool,
         pubsub: Arc<RwLock<PubSub>>,
+        lease_config: RedisLeaseConfig,
+        worker: String,
     ) -> Self {
         Self {
             pool,
             pubsub,
+            lease_config,
+            worker,
+            lease_counter: Arc::new(AtomicU64::new(0)),
         }
     }
 
@@ -126,55 +181,243 @@ impl TokenMetadataQueue {
         Ok(())
     }
 
-    // Get the next account from the failed list
+    fn now_ms() -> i64 {
+        chrono::Utc::now().timestamp_millis()
+    }
+
+    fn lease_key(lease_id: &str) -> String {
+        format!("account_lease:{}", lease_id)
+    }
+
+    fn worker_leases_key(worker: &str) -> String {
+        format!("account_worker_leases:{}", worker)
+    }
+
+    // Lease ids are `{worker}:{counter}`, so the owner is known without reading the lease hash
+    fn lease_worker(lease_id: &str) -> &str {
+        lease_id.rsplit_once(':').map(|(worker, _)| worker).unwrap_or(lease_id)
+    }
+
+    // Pop the head of a queue and lease it to this worker in one step, so a crash can't lose it
+    async fn lease_next_account(
+        &self,
+        queue: &str,
+    ) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        let mut conn = self.get_connection().await?;
+
+        let lease_id = format!("{}:{}", self.worker, self.lease_counter.fetch_add(1, Ordering::Relaxed));
+        let now = Self::now_ms();
+        let expires_at = now + (self.lease_config.visibility_timeout_secs * 1000) as i64;
+
+        let json: Option<String> = redis::Script::new(LEASE_SCRIPT)
+            .key(queue)
+            .key(ACCOUNT_LEASES)
+            .key(Self::lease_key(&lease_id))
+            .key(Self::worker_leases_key(&self.worker))
+            .arg(expires_at)
+            .arg(&lease_id)
+            .arg(&self.worker)
+            .arg(now)
+            .invoke_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_lease_next_account_failed::{}: {}", queue, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+        drop(conn);
+
+        let Some(json) = json else {
+            return Ok(None);
+        };
+        let lease = AccountLease {
+            id: lease_id,
+            queue: queue.to_string(),
+        };
+        match serde_json::from_str(&json) {
+            Ok(account) => Ok(Some((lease, account))),
+            Err(e) => {
+                // Nothing can ever process it, drop the lease instead of returning it to the queue
+                error!("deserialize_leased_account_failed::{}: {}", queue, e);
+                self.release_account_lease(&lease).await?;
+                Err(err_with_loc!(RedisClientError::DeserializeError(e)))
+            },
+        }
+    }
+
+    // Lease the next account from the failed list
     // We prioritize failed accounts over unprocessed ones for retry
-    pub async fn get_next_failed_account(&self) -> Result<Option<CreatorMetadata>> {
+    pub async fn lease_next_failed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        self.lease_next_account("failed_accounts").await
+    }
+
+    // Lease the next account from the unprocessed list
+    pub async fn lease_next_unprocessed_account(&self) -> Result<Option<(AccountLease, CreatorMetadata)>> {
+        self.lease_next_account("unprocessed_accounts").await
+    }
+
+    // Drop a lease once its account is handled, whether it succeeded or was requeued as failed
+    pub async fn release_account_lease(
+        &self,
+        lease: &AccountLease,
+    ) -> Result<()> {
         let mut conn = self.get_connection().await?;
 
-        let json: Option<String> = redis::cmd("LPOP")
-            .arg("failed_accounts")
+        let _: () = redis::pipe()
+            .atomic()
+            .cmd("ZREM")
+            .arg(ACCOUNT_LEASES)
+            .arg(&lease.id)
+            .ignore()
+            .cmd("SREM")
+            .arg(Self::worker_leases_key(&self.worker))
+            .arg(&lease.id)
+            .ignore()
+            .cmd("DEL")
+            .arg(Self::lease_key(&lease.id))
+            .ignore()
             .query_async(&mut *conn)
             .await
             .map_err(|e| {
-                error!("redis_get_next_failed_account_failed: {}", e);
+                error!("redis_release_account_lease_failed::{}: {}", lease.id, e);
                 err_with_loc!(RedisClientError::RedisError(e))
             })?;
 
-        match json {
-            Some(json) => {
-                let account = serde_json::from_str(&json).map_err(|e| {
-                    error!("deserialize_failed_account_failed: {}", e);
-                    err_with_loc!(RedisClientError::DeserializeError(e))
+        debug!("redis_account_lease_released::{}", lease.id);
+        Ok(())
+    }
+
+    // Push the expiry of every lease this worker holds one visibility timeout ahead
+    pub async fn heartbeat_account_leases(&self) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+
+        let lease_ids: Vec<String> = redis::cmd("SMEMBERS")
+            .arg(Self::worker_leases_key(&self.worker))
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_worker_leases_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+        if lease_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let expires_at = Self::now_ms() + (self.lease_config.visibility_timeout_secs * 1000) as i64;
+        let mut pipe = redis::pipe();
+        for lease_id in &lease_ids {
+            // XX: a lease the reaper already returned stays gone
+            pipe.cmd("ZADD")
+                .arg(ACCOUNT_LEASES)
+                .arg("XX")
+                .arg(expires_at)
+                .arg(lease_id)
+                .ignore();
+        }
+        let _: () = pipe.query_async(&mut *conn).await.map_err(|e| {
+            error!("redis_heartbeat_account_leases_failed: {}", e);
+            err_with_loc!(RedisClientError::RedisError(e))
+        })?;
+
+        Ok(lease_ids.len())
+    }
+
+    // Return leases whose worker stopped heartbeating to the front of their queue
+    pub async fn reap_expired_account_leases(&self) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+
+        let now = Self::now_ms();
+        let expired: Vec<String> = redis::cmd("ZRANGEBYSCORE")
+            .arg(ACCOUNT_LEASES)
+            .arg("-inf")
+            .arg(now)
+            .arg("LIMIT")
+            .arg(0)
+            .arg(100)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_expired_account_leases_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        let mut reaped = 0;
+        for lease_id in expired {
+            // The script re-checks expiry, so a concurrent heartbeat or another reaper wins cleanly
+            let returned: i64 = redis::Script::new(REAP_SCRIPT)
+                .key(ACCOUNT_LEASES)
+                .key(Self::lease_key(&lease_id))
+                .key(Self::worker_leases_key(Self::lease_worker(&lease_id)))
+                .arg(&lease_id)
+                .arg(now)
+                .invoke_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("redis_reap_account_lease_failed::{}: {}", lease_id, e);
+                    err_with_loc!(RedisClientError::RedisError(e))
                 })?;
-                Ok(Some(account))
-            },
-            None => Ok(None),
+            if returned == 1 {
+                warn!("account_lease_expired::returned_to_queue::{}", lease_id);
+                reaped += 1;
+            }
         }
+
+        Ok(reaped)
     }
 
-    // Get the next account from the unprocessed list
-    pub async fn get_next_unprocessed_account(&self) -> Result<Option<CreatorMetadata>> {
+    // Accounts currently leased by any worker, for operators
+    pub async fn get_in_flight_accounts(&self) -> Result<Vec<InFlightAccount>> {
         let mut conn = self.get_connection().await?;
 
-        let json: Option<String> = redis::cmd("LPOP")
-            .arg("unprocessed_accounts")
+        let leases: Vec<(String, f64)> = redis::cmd("ZRANGE")
+            .arg(ACCOUNT_LEASES)
+            .arg(0)
+            .arg(-1)
+            .arg("WITHSCORES")
             .query_async(&mut *conn)
             .await
             .map_err(|e| {
-                error!("redis_get_next_unprocessed_account_failed: {}", e);
+                error!("redis_get_account_leases_failed: {}", e);
                 err_with_loc!(RedisClientError::RedisError(e))
             })?;
 
-        match json {
-            Some(json) => {
-                let account = serde_json::from_str(&json).map_err(|e| {
-                    error!("deserialize_unprocessed_account_failed: {}", e);
-                    err_with_loc!(RedisClientError::DeserializeError(e))
+        let mut in_flight = Vec::with_capacity(leases.len());
+        for (lease_id, expires_at) in leases {
+            let fields: HashMap<String, String> = redis::cmd("HGETALL")
+                .arg(Self::lease_key(&lease_id))
+                .query_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("redis_get_account_lease_failed::{}: {}", lease_id, e);
+                    err_with_loc!(RedisClientError::RedisError(e))
                 })?;
-                Ok(Some(account))
-            },
-            None => Ok(None),
+            let mint = fields
+                .get("payload")
+                .and_then(|payload| serde_json::from_str::<serde_json::Value>(payload).ok())
+                .and_then(|payload| payload.get("mint").and_then(|mint| mint.as_str()).map(String::from));
+            in_flight.push(InFlightAccount {
+                lease_id,
+                queue: fields.get("queue").cloned().unwrap_or_default(),
+                worker: fields.get("worker").cloned().unwrap_or_default(),
+                mint,
+                leased_at: fields.get("leased_at").and_then(|at| at.parse().ok()).unwrap_or_default(),
+                expires_at: expires_at as i64,
+            });
         }
+
+        Ok(in_flight)
+    }
+
+    pub async fn get_in_flight_account_count(&self) -> Result<usize> {
+        let mut conn = self.get_connection().await?;
+
+        redis::cmd("ZCARD")
+            .arg(ACCOUNT_LEASES)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_get_in_flight_account_count_failed: {}", e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })
     }
 
     // Get counts of accounts in the pending queues
@@ -0,0 +1,306 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use bb8::PooledConnection;
+use bb8_redis::RedisConnectionManager;
+use bb8_redis::redis;
+use redis::streams::StreamAutoClaimReply;
+use redis::streams::StreamId;
+use redis::streams::StreamReadReply;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+use tracing::error;
+use tracing::warn;
+
+use crate::RedisClientError;
+use crate::Result;
+use crate::config::RedisStreamConfig;
+use crate::err_with_loc;
+use crate::storage::redis::RedisPool;
+use crate::storage::redis::worker_id;
+
+/// An event read from a stream, to be acknowledged once handled
+#[derive(Debug, Clone)]
+pub struct StreamEvent<T> {
+    pub id: String,
+    pub payload: T,
+}
+
+/// Durable event delivery over Redis Streams: every service reads through its own consumer group,
+/// acknowledges what it handled, and reclaims entries a crashed consumer left pending
+#[derive(Debug, Clone)]
+pub struct TokenMetadataStream {
+    pub pool: RedisPool,
+    pub config: RedisStreamConfig,
+    // Consumer group of this service, e.g. `baseer`
+    pub group: String,
+    // Unique per process so a restarted instance reclaims instead of inheriting entries
+    pub consumer: String,
+}
+
+impl TokenMetadataStream {
+    pub fn new(
+        pool: RedisPool,
+        config: RedisStreamConfig,
+        engine_name: &str,
+    ) -> Self {
+        Self {
+            pool,
+            config,
+            group: engine_name.to_string(),
+            consumer: worker_id(engine_name),
+        }
+    }
+
+    pub fn stream_key(channel: &str) -> String {
+        format!("stream:{}", channel)
+    }
+
+    pub async fn get_connection(&self) -> Result<PooledConnection<'_, RedisConnectionManager>> {
+        self.pool.get().await.map_err(|e| {
+            error!("failed_to_get_redis_connection: {}", e);
+            err_with_loc!(RedisClientError::GetConnectionError(e))
+        })
+    }
+
+    /// Append an event to the channel's stream, trimmed to about `max_len` entries,
+    /// and fan it out over pubsub when enabled
+    pub async fn publish<T: Serialize + Send>(
+        &self,
+        channel: &str,
+        value: &T,
+    ) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+        let payload = serde_json::to_string(value).map_err(|e| {
+            error!("serialize_stream_event_failed::{}: {}", channel, e);
+            err_with_loc!(RedisClientError::SerializeError(e))
+        })?;
+
+        let id: String = redis::cmd("XADD")
+            .arg(Self::stream_key(channel))
+            .arg("MAXLEN")
+            .arg("~")
+            .arg(self.config.max_len)
+            .arg("*")
+            .arg("payload")
+            .arg(&payload)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_xadd_failed::{}: {}", channel, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        if self.config.pubsub_fanout {
+            let _: () = redis::cmd("PUBLISH")
+                .arg(channel)
+                .arg(&payload)
+                .query_async(&mut *conn)
+                .await
+                .map_err(|e| {
+                    error!("redis_publish_failed::{}: {}", channel, e);
+                    err_with_loc!(RedisClientError::RedisError(e))
+                })?;
+        }
+
+        debug!("redis_stream_publish_done::{}::id::{}", channel, id);
+        Ok(())
+    }
+
+    /// Create this service's consumer group, starting from the oldest retained entry
+    pub async fn ensure_group(
+        &self,
+        channel: &str,
+    ) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let result: redis::RedisResult<()> = redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg(Self::stream_key(channel))
+            .arg(&self.group)
+            .arg("0")
+            .arg("MKSTREAM")
+            .query_async(&mut *conn)
+            .await;
+
+        match result {
+            Ok(()) => {
+                debug!("redis_stream_group_created::{}::group::{}", channel, self.group);
+                Ok(())
+            },
+            Err(e) if e.code() == Some("BUSYGROUP") => Ok(()),
+            Err(e) => {
+                error!("redis_xgroup_create_failed::{}: {}", channel, e);
+                Err(err_with_loc!(RedisClientError::RedisError(e)))
+            },
+        }
+    }
+
+    // Entries with a missing or malformed payload are acknowledged and dropped so they can't wedge the group
+    async fn decode<T: DeserializeOwned>(
+        &self,
+        channel: &str,
+        entries: Vec<StreamId>,
+    ) -> Vec<StreamEvent<T>> {
+        let mut events = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let payload = entry
+                .get::<String>("payload")
+                .and_then(|payload| serde_json::from_str::<T>(&payload).ok());
+            match payload {
+                Some(payload) => events.push(StreamEvent {
+                    id: entry.id,
+                    payload,
+                }),
+                None => {
+                    warn!("malformed_stream_entry::{}::id::{}", channel, entry.id);
+                    if let Err(e) = self.ack(channel, &entry.id).await {
+                        error!("ack_malformed_stream_entry_failed::{}::id::{}::error::{}", channel, entry.id, e);
+                    }
+                },
+            }
+        }
+        events
+    }
+
+    /// Read new entries for this consumer, blocking up to `block_ms` when there are none
+    pub async fn read<T: DeserializeOwned>(
+        &self,
+        channel: &str,
+    ) -> Result<Vec<StreamEvent<T>>> {
+        let mut conn = self.get_connection().await?;
+
+        let reply: Option<StreamReadReply> = redis::cmd("XREADGROUP")
+            .arg("GROUP")
+            .arg(&self.group)
+            .arg(&self.consumer)
+            .arg("COUNT")
+            .arg(self.config.batch_size)
+            .arg("BLOCK")
+            .arg(self.config.block_ms)
+            .arg("STREAMS")
+            .arg(Self::stream_key(channel))
+            .arg(">")
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_xreadgroup_failed::{}: {}", channel, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+        drop(conn);
+
+        let entries = reply
+            .map(|reply| reply.keys.into_iter().flat_map(|key| key.ids).collect())
+            .unwrap_or_default();
+        Ok(self.decode(channel, entries).await)
+    }
+
+    /// Take over entries other consumers of the group left unacknowledged for `claim_idle_ms`
+    pub async fn claim_stale<T: DeserializeOwned>(
+        &self,
+        channel: &str,
+    ) -> Result<Vec<StreamEvent<T>>> {
+        let mut conn = self.get_connection().await?;
+
+        let reply: StreamAutoClaimReply = redis::cmd("XAUTOCLAIM")
+            .arg(Self::stream_key(channel))
+            .arg(&self.group)
+            .arg(&self.consumer)
+            .arg(self.config.claim_idle_ms)
+            .arg("0-0")
+            .arg("COUNT")
+            .arg(self.config.batch_size)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_xautoclaim_failed::{}: {}", channel, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+        drop(conn);
+
+        if !reply.claimed.is_empty() {
+            warn!("redis_stream_entries_reclaimed::{}::count::{}", channel, reply.claimed.len());
+        }
+        Ok(self.decode(channel, reply.claimed).await)
+    }
+
+    /// Next batch for this consumer: entries left stale in the group once every `claim_interval_secs`
+    /// (starting with the first call), new entries otherwise. Claims run between reads so a blocking
+    /// read is never abandoned after Redis already delivered to it
+    pub async fn next_batch<T: DeserializeOwned>(
+        &self,
+        channel: &str,
+        last_claim: &mut Option<Instant>,
+    ) -> Result<Vec<StreamEvent<T>>> {
+        let claim_interval = Duration::from_secs(self.config.claim_interval_secs);
+        if last_claim.is_none_or(|at| at.elapsed() >= claim_interval) {
+            *last_claim = Some(Instant::now());
+            return self.claim_stale(channel).await;
+        }
+        self.read(channel).await
+    }
+
+    /// Drop a departed consumer from the group once nothing is pending for it anymore, since
+    /// deleting a consumer discards its pending entries. Returns whether it was removed
+    pub async fn remove_idle_consumer(
+        &self,
+        channel: &str,
+        consumer: &str,
+    ) -> Result<bool> {
+        let mut conn = self.get_connection().await?;
+
+        let pending: Vec<redis::Value> = redis::cmd("XPENDING")
+            .arg(Self::stream_key(channel))
+            .arg(&self.group)
+            .arg("-")
+            .arg("+")
+            .arg(1)
+            .arg(consumer)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_xpending_failed::{}::consumer::{}: {}", channel, consumer, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+        if !pending.is_empty() {
+            return Ok(false);
+        }
+
+        let _: i64 = redis::cmd("XGROUP")
+            .arg("DELCONSUMER")
+            .arg(Self::stream_key(channel))
+            .arg(&self.group)
+            .arg(consumer)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_xgroup_delconsumer_failed::{}::consumer::{}: {}", channel, consumer, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        debug!("redis_stream_consumer_removed::{}::consumer::{}", channel, consumer);
+        Ok(true)
+    }
+
+    pub async fn ack(
+        &self,
+        channel: &str,
+        id: &str,
+    ) -> Result<()> {
+        let mut conn = self.get_connection().await?;
+
+        let _: i64 = redis::cmd("XACK")
+            .arg(Self::stream_key(channel))
+            .arg(&self.group)
+            .arg(id)
+            .query_async(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("redis_xack_failed::{}::id::{}: {}", channel, id, e);
+                err_with_loc!(RedisClientError::RedisError(e))
+            })?;
+
+        Ok(())
+    }
+}